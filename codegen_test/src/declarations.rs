@@ -6,76 +6,61 @@ pub(super) fn derives(derives: Vec<&str>) -> TypeDeclarationConfig {
         .map(|s| s.to_string())
         .collect::<Vec<_>>()
         .join(", ");
-    let s = Box::leak(format!("#[derive({})]", derives).into_boxed_str());
-    TypeDeclarationConfig::RustAttribute(s)
+    TypeDeclarationConfig::rust_attribute(format!("#[derive({})]", derives))
 }
 
 pub fn make_declarations() -> Declarations {
     let mut d = Declarations::new();
-    d.add_config(DeclarationsConfig::HackNamespace("Unigraph"));
+    d.add_config(DeclarationsConfig::hack_namespace("Unigraph"));
 
     let op_inline = d
-        .add(TypeDeclaration {
-            name: "OpInline",
-            docs: "",
-            config: vec![derives(vec!["serde::Serialize", "serde::Deserialize"])],
-            generic_params: vec![],
-            value: DeclarationValue::TStruct(TStruct {
-                fields: vec![StructField {
-                    name: "graphs",
-                    docs: "",
-                    field_type: StructFieldType::TVec(TVec::TPrimitive(TPrimitive::String)),
-                    config: vec![],
-                }],
-            }),
-        })
+        .add(
+            TypeDeclaration::new(
+                "OpInline",
+                DeclarationValue::TStruct(TStruct {
+                    fields: vec![StructField::new(
+                        "graphs",
+                        StructFieldType::TVec(TVec::TPrimitive(TPrimitive::String)),
+                    )],
+                }),
+            )
+            .config(derives(vec!["serde::Serialize", "serde::Deserialize"])),
+        )
         .primitive();
 
     let op_fetch = d
-        .add(TypeDeclaration {
-            name: "OpFetch",
-            docs: "",
-            config: vec![derives(vec!["serde::Serialize", "serde::Deserialize"])],
-            generic_params: vec![],
-            value: DeclarationValue::TStruct(TStruct {
-                fields: vec![
-                    StructField {
-                        name: "timeline_key",
-                        docs: "",
-                        field_type: StructFieldType::TPrimitive(TPrimitive::Ti64),
-                        config: vec![],
-                    },
-                    StructField {
-                        name: "graph_ids",
-                        docs: "",
-                        field_type: StructFieldType::TVec(TVec::TPrimitive(TPrimitive::Ti64)),
-                        config: vec![],
-                    },
-                ],
-            }),
-        })
+        .add(
+            TypeDeclaration::new(
+                "OpFetch",
+                DeclarationValue::TStruct(TStruct {
+                    fields: vec![
+                        StructField::new(
+                            "timeline_key",
+                            StructFieldType::TPrimitive(TPrimitive::Ti64),
+                        ),
+                        StructField::new(
+                            "graph_ids",
+                            StructFieldType::TVec(TVec::TPrimitive(TPrimitive::Ti64)),
+                        ),
+                    ],
+                }),
+            )
+            .config(derives(vec!["serde::Serialize", "serde::Deserialize"])),
+        )
         .primitive();
 
-    d.add(TypeDeclaration {
-        name: "OperationsEnum",
-        docs: "",
-        config: vec![derives(vec!["serde::Serialize", "serde::Deserialize"])],
-        generic_params: vec![],
-        value: DeclarationValue::TEnum(TEnum {
-            variants: vec![
-                EnumVariant {
-                    name: "OpInline",
-                    docs: "",
-                    variant_type: EnumVariantType::TPrimitive(op_inline),
-                },
-                EnumVariant {
-                    name: "OpFetch",
-                    docs: "",
-                    variant_type: EnumVariantType::TPrimitive(op_fetch),
-                },
-            ],
-        }),
-    });
+    d.add(
+        TypeDeclaration::new(
+            "OperationsEnum",
+            DeclarationValue::TEnum(TEnum {
+                variants: vec![
+                    EnumVariant::new("OpInline", EnumVariantType::TPrimitive(op_inline)),
+                    EnumVariant::new("OpFetch", EnumVariantType::TPrimitive(op_fetch)),
+                ],
+            }),
+        )
+        .config(derives(vec!["serde::Serialize", "serde::Deserialize"])),
+    );
 
     d
 }
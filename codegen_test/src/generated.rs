@@ -1,5 +1,3 @@
-
-
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct OpInline {
     pub graphs: Vec<String>,
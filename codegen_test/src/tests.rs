@@ -1 +1,310 @@
+use gull::prelude::*;
+use std::collections::{BTreeMap, BTreeSet};
 
+/// A single node in the graph
+#[derive(Gull)]
+#[allow(dead_code)]
+struct Node<T: Ord> {
+    /// Node identifier
+    id: i64,
+    #[serde(rename = "displayName")]
+    name: String,
+    tags: BTreeSet<T>,
+    parent: Option<Box<Node<T>>>,
+    #[serde(skip)]
+    cache: Vec<u8>,
+}
+
+#[derive(Gull)]
+#[allow(dead_code)]
+#[serde(rename_all = "snake_case")]
+enum Status {
+    NotStarted,
+    InProgress,
+    Done,
+}
+
+/// Operation is a single unit of transformation logic
+#[derive(Gull)]
+#[allow(dead_code)]
+enum Operation {
+    /// Fetch items by their IDs
+    Fetch {
+        items: Vec<i64>,
+    },
+    Store(BTreeMap<String, Status>),
+    #[deprecated(since = "0.2.0", note = "Graphs are dropped automatically")]
+    Drop(bool),
+}
+
+#[derive(Gull)]
+#[allow(dead_code)]
+struct Frame(String, Option<u32>);
+
+#[derive(Gull)]
+#[allow(dead_code)]
+struct Page<T: Ord = String> {
+    nodes: Vec<Node<T>>,
+    #[deprecated = "Use the status of every node"]
+    status: Option<Status>,
+}
+
+/// Serialized as its discriminant
+#[derive(Gull, serde_repr::Serialize_repr)]
+#[allow(dead_code)]
+#[repr(i32)]
+enum Level {
+    Low = -1,
+    Medium,
+    #[deprecated]
+    High = 10,
+}
+
+#[derive(Gull)]
+#[allow(dead_code)]
+struct Audit {
+    created_at: i64,
+}
+
+#[derive(Gull)]
+#[allow(dead_code)]
+struct Comment {
+    text: String,
+    #[serde(flatten)]
+    audit: Audit,
+}
+
+fn make_declarations() -> Declarations {
+    let mut d = Declarations::new();
+    d.add_config(DeclarationsConfig::hack_namespace("Derived"));
+    d.add_derived::<Node<i64>>();
+    d.add_derived::<Status>();
+    d.add_derived::<Operation>();
+    d.add_derived::<Frame>();
+    d.add_derived::<Page>();
+    d
+}
+
+#[test]
+fn derived_flow_test() -> anyhow::Result<()> {
+    k9::snapshot!(
+        make_declarations().codegen_flow()?,
+        r#"
+
+// A single node in the graph
+export type Node<T> = {
+    // Node identifier
+    'id': number,
+    'displayName': string,
+    'tags': Array<T>,
+    'parent'?: Node<T>,
+};
+
+export type Status = "not_started" | "in_progress" | "done";
+
+export enum StatusEnum {NotStarted = "not_started", InProgress = "in_progress", Done = "done"};
+
+// Operation is a single unit of transformation logic
+export type OperationType = "Fetch" | "Store" | "Drop";
+
+export type Operation = {
+    // Fetch items by their IDs
+    'Fetch'?:  {
+        'items': Array<number>,
+    },
+    'Store'?: {[key: string]: Status},
+    /** @deprecated Graphs are dropped automatically (since 0.2.0) */
+    'Drop'?: boolean,
+};
+
+export function getOperationType(x: Operation): OperationType {
+    if (x.Fetch !== undefined) {
+        return 'Fetch';
+    }
+    if (x.Store !== undefined) {
+        return 'Store';
+    }
+    if (x.Drop !== undefined) {
+        return 'Drop';
+    }
+    throw new Error('No variant of Operation is set');
+}
+
+export function operationFetch(value: {
+    'items': Array<number>,
+}): Operation {
+    return {'Fetch': value};
+}
+
+export function operationStore(value: {[key: string]: Status}): Operation {
+    return {'Store': value};
+}
+
+/** @deprecated Graphs are dropped automatically (since 0.2.0) */
+export function operationDrop(value: boolean): Operation {
+    return {'Drop': value};
+}
+
+export function matchOperation<TResult>(
+    x: Operation,
+    cases: {|
+        'Fetch': (value: {
+            'items': Array<number>,
+        }) => TResult,
+        'Store': (value: {[key: string]: Status}) => TResult,
+        'Drop': (value: boolean) => TResult,
+    |},
+): TResult {
+    if (x.Fetch !== undefined) {
+        return cases.Fetch(x.Fetch);
+    }
+    if (x.Store !== undefined) {
+        return cases.Store(x.Store);
+    }
+    if (x.Drop !== undefined) {
+        return cases.Drop(x.Drop);
+    }
+    throw new Error('No variant of Operation is set');
+}
+
+export type Frame = [string, ?number];
+
+export type Page<T = string> = {
+    'nodes': Array<Node<T>>,
+    /** @deprecated Use the status of every node */
+    'status'?: Status,
+};
+
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn derived_hack_test() -> anyhow::Result<()> {
+    k9::snapshot!(
+        make_declarations().codegen_hack()?,
+        r#"
+<?hh
+
+// A single node in the graph
+type DerivedNode<T> = shape(
+    // Node identifier
+    'id' => int,
+    'displayName' => string,
+    'tags' => keyset<T>,
+    ?'parent' => DerivedNode<T>,
+);
+
+enum DerivedStatus: string as string {
+    NOT_STARTED = "not_started";
+    IN_PROGRESS = "in_progress";
+    DONE = "done";
+}
+
+// Operation is a single unit of transformation logic
+
+enum DerivedOperationType: string as string {
+    FETCH = "Fetch";
+    STORE = "Store";
+    DROP = "Drop";
+}
+
+type DerivedOperation = shape(
+    // Fetch items by their IDs
+    ?'Fetch' =>  shape(
+        'items' => vec<int>,
+    ),
+    ?'Store' => dict<arraykey, DerivedStatus>,
+    // @deprecated Graphs are dropped automatically (since 0.2.0)
+    ?'Drop' => bool,
+);
+
+function DerivedOperation_getType(DerivedOperation $x): DerivedOperationType {
+    if (Shapes::keyExists($x, 'Fetch')) {
+        return DerivedOperationType::FETCH;
+    }
+    if (Shapes::keyExists($x, 'Store')) {
+        return DerivedOperationType::STORE;
+    }
+    if (Shapes::keyExists($x, 'Drop')) {
+        return DerivedOperationType::DROP;
+    }
+    invariant_violation('No variant of DerivedOperation is set');
+}
+
+function DerivedOperation_getFetch(DerivedOperation $x): ?shape(
+    'items' => vec<int>,
+) {
+    return Shapes::idx($x, 'Fetch');
+}
+
+function DerivedOperation_getStore(DerivedOperation $x): ?dict<arraykey, DerivedStatus> {
+    return Shapes::idx($x, 'Store');
+}
+
+<<__Deprecated('Graphs are dropped automatically (since 0.2.0)')>>
+function DerivedOperation_getDrop(DerivedOperation $x): ?bool {
+    return Shapes::idx($x, 'Drop');
+}
+
+type DerivedFrame = (string, ?int);
+
+type DerivedPage<T> = shape(
+    'nodes' => vec<DerivedNode<T>>,
+    // @deprecated Use the status of every node
+    ?'status' => DerivedStatus,
+);
+
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn derived_integer_enum_test() -> anyhow::Result<()> {
+    let mut d = Declarations::new();
+    d.add_derived::<Level>();
+    k9::snapshot!(
+        d.codegen_rust()?,
+        "
+
+
+/// Serialized as its discriminant
+#[derive(serde_repr::Serialize_repr, serde_repr::Deserialize_repr)]
+#[repr(i32)]
+pub enum Level {
+    Low = -1,
+    Medium = 0,
+    #[deprecated]
+    High = 10,
+}
+
+"
+    );
+    Ok(())
+}
+
+#[test]
+fn derived_flatten_test() -> anyhow::Result<()> {
+    let mut d = Declarations::new();
+    d.add_derived::<Audit>();
+    d.add_derived::<Comment>();
+    k9::snapshot!(
+        d.codegen_hack()?,
+        r#"
+<?hh
+
+type Audit = shape(
+    'created_at' => int,
+);
+
+type Comment = shape(
+    'text' => string,
+    'created_at' => int,
+);
+
+"#
+    );
+    Ok(())
+}
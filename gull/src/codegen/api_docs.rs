@@ -0,0 +1,619 @@
+use super::docs::adjust_indentation;
+use super::Codegen;
+use crate::prelude::*;
+use anyhow::Result;
+
+const TITLE: &str = "API reference";
+
+// Markdown API reference. Every type gets its docs, a table of its fields or
+// variants with links to referenced types, its signature in every language
+// and an example JSON value.
+pub struct MarkdownCodegen;
+
+// Same as `MarkdownCodegen`, rendered as a single self contained HTML page
+pub struct HtmlCodegen;
+
+impl Codegen for MarkdownCodegen {
+    fn gen_declarations(declarations: &Declarations) -> Result<String> {
+        let blocks = gen_blocks(declarations)?;
+        Ok(blocks
+            .iter()
+            .map(markdown::block)
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+}
+
+impl Codegen for HtmlCodegen {
+    fn gen_declarations(declarations: &Declarations) -> Result<String> {
+        let blocks = gen_blocks(declarations)?;
+        Ok(html::page(&blocks))
+    }
+}
+
+// Document model shared by both renderers
+enum Inline {
+    Text(String),
+    Code(String),
+    Link { text: String, id: String },
+}
+
+enum Block {
+    Heading {
+        level: usize,
+        id: String,
+        text: String,
+    },
+    // Free form docs, passed through as is in markdown
+    Docs(String),
+    Paragraph(Vec<Inline>),
+    // Bold title of the following block
+    Label(&'static str),
+    Table {
+        header: Vec<&'static str>,
+        rows: Vec<Vec<Vec<Inline>>>,
+    },
+    Code {
+        language: &'static str,
+        code: String,
+    },
+    // (nesting level, item)
+    List(Vec<(usize, Inline)>),
+}
+
+fn gen_blocks(declarations: &Declarations) -> Result<Vec<Block>> {
+    let mut toc = vec![];
+    let mut body = vec![];
+    // Types following a docs block are nested under its section
+    let mut in_section = false;
+
+    for declaration in declarations.ordered() {
+        match &declaration.value {
+            DeclarationValue::Docs => {
+                let (title, docs) = split_section_docs(&declaration.docs);
+                if let Some(title) = title {
+                    let id = slug(&title);
+                    toc.push((
+                        0,
+                        Inline::Link {
+                            text: title.clone(),
+                            id: id.clone(),
+                        },
+                    ));
+                    body.push(Block::Heading {
+                        level: 2,
+                        id,
+                        text: title,
+                    });
+                    in_section = true;
+                }
+                if !docs.is_empty() {
+                    body.push(Block::Docs(docs));
+                }
+            }
+            DeclarationValue::CodeBlock(_) => {}
+            _ => {
+                let id = slug(&declaration.name);
+                toc.push((
+                    in_section as usize,
+                    Inline::Link {
+                        text: declaration.name.to_string(),
+                        id: id.clone(),
+                    },
+                ));
+                body.push(Block::Heading {
+                    level: 3,
+                    id,
+                    text: declaration.name.to_string(),
+                });
+                body.extend(gen_declaration(declarations, declaration)?);
+            }
+        }
+    }
+
+    let mut blocks = vec![Block::Heading {
+        level: 1,
+        id: slug(TITLE),
+        text: TITLE.to_string(),
+    }];
+    if !toc.is_empty() {
+        blocks.push(Block::List(toc));
+    }
+    blocks.extend(body);
+    Ok(blocks)
+}
+
+// Docs blocks are usually framed with `=====` lines. The first line of text
+// becomes the section title.
+fn split_section_docs(docs: &str) -> (Option<String>, String) {
+    let docs = adjust_indentation(docs);
+    let mut lines = docs
+        .lines()
+        .filter(|l| !is_separator(l))
+        .skip_while(|l| l.trim().is_empty());
+    let title = lines.next().map(|l| l.trim().to_string());
+    let rest = lines.collect::<Vec<_>>().join("\n").trim().to_string();
+    (title, rest)
+}
+
+fn is_separator(line: &str) -> bool {
+    let line = line.trim();
+    !line.is_empty() && line.chars().all(|c| matches!(c, '=' | '-' | '*' | '#'))
+}
+
+fn slug(text: &str) -> String {
+    text.trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+fn gen_declaration(
+    declarations: &Declarations,
+    declaration: &TypeDeclaration,
+) -> Result<Vec<Block>> {
+    let mut blocks = vec![];
+
+    if let Some(d) = declaration.deprecation() {
+        let text = match d.note() {
+            Some(note) => format!("Deprecated: {}", note),
+            None => "Deprecated".to_string(),
+        };
+        blocks.push(Block::Paragraph(vec![Inline::Text(text)]));
+    }
+
+    if !declaration.docs.trim().is_empty() {
+        blocks.push(Block::Docs(adjust_indentation(&declaration.docs)));
+    }
+
+    match &declaration.value {
+        DeclarationValue::TStruct(s) => {
+            blocks.push(Block::Table {
+                header: vec!["Field", "Type", "Description"],
+                rows: s.fields.iter().map(gen_field_row).collect(),
+            });
+        }
+        DeclarationValue::TEnum(e) => {
+            blocks.push(Block::Table {
+                header: vec!["Variant", "Type", "Description"],
+                rows: e
+                    .variants
+                    .iter()
+                    .map(|v| {
+                        let ty = match &v.variant_type {
+                            EnumVariantType::TStruct(s) => gen_struct_type(s),
+                            EnumVariantType::TPrimitive(p) => gen_primitive(p),
+                        };
+                        vec![
+                            vec![Inline::Code(v.name.to_string())],
+                            ty,
+                            gen_cell_docs(&v.docs, v.deprecation()),
+                        ]
+                    })
+                    .collect(),
+            });
+        }
+        DeclarationValue::TSimpleEnum(e) => {
+            let documented = e
+                .variants
+                .iter()
+                .any(|v| !v.docs.is_empty() || v.deprecation().is_some());
+            blocks.push(Block::Table {
+                header: if documented {
+                    vec!["Value", "Description"]
+                } else {
+                    vec!["Value"]
+                },
+                rows: e
+                    .variants
+                    .iter()
+                    .map(|v| {
+                        let value = match v.discriminant {
+                            Some(d) => d.to_string(),
+                            None => format!("{:?}", v.value),
+                        };
+                        let mut row = vec![vec![Inline::Code(value)]];
+                        if documented {
+                            row.push(gen_cell_docs(&v.docs, v.deprecation()));
+                        }
+                        row
+                    })
+                    .collect(),
+            });
+        }
+        value => {
+            let mut ty = vec![Inline::Text("Type: ".into())];
+            ty.extend(match value {
+                DeclarationValue::TPrimitive(p) => gen_primitive(p),
+                DeclarationValue::TMap(m) => gen_map(m),
+                DeclarationValue::TVec(v) => gen_vec(v),
+                DeclarationValue::TOption(o) => gen_option(o),
+                DeclarationValue::TTuple(t) => gen_tuple(t),
+                _ => vec![],
+            });
+            blocks.push(Block::Paragraph(ty));
+        }
+    }
+
+    for (title, language, code) in gen_signatures(declarations, declaration)? {
+        blocks.push(Block::Label(title));
+        blocks.push(Block::Code { language, code });
+    }
+
+    // Generic and hardcoded types don't have example values
+    if let Ok(example) = declarations.fixture(&declaration.name, FixtureKind::Maximal) {
+        blocks.push(Block::Label("Example"));
+        blocks.push(Block::Code {
+            language: "json",
+            code: serde_json::to_string_pretty(&example)?,
+        });
+    }
+
+    Ok(blocks)
+}
+
+// Signature of a single declaration in every language, without any docs
+// since they're already rendered
+fn gen_signatures(
+    declarations: &Declarations,
+    declaration: &TypeDeclaration,
+) -> Result<Vec<(&'static str, &'static str, String)>> {
+    let mut single = Declarations::new();
+    for config in &declarations.config {
+        if let DeclarationsConfig::HackNamespace(_) = config {
+            single.add_config(config.clone());
+        }
+    }
+    single.add(without_docs(declaration));
+
+    let rust = single.codegen_rust()?;
+    let rust = rust
+        .lines()
+        .filter(|l| !l.starts_with("use "))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let hack = single.codegen_hack()?;
+    let hack = hack.trim_start_matches("<?hh");
+
+    // Declarations that aren't generated for a language have no signature in it
+    Ok(vec![
+        ("Rust", "rust", rust.trim().to_string()),
+        ("Hack", "hack", hack.trim().to_string()),
+        ("Flow", "js", single.codegen_flow()?.trim().to_string()),
+    ]
+    .into_iter()
+    .filter(|(_, _, code)| !code.is_empty())
+    .collect())
+}
+
+fn without_docs(declaration: &TypeDeclaration) -> TypeDeclaration {
+    let mut declaration = declaration.clone();
+    declaration.docs = "".into();
+    let clear_fields = |s: &mut TStruct| s.fields.iter_mut().for_each(|f| f.docs = "".into());
+    match &mut declaration.value {
+        DeclarationValue::TStruct(s) => clear_fields(s),
+        DeclarationValue::TEnum(e) => {
+            for variant in &mut e.variants {
+                variant.docs = "".into();
+                if let EnumVariantType::TStruct(s) = &mut variant.variant_type {
+                    clear_fields(s);
+                }
+            }
+        }
+        DeclarationValue::TSimpleEnum(e) => e.variants.iter_mut().for_each(|v| v.docs = "".into()),
+        _ => {}
+    }
+    declaration
+}
+
+fn gen_field_row(field: &StructField) -> Vec<Vec<Inline>> {
+    let mut ty = match &field.field_type {
+        StructFieldType::TMap(m) => gen_map(m),
+        StructFieldType::TSet(s) => gen_set(s),
+        StructFieldType::TOption(o) => gen_option(o),
+        StructFieldType::TPrimitive(p) => gen_primitive(p),
+        StructFieldType::TTuple(t) => gen_tuple(t),
+        StructFieldType::TVec(v) => gen_vec(v),
+    };
+    for constraint in field.constraints() {
+        ty.push(Inline::Text(" ".into()));
+        ty.push(Inline::Code(constraint.to_string()));
+    }
+    let mut name = vec![Inline::Code(field.name.to_string())];
+    if field.is_flattened() {
+        name.push(Inline::Text(" (fields inlined)".into()));
+    }
+    vec![name, ty, gen_cell_docs(&field.docs, field.deprecation())]
+}
+
+// Table cells can't span multiple lines
+fn gen_cell_docs(docs: &str, deprecation: Option<&Deprecation>) -> Vec<Inline> {
+    let mut docs = docs.split_whitespace().collect::<Vec<_>>().join(" ");
+    if let Some(d) = deprecation {
+        let deprecated = match d.note() {
+            Some(note) => format!("Deprecated: {}.", note),
+            None => "Deprecated.".to_string(),
+        };
+        docs = format!("{} {}", deprecated, docs).trim_end().to_string();
+    }
+    vec![Inline::Text(docs)]
+}
+
+// Types are written the same way as in a schema, e.g. `vec<Node>`, with every
+// reference linking to the referenced type
+fn gen_primitive(p: &TPrimitive) -> Vec<Inline> {
+    let text = |s: &str| vec![Inline::Text(s.to_string())];
+    match p {
+        TPrimitive::String => text("string"),
+        TPrimitive::Ti64 => text("i64"),
+        TPrimitive::Tf64 => text("f64"),
+        TPrimitive::Ti32 => text("i32"),
+        TPrimitive::Tu32 => text("u32"),
+        TPrimitive::Tusize => text("usize"),
+        TPrimitive::Tbool => text("bool"),
+        TPrimitive::TGeneric(g) => gen_generic(g),
+        TPrimitive::TReference(r) => gen_reference(r),
+        TPrimitive::THardcoded(s) => vec![Inline::Code(s.to_string())],
+        TPrimitive::TDifferentPerLanguage { rust, hack, flow } => {
+            let mut r = text("per_language(rust: ");
+            r.extend(gen_primitive(rust));
+            r.extend(text(", hack: "));
+            r.extend(gen_primitive(hack));
+            r.extend(text(", flow: "));
+            r.extend(gen_primitive(flow));
+            r.extend(text(")"));
+            r
+        }
+        TPrimitive::TVec(v) => gen_vec(v),
+        TPrimitive::TMap(m) => gen_map(m),
+        TPrimitive::TOption(o) => gen_option(o),
+    }
+}
+
+fn gen_reference(r: &TReference) -> Vec<Inline> {
+    let mut result = vec![Inline::Link {
+        text: r.get_name().to_string(),
+        id: slug(r.get_name()),
+    }];
+    if !r.generic_params.is_empty() {
+        result.extend(wrap("<", r.generic_params.iter().map(gen_generic), ">"));
+    }
+    result
+}
+
+fn gen_generic(g: &TGeneric) -> Vec<Inline> {
+    match g {
+        TGeneric::TDefinition { name, .. } => vec![Inline::Text(name.to_string())],
+        TGeneric::TReference(r) => gen_reference(r),
+        TGeneric::TPrimitive(p) => gen_primitive(p),
+    }
+}
+
+// `open` + comma separated items + `close`
+fn wrap(open: &str, items: impl Iterator<Item = Vec<Inline>>, close: &str) -> Vec<Inline> {
+    let mut result = vec![Inline::Text(open.to_string())];
+    for (i, item) in items.enumerate() {
+        if i > 0 {
+            result.push(Inline::Text(", ".into()));
+        }
+        result.extend(item);
+    }
+    result.push(Inline::Text(close.to_string()));
+    result
+}
+
+fn gen_vec(v: &TVec) -> Vec<Inline> {
+    let TVec::TPrimitive(p) = v;
+    wrap("vec<", std::iter::once(gen_primitive(p)), ">")
+}
+
+fn gen_set(s: &TSet) -> Vec<Inline> {
+    let TSet::TPrimitive(p) = s;
+    wrap("set<", std::iter::once(gen_primitive(p)), ">")
+}
+
+fn gen_map(m: &TMap) -> Vec<Inline> {
+    let open = match m.t {
+        TMapType::BTree => "map<",
+        TMapType::Hash => "hash_map<",
+    };
+    let value = match &m.value {
+        TMapValue::TPrimitive(p) => gen_primitive(p),
+        TMapValue::TSet(s) => gen_set(s),
+    };
+    wrap(open, vec![gen_primitive(&m.key), value].into_iter(), ">")
+}
+
+fn gen_option(o: &TOption) -> Vec<Inline> {
+    let inner = match o {
+        TOption::TPrimitive(p) => gen_primitive(p),
+        TOption::TMap(m) => gen_map(m),
+        TOption::TVec(v) => gen_vec(v),
+        TOption::TSet(s) => gen_set(s),
+        TOption::TTuple(t) => gen_tuple(t),
+    };
+    wrap("option<", std::iter::once(inner), ">")
+}
+
+fn gen_tuple(t: &TTuple) -> Vec<Inline> {
+    let items = t.items.iter().map(|item| match item {
+        TupleItem::TPrimitive(p) => gen_primitive(p),
+        TupleItem::TOption(o) => gen_option(o),
+    });
+    wrap("(", items, ")")
+}
+
+fn gen_struct_type(s: &TStruct) -> Vec<Inline> {
+    let fields = s.fields.iter().map(|f| {
+        let mut field = if f.is_flattened() {
+            vec![Inline::Text("...".into())]
+        } else {
+            vec![Inline::Text(format!("{}: ", f.name))]
+        };
+        field.extend(gen_field_row(f).swap_remove(1));
+        field
+    });
+    wrap("{ ", fields, " }")
+}
+
+mod markdown {
+    use super::{Block, Inline};
+
+    pub fn block(block: &Block) -> String {
+        match block {
+            Block::Heading { level, text, .. } => {
+                format!("{} {}\n", "#".repeat(*level), escape(text))
+            }
+            Block::Docs(docs) => format!("{}\n", docs.trim()),
+            Block::Paragraph(inlines) => format!("{}\n", line(inlines)),
+            Block::Label(label) => format!("**{}**\n", label),
+            Block::Table { header, rows } => {
+                let mut result = format!(
+                    "| {} |\n|{}\n",
+                    header.join(" | "),
+                    " --- |".repeat(header.len())
+                );
+                for row in rows {
+                    let cells = row.iter().map(|c| line(c)).collect::<Vec<_>>();
+                    result.push_str(&format!("| {} |\n", cells.join(" | ")));
+                }
+                result
+            }
+            Block::Code { language, code } => format!("```{}\n{}\n```\n", language, code),
+            Block::List(items) => items
+                .iter()
+                .map(|(level, item)| format!("{}- {}\n", "  ".repeat(*level), inline(item)))
+                .collect(),
+        }
+    }
+
+    fn line(inlines: &[Inline]) -> String {
+        inlines.iter().map(inline).collect()
+    }
+
+    fn inline(inline: &Inline) -> String {
+        match inline {
+            Inline::Text(text) => escape(text),
+            Inline::Code(code) => format!("`{}`", code),
+            Inline::Link { text, id } => format!("[{}](#{})", escape(text), id),
+        }
+    }
+
+    fn escape(text: &str) -> String {
+        text.replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('|', "\\|")
+    }
+}
+
+mod html {
+    use super::{Block, Inline, TITLE};
+
+    const STYLE: &str =
+        "body { font-family: sans-serif; max-width: 960px; margin: 0 auto; padding: 1em; }
+table { border-collapse: collapse; }
+td, th { border: 1px solid #ddd; padding: 4px 8px; text-align: left; }
+pre { background: #f6f8fa; padding: 8px; overflow-x: auto; }";
+
+    pub fn page(blocks: &[Block]) -> String {
+        let body = blocks.iter().map(block).collect::<String>();
+        format!(
+            "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>{}</title>
+<style>
+{}
+</style>
+</head>
+<body>
+{}</body>
+</html>
+",
+            TITLE, STYLE, body
+        )
+    }
+
+    fn block(block: &Block) -> String {
+        match block {
+            Block::Heading { level, id, text } => {
+                format!("<h{0} id=\"{1}\">{2}</h{0}>\n", level, id, escape(text))
+            }
+            Block::Docs(docs) => docs
+                .split("\n\n")
+                .map(|p| format!("<p>{}</p>\n", escape(p.trim())))
+                .collect(),
+            Block::Paragraph(inlines) => format!("<p>{}</p>\n", line(inlines)),
+            Block::Label(label) => format!("<p><strong>{}</strong></p>\n", label),
+            Block::Table { header, rows } => {
+                let mut result = String::from("<table>\n<tr>");
+                for cell in header {
+                    result.push_str(&format!("<th>{}</th>", cell));
+                }
+                result.push_str("</tr>\n");
+                for row in rows {
+                    result.push_str("<tr>");
+                    for cell in row {
+                        result.push_str(&format!("<td>{}</td>", line(cell)));
+                    }
+                    result.push_str("</tr>\n");
+                }
+                result.push_str("</table>\n");
+                result
+            }
+            Block::Code { language, code } => format!(
+                "<pre><code class=\"language-{}\">{}</code></pre>\n",
+                language,
+                escape(code)
+            ),
+            // Nested lists go inside of the parent `<li>`
+            Block::List(items) => {
+                let mut result = String::from("<ul>\n");
+                let mut level = 0;
+                for (i, (item_level, item)) in items.iter().enumerate() {
+                    if i > 0 && *item_level > level {
+                        result.push_str("\n<ul>\n");
+                    } else if i > 0 {
+                        result.push_str("</li>\n");
+                        for _ in *item_level..level {
+                            result.push_str("</ul>\n</li>\n");
+                        }
+                    }
+                    result.push_str(&format!("<li>{}", inline(item)));
+                    level = *item_level;
+                }
+                result.push_str("</li>\n");
+                for _ in 0..level {
+                    result.push_str("</ul>\n</li>\n");
+                }
+                result.push_str("</ul>\n");
+                result
+            }
+        }
+    }
+
+    fn line(inlines: &[Inline]) -> String {
+        inlines.iter().map(inline).collect()
+    }
+
+    fn inline(inline: &Inline) -> String {
+        match inline {
+            Inline::Text(text) => escape(text),
+            Inline::Code(code) => format!("<code>{}</code>", escape(code)),
+            Inline::Link { text, id } => format!("<a href=\"#{}\">{}</a>", id, escape(text)),
+        }
+    }
+
+    fn escape(text: &str) -> String {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    }
+}
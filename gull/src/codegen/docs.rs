@@ -48,7 +48,7 @@ pub fn format_docstring(doc: &str, style: CommentStyle, indent: usize) -> Option
 ///
 /// This function finds the minimun amount we can "de-indent" the block
 /// and trims the beginning of each lines whitespace to achieve that.
-fn adjust_indentation(doc: &str) -> String {
+pub fn adjust_indentation(doc: &str) -> String {
     let mut lines = doc.trim().lines();
 
     // first line is usually not indented, since it starts right after "
@@ -1,14 +1,45 @@
 use super::docs::{format_docstring, CommentStyle};
 use super::{shared, Codegen};
+use crate::flatten::Flattener;
+use crate::map_keys::{MapKey, MapKeys};
 use crate::prelude::*;
 use anyhow::Result;
 use convert_case::{Case, Casing};
-
-pub struct FlowCodegen {}
+use std::cell::Cell;
+use std::collections::BTreeMap;
+
+pub struct FlowCodegen {
+    validators: bool,
+    keys: MapKeys,
+    // Validators check flattened fields inline
+    flattener: Flattener,
+    // Number of (merged) fields of every struct, to tell which fields of a
+    // struct came from the structs it includes
+    field_counts: BTreeMap<String, usize>,
+    // Object and array settings of the declaration that is being generated
+    exact_objects: Cell<bool>,
+    read_only_objects: Cell<bool>,
+    read_only_arrays: Cell<bool>,
+}
 
 impl Codegen for FlowCodegen {
     fn gen_declarations(declarations: &Declarations) -> Result<String> {
-        let rc = FlowCodegen {};
+        let mut rc = FlowCodegen {
+            validators: false,
+            keys: MapKeys::new(declarations),
+            flattener: Flattener::new(declarations),
+            field_counts: declarations
+                .declarations
+                .iter()
+                .filter_map(|d| match &d.value {
+                    DeclarationValue::TStruct(s) => Some((d.name.to_string(), s.fields.len())),
+                    _ => None,
+                })
+                .collect(),
+            exact_objects: Cell::new(false),
+            read_only_objects: Cell::new(false),
+            read_only_arrays: Cell::new(false),
+        };
 
         let mut declarations_code = String::new();
 
@@ -20,13 +51,25 @@ impl Codegen for FlowCodegen {
                 DeclarationsConfig::FileHeader(header) => {
                     declarations_code.push_str(&format!("{}\n", header));
                 }
+                DeclarationsConfig::Validators => rc.validators = true,
+                DeclarationsConfig::EmitOrder(_) | DeclarationsConfig::RustImpls => {}
             }
         }
 
-        for declaration in &declarations.declarations {
+        for declaration in declarations.ordered() {
             declarations_code.push('\n');
             declarations_code.push_str(&rc.gen_declaration(declaration)?);
             declarations_code.push('\n');
+
+            if rc.validators {
+                if let Some(validator) = rc.gen_validator(declaration) {
+                    declarations_code.push_str(&format!("\n{}\n", validator));
+                }
+            }
+        }
+
+        if rc.validators {
+            declarations_code.push_str(VALIDATOR_HELPERS);
         }
 
         Ok(declarations_code)
@@ -36,12 +79,22 @@ impl Codegen for FlowCodegen {
 impl FlowCodegen {
     fn gen_declaration(&self, declaration: &TypeDeclaration) -> Result<String> {
         let name = self.gen_name(&declaration);
+        self.exact_objects
+            .set(declaration.has_config(&TypeDeclarationConfig::FlowExactObjects));
+        self.read_only_objects
+            .set(declaration.has_config(&TypeDeclarationConfig::FlowReadOnlyObjects));
+        self.read_only_arrays
+            .set(declaration.has_config(&TypeDeclarationConfig::FlowReadOnlyArrays));
+        let jsdoc = declaration
+            .deprecation()
+            .map(|d| format!("{}\n", gen_deprecated(d)))
+            .unwrap_or_default();
         let mut r = match &declaration.value {
             DeclarationValue::TPrimitive(p) => {
                 format!(
                     "export type {}{} = {};",
                     name,
-                    shared::generic_params(&declaration.generic_params, |g| self.gen_generic(g)),
+                    self.gen_generic_definitions(&declaration.generic_params),
                     self.gen_primitive_type(p)
                 )
             }
@@ -49,7 +102,7 @@ impl FlowCodegen {
                 format!(
                     "export type {}{} = {};",
                     name,
-                    shared::generic_params(&declaration.generic_params, |g| self.gen_generic(g)),
+                    self.gen_generic_definitions(&declaration.generic_params),
                     self.gen_map(m)
                 )
             }
@@ -57,7 +110,7 @@ impl FlowCodegen {
                 format!(
                     "export type {}{} = {};",
                     name,
-                    shared::generic_params(&declaration.generic_params, |g| self.gen_generic(g)),
+                    self.gen_generic_definitions(&declaration.generic_params),
                     self.gen_vec(v)
                 )
             }
@@ -65,7 +118,7 @@ impl FlowCodegen {
                 format!(
                     "export type {}{} = {};",
                     name,
-                    shared::generic_params(&declaration.generic_params, |g| self.gen_generic(g)),
+                    self.gen_generic_definitions(&declaration.generic_params),
                     self.gen_option(o),
                 )
             }
@@ -73,7 +126,7 @@ impl FlowCodegen {
                 format!(
                     "export type {}{} = {};",
                     name,
-                    shared::generic_params(&declaration.generic_params, |g| self.gen_generic(g)),
+                    self.gen_generic_definitions(&declaration.generic_params),
                     self.gen_tuple(t)
                 )
             }
@@ -81,17 +134,27 @@ impl FlowCodegen {
                 format!(
                     "export type {}{} = {};",
                     name,
-                    shared::generic_params(&declaration.generic_params, |g| self.gen_generic(g)),
-                    self.gen_struct(s, 0)
+                    self.gen_generic_definitions(&declaration.generic_params),
+                    self.gen_struct(&self.spread_includes(declaration, s), 0)
                 )
             }
-            DeclarationValue::TEnum(e) => self.gen_enum(&name, &declaration.generic_params, e),
-            DeclarationValue::TSimpleEnum(e) => self.gen_simple_enum(&name, &e.variants),
+            DeclarationValue::TEnum(e) => {
+                self.gen_enum(&name, &declaration.generic_params, e, &jsdoc)
+            }
+            DeclarationValue::TSimpleEnum(e) => self.gen_simple_enum(&name, e, &jsdoc),
             DeclarationValue::Docs => String::new(),
             DeclarationValue::CodeBlock(b) => self.gen_code_block(b),
         };
 
-        if let Some(doc) = format_docstring(declaration.docs, CommentStyle::DoubleSlash, 0) {
+        match declaration.value {
+            DeclarationValue::TEnum(_)
+            | DeclarationValue::TSimpleEnum(_)
+            | DeclarationValue::Docs
+            | DeclarationValue::CodeBlock(_) => {}
+            _ => r = format!("{}{}", jsdoc, r),
+        }
+
+        if let Some(doc) = format_docstring(&declaration.docs, CommentStyle::DoubleSlash, 0) {
             r = format!("{}\n{}", doc, r);
         }
 
@@ -104,14 +167,33 @@ impl FlowCodegen {
             TMapValue::TSet(s) => self.gen_set(s),
         };
 
-        format!("{{[key: {}]: {}}}", self.gen_primitive_type(&m.key), value)
+        // JSON object keys are always strings
+        let object = match self.keys.kind(&m.key) {
+            MapKey::Number => format!("{{[key: string]: {}}}", value),
+            MapKey::Enum(variants) => format!(
+                "{{{}}}",
+                variants
+                    .iter()
+                    .map(|v| format!(
+                        "'{}'?: {}",
+                        v.replace('\\', "\\\\").replace('\'', "\\'"),
+                        value
+                    ))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            MapKey::String | MapKey::Other | MapKey::Invalid(_) => {
+                format!("{{[key: {}]: {}}}", self.gen_primitive_type(&m.key), value)
+            }
+        };
+        self.read_only(object)
     }
 
     fn gen_vec(&self, v: &TVec) -> String {
         let value = match &v {
             TVec::TPrimitive(p) => self.gen_primitive_type(p),
         };
-        format!("Array<{}>", value)
+        self.gen_array(value)
     }
 
     fn gen_set(&self, s: &TSet) -> String {
@@ -119,7 +201,7 @@ impl FlowCodegen {
             TSet::TPrimitive(p) => self.gen_primitive_type(p),
         };
 
-        format!("Array<{}>", value)
+        self.gen_array(value)
     }
 
     fn gen_option(&self, o: &TOption) -> String {
@@ -136,6 +218,24 @@ impl FlowCodegen {
         }
     }
 
+    // Included structs are merged into `s` before codegen. Replace their
+    // fields with a spread of each included type, like a flattened field.
+    fn spread_includes(&self, declaration: &TypeDeclaration, s: &TStruct) -> TStruct {
+        let mut fields = vec![];
+        let mut merged = 0;
+        for r in declaration.includes() {
+            merged += self.field_counts.get(r.get_name()).copied().unwrap_or(0);
+            fields.push(StructField {
+                name: r.get_name().to_string().into(),
+                docs: "".into(),
+                field_type: StructFieldType::TPrimitive(TPrimitive::TReference(r.clone())),
+                config: vec![StructFieldConfig::Flatten],
+            });
+        }
+        fields.extend(s.fields.iter().skip(merged).cloned());
+        TStruct { fields }
+    }
+
     fn gen_struct(&self, s: &TStruct, indent: usize) -> String {
         let mut fields = String::new();
 
@@ -155,46 +255,137 @@ impl FlowCodegen {
                 }
             };
 
-            field_type = format!(
-                "\n    {}'{}'{}: {},",
-                &indent_prefix, field.name, is_option, field_type
-            );
+            field_type = if field.is_flattened() {
+                format!("\n    {}...{},", &indent_prefix, field_type)
+            } else {
+                format!(
+                    "\n    {}'{}'{}: {},",
+                    &indent_prefix, field.name, is_option, field_type
+                )
+            };
 
             // reset option so that other fields after it don't all become options
             is_option = "";
 
-            if let Some(doc) = format_docstring(field.docs, CommentStyle::DoubleSlash, indent + 4) {
+            if let Some(d) = field.deprecation() {
+                field_type = format!(
+                    "\n    {}{}{}",
+                    &indent_prefix,
+                    gen_deprecated(d),
+                    field_type
+                );
+            }
+
+            if let Some(doc) = format_docstring(&field.docs, CommentStyle::DoubleSlash, indent + 4)
+            {
                 field_type = format!("\n{}{}", doc, field_type);
             }
 
             fields.push_str(&field_type);
         }
 
-        format!("{{{}\n{}}}", fields, indent_prefix)
+        self.gen_object(fields, &indent_prefix)
     }
 
-    fn gen_simple_enum(&self, name: &str, variants: &[&str]) -> String {
-        let ty_def = variants
-            .iter()
-            .map(|n| format!("\"{}\"", n))
-            .collect::<Vec<_>>()
-            .join(" | ");
-        let ty = format!("export type {} = {};", name, ty_def);
+    // `{ ... }` object with the given fields, exact and/or read only depending
+    // on the declaration
+    fn gen_object(&self, fields: String, indent_prefix: &str) -> String {
+        let (open, close) = if self.exact_objects.get() {
+            ("{|", "|}")
+        } else {
+            ("{", "}")
+        };
+        self.read_only(format!("{}{}\n{}{}", open, fields, indent_prefix, close))
+    }
+
+    fn read_only(&self, object: String) -> String {
+        if self.read_only_objects.get() {
+            format!("$ReadOnly<{}>", object)
+        } else {
+            object
+        }
+    }
 
-        let value_def = variants
+    fn gen_array(&self, item: String) -> String {
+        if self.read_only_arrays.get() {
+            format!("$ReadOnlyArray<{}>", item)
+        } else {
+            format!("Array<{}>", item)
+        }
+    }
+
+    fn gen_simple_enum(&self, name: &str, e: &TSimpleEnum, jsdoc: &str) -> String {
+        let variants = &e.variants;
+        // Flow enums either have no initializers or one for every member.
+        // Integer enums are a frozen object instead
+        let initializers = variants.iter().any(|v| v.name != v.value);
+        let members = variants.iter().map(|v| {
+            if e.is_integer() {
+                format!("{}: {}", v.name, gen_literal(v))
+            } else if initializers {
+                format!("{} = {}", v.name, gen_literal(v))
+            } else {
+                v.name.to_string()
+            }
+        });
+
+        // Variants with docs are listed one per line, with docs above them
+        let documented = variants
             .iter()
-            .map(|v| v.to_string())
-            .collect::<Vec<_>>()
-            .join(", ");
-        let value = format!(
-            "export enum {}Enum {{{}}};",
-            name.to_case(Case::UpperCamel),
-            value_def,
-        );
+            .any(|v| !v.docs.is_empty() || v.deprecation().is_some());
+        let (ty_def, value_def) = if documented {
+            let mut ty_def = String::new();
+            let mut value_def = String::new();
+            for (variant, member) in variants.iter().zip(members) {
+                let mut docs = String::new();
+                if let Some(doc) = format_docstring(&variant.docs, CommentStyle::DoubleSlash, 4) {
+                    docs.push_str(&format!("\n{}", doc));
+                }
+                if let Some(d) = variant.deprecation() {
+                    docs.push_str(&format!("\n    {}", gen_deprecated(d)));
+                }
+                ty_def.push_str(&format!("{}\n    | {}", docs, gen_literal(variant)));
+                value_def.push_str(&format!("{}\n    {},", docs, member));
+            }
+            (ty_def, format!("{}\n", value_def))
+        } else {
+            let ty_def = variants
+                .iter()
+                .map(gen_literal)
+                .collect::<Vec<_>>()
+                .join(" | ");
+            (
+                format!(" {}", ty_def),
+                members.collect::<Vec<_>>().join(", "),
+            )
+        };
+
+        let ty = format!("{}export type {} ={};", jsdoc, name, ty_def);
+        let value = if e.is_integer() {
+            let object_type = variants
+                .iter()
+                .map(|v| format!("+{}: {}", v.name, gen_literal(v)))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!(
+                "{}export const {}Enum: {{|{}|}} = Object.freeze({{{}}});",
+                jsdoc,
+                name.to_case(Case::UpperCamel),
+                object_type,
+                value_def,
+            )
+        } else {
+            format!(
+                "{}export enum {}Enum {{{}}};",
+                jsdoc,
+                name.to_case(Case::UpperCamel),
+                value_def,
+            )
+        };
         format!("{}\n\n{}", ty, value)
     }
 
-    fn gen_enum(&self, name: &str, generic_params: &[TGeneric], e: &TEnum) -> String {
+    fn gen_enum(&self, name: &str, generic_params: &[TGeneric], e: &TEnum, jsdoc: &str) -> String {
         let variant_types = e
             .variants
             .iter()
@@ -205,8 +396,8 @@ impl FlowCodegen {
         let variant_type_enum_name = format!("{}Type", name);
 
         let variant_type_hack_enum = format!(
-            "export type {} = {};",
-            variant_type_enum_name, variant_types
+            "{}export type {} = {};",
+            jsdoc, variant_type_enum_name, variant_types
         );
 
         let mut variants = String::new();
@@ -218,8 +409,11 @@ impl FlowCodegen {
             };
 
             variant_type = format!("\n    '{}'?: {},", variant.name, variant_type);
+            if let Some(d) = variant.deprecation() {
+                variant_type = format!("\n    {}{}", gen_deprecated(d), variant_type);
+            }
 
-            if let Some(doc) = format_docstring(variant.docs, CommentStyle::DoubleSlash, 4) {
+            if let Some(doc) = format_docstring(&variant.docs, CommentStyle::DoubleSlash, 4) {
                 variant_type = format!("\n{}{}", doc, variant_type);
             }
 
@@ -229,14 +423,114 @@ impl FlowCodegen {
         format!(
             "{}
 
-export type {}{} = {{{}\n}};",
+{}export type {}{} = {};
+
+{}",
             variant_type_hack_enum,
+            jsdoc,
             name,
-            shared::generic_params(&generic_params, |g| self.gen_generic(g)),
-            variants
+            self.gen_generic_definitions(generic_params),
+            self.gen_object(variants, ""),
+            self.gen_enum_helpers(name, generic_params, e, jsdoc)
         )
     }
 
+    // `getNameType()` that tells which variant is set, a constructor per
+    // variant and an exhaustive `matchName()`, so the object with an optional
+    // key per variant doesn't have to be inspected by hand. Variant keys are
+    // never `undefined` in JSON, but `option` values can be `null`.
+    fn gen_enum_helpers(
+        &self,
+        name: &str,
+        generic_params: &[TGeneric],
+        e: &TEnum,
+        jsdoc: &str,
+    ) -> String {
+        let generic_definitions =
+            shared::generic_params(generic_params, |g| self.gen_generic_definition(g, false));
+        let match_generic_definitions = shared::generic_params(
+            &[generic_params, &[TGeneric::param("TResult")]].concat(),
+            |g| self.gen_generic_definition(g, false),
+        );
+        let enum_type = format!(
+            "{}{}",
+            name,
+            shared::generic_params(generic_params, |g| self.gen_generic(g))
+        );
+
+        let mut type_checks = String::new();
+        let mut match_checks = String::new();
+        let mut cases = String::new();
+        let mut constructors = vec![];
+
+        for variant in &e.variants {
+            let value_type = match &variant.variant_type {
+                EnumVariantType::TStruct(s) => self.gen_struct(s, 0),
+                EnumVariantType::TPrimitive(p) => self.gen_primitive_type(p),
+            };
+
+            type_checks.push_str(&format!(
+                "    if (x.{} !== undefined) {{
+        return '{}';
+    }}
+",
+                variant.name, variant.name
+            ));
+            match_checks.push_str(&format!(
+                "    if (x.{} !== undefined) {{
+        return cases.{}(x.{});
+    }}
+",
+                variant.name, variant.name, variant.name
+            ));
+            cases.push_str(&format!(
+                "\n        '{}': (value: {}) => TResult,",
+                variant.name,
+                value_type.replace('\n', "\n        ")
+            ));
+
+            let constructor_jsdoc = variant
+                .deprecation()
+                .map(|d| format!("{}\n", gen_deprecated(d)))
+                .unwrap_or_else(|| jsdoc.to_string());
+            constructors.push(format!(
+                "{}export function {}{}{}(value: {}): {} {{
+    return {{'{}': value}};
+}}",
+                constructor_jsdoc,
+                name.to_case(Case::Camel),
+                variant.name,
+                generic_definitions,
+                value_type,
+                enum_type,
+                variant.name
+            ));
+        }
+
+        let get_type = format!(
+            "{}export function get{}Type{}(x: {}): {}Type {{
+{}    throw new Error('No variant of {} is set');
+}}",
+            jsdoc, name, generic_definitions, enum_type, name, type_checks, name
+        );
+        let match_fn = format!(
+            "{}export function match{}{}(
+    x: {},
+    cases: {{|{}
+    |}},
+): TResult {{
+{}    throw new Error('No variant of {} is set');
+}}",
+            jsdoc, name, match_generic_definitions, enum_type, cases, match_checks, name
+        );
+
+        std::iter::once(get_type)
+            .chain(constructors)
+            .chain(std::iter::once(match_fn))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
     fn gen_tuple(&self, t: &TTuple) -> String {
         let mut values = String::new();
 
@@ -292,9 +586,230 @@ export type {}{} = {{{}\n}};",
             TGeneric::TReference(r, ..) => {
                 self.gen_primitive_type(&TPrimitive::TReference(r.clone()))
             }
+            TGeneric::TPrimitive(p) => self.gen_primitive_type(p),
+        }
+    }
+
+    fn gen_generic_definitions(&self, params: &[TGeneric]) -> String {
+        shared::generic_params(params, |g| self.gen_generic_definition(g, true))
+    }
+
+    // `T: Bound = Default`. Functions can't have defaults.
+    fn gen_generic_definition(&self, g: &TGeneric, with_default: bool) -> String {
+        match g {
+            TGeneric::TDefinition {
+                name,
+                flow_bounds,
+                default,
+                ..
+            } => {
+                let mut result = name.to_string();
+                if let Some(bounds) = flow_bounds {
+                    result.push_str(&format!(": {}", bounds));
+                }
+                if let Some(default) = default.as_ref().filter(|_| with_default) {
+                    result.push_str(&format!(" = {}", self.gen_primitive_type(default)));
+                }
+                result
+            }
+            _ => self.gen_generic(g),
+        }
+    }
+
+    // `validateName(x: mixed): Name` that throws if `x` doesn't match the type.
+    // Validators of generic types take a validator for every generic param.
+    fn gen_validator(&self, declaration: &TypeDeclaration) -> Option<String> {
+        let check = match &declaration.value {
+            DeclarationValue::TPrimitive(p) => self.gen_check(p),
+            DeclarationValue::TMap(m) => self.gen_map_check(m),
+            DeclarationValue::TVec(v) => self.gen_vec_check(v),
+            DeclarationValue::TOption(o) => format!("gullOptionOf({})", self.gen_option_check(o)),
+            DeclarationValue::TTuple(t) => self.gen_tuple_check(t),
+            DeclarationValue::TStruct(s) => self.gen_struct_check(s, 4),
+            DeclarationValue::TEnum(e) => self.gen_enum_check(e),
+            DeclarationValue::TSimpleEnum(e) => format!(
+                "gullOneOf([{}])",
+                e.variants
+                    .iter()
+                    .map(gen_literal)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            DeclarationValue::Docs | DeclarationValue::CodeBlock(_) => return None,
+        };
+
+        let name = self.gen_name(declaration);
+        let generic_definitions = shared::generic_params(&declaration.generic_params, |g| {
+            self.gen_generic_definition(g, false)
+        });
+        let generics = shared::generic_params(&declaration.generic_params, |g| self.gen_generic(g));
+        let generic_checks = declaration
+            .generic_params
+            .iter()
+            .filter_map(|g| match g {
+                TGeneric::TDefinition { name, .. } => {
+                    Some(format!("validate{}: GullCheck, ", name))
+                }
+                TGeneric::TReference(_) | TGeneric::TPrimitive(_) => None,
+            })
+            .collect::<String>();
+
+        // Lambdas of generic references have to be wrapped to be called right away
+        let check = if check.starts_with('(') {
+            format!("({})", check)
+        } else {
+            check
+        };
+
+        Some(format!(
+            "export function validate{}{}(x: mixed, {}path: string = '$'): {}{} {{
+    {}(x, path);
+    return (x: any);
+}}",
+            name, generic_definitions, generic_checks, name, generics, check
+        ))
+    }
+
+    fn gen_check(&self, ty: &TPrimitive) -> String {
+        match ty {
+            TPrimitive::String => "gullString".to_string(),
+            TPrimitive::Tbool => "gullBoolean".to_string(),
+            TPrimitive::Tf64 => "gullNumber".to_string(),
+            TPrimitive::Ti64 | TPrimitive::Ti32 | TPrimitive::Tu32 | TPrimitive::Tusize => {
+                "gullInt".to_string()
+            }
+            // Hardcoded types are opaque, there's nothing we can check
+            TPrimitive::THardcoded(_) => "gullAny".to_string(),
+            TPrimitive::TVec(v) => self.gen_vec_check(v),
+            TPrimitive::TMap(m) => self.gen_map_check(m),
+            TPrimitive::TOption(o) => format!("gullOptionOf({})", self.gen_option_check(o)),
+            TPrimitive::TDifferentPerLanguage { flow, .. } => self.gen_check(&flow),
+            TPrimitive::TGeneric(g) => self.gen_generic_check(g),
+            TPrimitive::TReference(r) => self.gen_reference_check(r),
+        }
+    }
+
+    fn gen_generic_check(&self, g: &TGeneric) -> String {
+        match g {
+            TGeneric::TDefinition { name, .. } => format!("validate{}", name),
+            TGeneric::TReference(r) => self.gen_reference_check(r),
+            TGeneric::TPrimitive(p) => self.gen_check(p),
+        }
+    }
+
+    fn gen_reference_check(&self, r: &TReference) -> String {
+        if r.generic_params.is_empty() {
+            return format!("validate{}", r.get_name());
+        }
+
+        let generic_checks = r
+            .generic_params
+            .iter()
+            .map(|g| format!("{}, ", self.gen_generic_check(g)))
+            .collect::<String>();
+        format!(
+            "(x, path) => validate{}(x, {}path)",
+            r.get_name(),
+            generic_checks
+        )
+    }
+
+    fn gen_vec_check(&self, v: &TVec) -> String {
+        match v {
+            TVec::TPrimitive(p) => format!("gullArrayOf({})", self.gen_check(p)),
+        }
+    }
+
+    fn gen_set_check(&self, s: &TSet) -> String {
+        match s {
+            TSet::TPrimitive(p) => format!("gullArrayOf({})", self.gen_check(p)),
         }
     }
 
+    fn gen_map_check(&self, m: &TMap) -> String {
+        let value = match &m.value {
+            TMapValue::TPrimitive(p) => self.gen_check(p),
+            TMapValue::TSet(s) => self.gen_set_check(s),
+        };
+        match self.keys.kind(&m.key) {
+            // Keys are strings even for integer enums
+            MapKey::Enum(values) => format!(
+                "gullMapOf({}, gullOneOf([{}]))",
+                value,
+                values
+                    .iter()
+                    .map(|v| gen_string(v))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            _ => format!("gullMapOf({})", value),
+        }
+    }
+
+    // check of the value inside of the option
+    fn gen_option_check(&self, o: &TOption) -> String {
+        match o {
+            TOption::TPrimitive(p) => self.gen_check(p),
+            TOption::TMap(m) => self.gen_map_check(m),
+            TOption::TVec(v) => self.gen_vec_check(v),
+            TOption::TSet(s) => self.gen_set_check(s),
+            TOption::TTuple(t) => self.gen_tuple_check(t),
+        }
+    }
+
+    fn gen_tuple_check(&self, t: &TTuple) -> String {
+        let items = t
+            .items
+            .iter()
+            .map(|item| match item {
+                TupleItem::TPrimitive(p) => self.gen_check(p),
+                TupleItem::TOption(o) => format!("gullOptionOf({})", self.gen_option_check(o)),
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("gullTupleOf([{}])", items)
+    }
+
+    fn gen_struct_check(&self, s: &TStruct, indent: usize) -> String {
+        let prefix = " ".repeat(indent);
+        let mut fields = String::new();
+        let flattened = self
+            .flattener
+            .fields(s)
+            .expect("flattened fields are validated before codegen");
+
+        for field in &flattened {
+            let check = match &field.field_type {
+                StructFieldType::TMap(m) => self.gen_map_check(m),
+                StructFieldType::TSet(s) => self.gen_set_check(s),
+                StructFieldType::TPrimitive(p) => self.gen_check(p),
+                StructFieldType::TTuple(t) => self.gen_tuple_check(t),
+                StructFieldType::TVec(v) => self.gen_vec_check(v),
+                StructFieldType::TOption(o) => {
+                    format!("gullOptionOf({})", self.gen_option_check(o))
+                }
+            };
+            let check = gen_constrained(check, &field.constraints());
+            fields.push_str(&format!("\n{}    '{}': {},", prefix, field.name, check));
+        }
+
+        format!("gullStructOf({{{}\n{}}})", fields, prefix)
+    }
+
+    fn gen_enum_check(&self, e: &TEnum) -> String {
+        let mut variants = String::new();
+
+        for variant in &e.variants {
+            let check = match &variant.variant_type {
+                EnumVariantType::TStruct(s) => self.gen_struct_check(s, 8),
+                EnumVariantType::TPrimitive(p) => self.gen_check(p),
+            };
+            variants.push_str(&format!("\n        '{}': {},", variant.name, check));
+        }
+
+        format!("gullEnumOf({{{}\n    }})", variants)
+    }
+
     fn gen_code_block(&self, b: &CodeBlock) -> String {
         match b {
             CodeBlock::Rust(_) => String::new(),
@@ -307,3 +822,176 @@ export type {}{} = {{{}\n}};",
         }
     }
 }
+
+// Emitted once at the end of the file when validators are enabled.
+// Every check is a `(x: mixed, path: string) => mixed` function that throws
+// an error with the path to the invalid value.
+const VALIDATOR_HELPERS: &str = r#"
+type GullCheck = (x: mixed, path: string) => mixed;
+
+function gullFail(x: mixed, path: string, expected: string): empty {
+    const actual = x === null ? 'null' : Array.isArray(x) ? 'array' : typeof x;
+    throw new Error(`${path}: expected ${expected}, got ${actual}`);
+}
+
+function gullAny(x: mixed, path: string): void {}
+
+function gullString(x: mixed, path: string): void {
+    if (typeof x !== 'string') gullFail(x, path, 'string');
+}
+
+function gullNumber(x: mixed, path: string): void {
+    if (typeof x !== 'number') gullFail(x, path, 'number');
+}
+
+function gullInt(x: mixed, path: string): void {
+    if (typeof x !== 'number' || !Number.isInteger(x)) gullFail(x, path, 'integer');
+}
+
+function gullBoolean(x: mixed, path: string): void {
+    if (typeof x !== 'boolean') gullFail(x, path, 'boolean');
+}
+
+function gullObject(x: mixed, path: string): {+[key: string]: mixed} {
+    if (typeof x !== 'object' || x === null || Array.isArray(x)) {
+        return gullFail(x, path, 'object');
+    }
+    return x;
+}
+
+function gullArray(x: mixed, path: string): $ReadOnlyArray<mixed> {
+    if (!Array.isArray(x)) return gullFail(x, path, 'array');
+    return x;
+}
+
+function gullArrayOf(item: GullCheck): GullCheck {
+    return (x, path) => gullArray(x, path).forEach((v, i) => item(v, `${path}[${i}]`));
+}
+
+function gullMapOf(value: GullCheck, key?: GullCheck): GullCheck {
+    return (x, path) => {
+        const o = gullObject(x, path);
+        Object.keys(o).forEach(k => {
+            if (key) key(k, `${path}.${k}`);
+            value(o[k], `${path}.${k}`);
+        });
+    };
+}
+
+function gullOptionOf(inner: GullCheck): GullCheck {
+    return (x, path) => (x == null ? x : inner(x, path));
+}
+
+function gullTupleOf(items: $ReadOnlyArray<GullCheck>): GullCheck {
+    return (x, path) => {
+        const a = gullArray(x, path);
+        if (a.length !== items.length) gullFail(x, path, `${items.length} items`);
+        items.forEach((item, i) => item(a[i], `${path}[${i}]`));
+    };
+}
+
+function gullStructOf(fields: {+[key: string]: GullCheck}): GullCheck {
+    return (x, path) => {
+        const o = gullObject(x, path);
+        Object.keys(fields).forEach(k => fields[k](o[k], `${path}.${k}`));
+    };
+}
+
+function gullEnumOf(variants: {+[key: string]: GullCheck}): GullCheck {
+    return (x, path) => {
+        const o = gullObject(x, path);
+        const keys = Object.keys(o);
+        if (keys.length !== 1 || variants[keys[0]] == null) {
+            return gullFail(x, path, `one of ${Object.keys(variants).join(', ')}`);
+        }
+        variants[keys[0]](o[keys[0]], `${path}.${keys[0]}`);
+    };
+}
+
+function gullOneOf(values: $ReadOnlyArray<string | number>): GullCheck {
+    return (x, path) => {
+        if (!values.includes(x)) {
+            gullFail(x, path, values.map(v => JSON.stringify(v)).join(' | '));
+        }
+    };
+}
+
+type GullConstraints = {|
+    minLength?: number,
+    maxLength?: number,
+    pattern?: string,
+    minimum?: number,
+    maximum?: number,
+|};
+
+// Strings are measured in characters, like in Rust and Hack
+function gullLength(x: mixed): number {
+    if (typeof x === 'string') return Array.from(x).length;
+    if (Array.isArray(x)) return x.length;
+    return typeof x === 'object' && x !== null ? Object.keys(x).length : 0;
+}
+
+function gullConstrained(inner: GullCheck, c: GullConstraints): GullCheck {
+    return (x, path) => {
+        inner(x, path);
+        if (x == null) return;
+        const violated = (expected: string) => {
+            throw new Error(`${path}: expected ${expected}`);
+        };
+        const length = gullLength(x);
+        if (c.minLength != null && length < c.minLength) {
+            violated(`length of at least ${c.minLength}`);
+        }
+        if (c.maxLength != null && length > c.maxLength) {
+            violated(`length of at most ${c.maxLength}`);
+        }
+        if (c.pattern != null && typeof x === 'string' && !new RegExp(c.pattern, 'u').test(x)) {
+            violated(`a match of ${c.pattern}`);
+        }
+        if (typeof x === 'number') {
+            if (c.minimum != null && x < c.minimum) violated(`at least ${c.minimum}`);
+            if (c.maximum != null && x > c.maximum) violated(`at most ${c.maximum}`);
+        }
+    };
+}
+"#;
+
+// Wraps the check of a field with `gullConstrained` if it has constraints
+fn gen_constrained(check: String, constraints: &[&FieldConstraint]) -> String {
+    if constraints.is_empty() {
+        return check;
+    }
+    let constraints = constraints
+        .iter()
+        .map(|c| match c {
+            FieldConstraint::MinLength(n) => format!("minLength: {}", n),
+            FieldConstraint::MaxLength(n) => format!("maxLength: {}", n),
+            FieldConstraint::Pattern(p) => format!("pattern: {}", gen_string(p)),
+            FieldConstraint::Minimum(n) => format!("minimum: {}", n),
+            FieldConstraint::Maximum(n) => format!("maximum: {}", n),
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("gullConstrained({}, {{{}}})", check, constraints)
+}
+
+// JSON string literals are valid JS string literals
+fn gen_string(s: &str) -> String {
+    serde_json::Value::from(s).to_string()
+}
+
+// What a simple enum variant serializes to, as a JS literal
+fn gen_literal(v: &SimpleEnumVariant) -> String {
+    match v.discriminant {
+        Some(d) => d.to_string(),
+        None => gen_string(&v.value),
+    }
+}
+
+// JSDoc tag that flow's `deprecated-type` lint and editors pick up
+fn gen_deprecated(d: &Deprecation) -> String {
+    match d.note() {
+        Some(note) => format!("/** @deprecated {} */", note.replace("*/", "* /")),
+        None => "/** @deprecated */".to_string(),
+    }
+}
@@ -1,48 +1,120 @@
 use super::docs::{format_docstring, CommentStyle};
 use super::shared;
 use super::Codegen;
+use crate::flatten::Flattener;
+use crate::map_keys::{MapKey, MapKeys};
 use crate::prelude::*;
-use anyhow::Result;
+use anyhow::{bail, Result};
 use convert_case::{Case, Casing};
+use std::cell::Cell;
+use std::collections::BTreeMap;
 
 pub struct HackCodegen {
-    namespace: &'static str,
+    namespace: String,
+    validators: bool,
+    keys: MapKeys,
+    // Shapes can't include other shapes, flattened fields are inlined
+    flattener: Flattener,
+    // Whether shapes of the declaration that is being generated are open
+    open_shapes: Cell<bool>,
+    // Simple enums generated as enum classes or class constants. Their
+    // values can only be typed as `string` (or `int` for integer enums)
+    untyped_enums: BTreeMap<String, &'static str>,
 }
 
 impl Codegen for HackCodegen {
     fn gen_declarations(declarations: &Declarations) -> Result<String> {
-        let mut rc = HackCodegen { namespace: "" };
+        check_accessor_names(declarations)?;
+
+        let mut rc = HackCodegen {
+            namespace: String::new(),
+            validators: false,
+            keys: MapKeys::new(declarations),
+            flattener: Flattener::new(declarations),
+            open_shapes: Cell::new(false),
+            untyped_enums: declarations
+                .declarations
+                .iter()
+                .filter_map(|d| match &d.value {
+                    DeclarationValue::TSimpleEnum(e)
+                        if d.hack_enum_style() != HackEnumStyle::Enum =>
+                    {
+                        Some((d.name.to_string(), value_type(e)))
+                    }
+                    _ => None,
+                })
+                .collect(),
+        };
 
         let mut declarations_code = String::from("<?hh\n");
 
         for config in &declarations.config {
             match config {
-                DeclarationsConfig::HackNamespace(namespace) => rc.namespace = namespace,
+                DeclarationsConfig::HackNamespace(namespace) => {
+                    rc.namespace = namespace.to_string()
+                }
                 DeclarationsConfig::FileHeader(header) => {
                     declarations_code.push_str(&format!("{}\n", header));
                 }
+                DeclarationsConfig::Validators => rc.validators = true,
+                DeclarationsConfig::EmitOrder(_) | DeclarationsConfig::RustImpls => {}
             }
         }
 
-        for declaration in &declarations.declarations {
+        for declaration in declarations.ordered() {
             declarations_code.push('\n');
             declarations_code.push_str(&rc.gen_declaration(declaration)?);
             declarations_code.push('\n');
+
+            if rc.validators {
+                if let Some(validator) = rc.gen_validator(declaration) {
+                    declarations_code.push_str(&format!("\n{}\n", validator));
+                }
+            }
+        }
+
+        if rc.validators {
+            declarations_code.push_str(&rc.gen_validator_helpers());
         }
 
         Ok(declarations_code)
     }
 }
 
+// A variant named `Type` would get an accessor with the same name as
+// `Name_getType()`
+fn check_accessor_names(declarations: &Declarations) -> Result<()> {
+    for declaration in &declarations.declarations {
+        if let DeclarationValue::TEnum(e) = &declaration.value {
+            if e.variants.iter().any(|v| v.name == "Type") {
+                bail!(
+                    "variant `Type` of `{}` clashes with the generated `{}_getType()` in Hack",
+                    declaration.name,
+                    declaration.name
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
 impl HackCodegen {
     fn gen_declaration(&self, declaration: &TypeDeclaration) -> Result<String> {
-        let name = self.gen_namespaced_name(declaration.name);
+        let name = self.gen_namespaced_name(&declaration.name);
+        self.open_shapes
+            .set(declaration.has_config(&TypeDeclarationConfig::HackOpenShapes));
+        // Hack attributes can only go on the declaration itself, fields and
+        // variants get a comment instead
+        let attribute = declaration
+            .deprecation()
+            .map(|d| format!("{}\n", gen_deprecated(d)))
+            .unwrap_or_default();
         let mut r = match &declaration.value {
             DeclarationValue::TPrimitive(p) => {
                 format!(
                     "type {}{} = {};",
                     name,
-                    shared::generic_params(&declaration.generic_params, |g| self.gen_generic(g)),
+                    self.gen_generic_definitions(&declaration.generic_params),
                     self.gen_primitive_type(p)
                 )
             }
@@ -50,7 +122,7 @@ impl HackCodegen {
                 format!(
                     "type {}{} = {};",
                     name,
-                    shared::generic_params(&declaration.generic_params, |g| self.gen_generic(g)),
+                    self.gen_generic_definitions(&declaration.generic_params),
                     self.gen_map(m)
                 )
             }
@@ -58,7 +130,7 @@ impl HackCodegen {
                 format!(
                     "type {}{} = {};",
                     name,
-                    shared::generic_params(&declaration.generic_params, |g| self.gen_generic(g)),
+                    self.gen_generic_definitions(&declaration.generic_params),
                     self.gen_vec(v)
                 )
             }
@@ -66,7 +138,7 @@ impl HackCodegen {
                 format!(
                     "type {}{} = {};",
                     name,
-                    shared::generic_params(&declaration.generic_params, |g| self.gen_generic(g)),
+                    self.gen_generic_definitions(&declaration.generic_params),
                     self.gen_option(o)
                 )
             }
@@ -74,7 +146,7 @@ impl HackCodegen {
                 format!(
                     "type {}{} = {};",
                     name,
-                    shared::generic_params(&declaration.generic_params, |g| self.gen_generic(g)),
+                    self.gen_generic_definitions(&declaration.generic_params),
                     self.gen_tuple(t)
                 )
             }
@@ -82,17 +154,26 @@ impl HackCodegen {
                 format!(
                     "type {}{} = {};",
                     name,
-                    shared::generic_params(&declaration.generic_params, |g| self.gen_generic(g)),
+                    self.gen_generic_definitions(&declaration.generic_params),
                     self.gen_struct(s, 0)
                 )
             }
-            DeclarationValue::TEnum(e) => self.gen_enum(&name, &declaration.generic_params, e),
-            DeclarationValue::TSimpleEnum(e) => self.gen_simple_enum(&name, &e.variants),
+            DeclarationValue::TEnum(e) => self.gen_enum(&name, declaration, e, &attribute),
+            DeclarationValue::TSimpleEnum(e) => {
+                self.gen_simple_enum(&name, e, declaration.hack_enum_style())
+            }
             DeclarationValue::Docs => String::new(),
             DeclarationValue::CodeBlock(b) => self.gen_code_block(b),
         };
 
-        if let Some(doc) = format_docstring(declaration.docs, CommentStyle::DoubleSlash, 0) {
+        match declaration.value {
+            DeclarationValue::TEnum(_)
+            | DeclarationValue::Docs
+            | DeclarationValue::CodeBlock(_) => {}
+            _ => r = format!("{}{}", attribute, r),
+        }
+
+        if let Some(doc) = format_docstring(&declaration.docs, CommentStyle::DoubleSlash, 0) {
             r = format!("{}\n{}", doc, r);
         }
 
@@ -105,7 +186,12 @@ impl HackCodegen {
             TMapValue::TSet(s) => self.gen_set(s),
         };
 
-        format!("dict<{}, {}>", self.gen_primitive_type(&m.key), value)
+        // Numeric string keys become ints when JSON is decoded
+        let key = match self.keys.kind(&m.key) {
+            MapKey::Number | MapKey::String => "arraykey".to_string(),
+            _ => self.gen_primitive_type(&m.key),
+        };
+        format!("dict<{}, {}>", key, value)
     }
 
     fn gen_vec(&self, v: &TVec) -> String {
@@ -144,7 +230,7 @@ impl HackCodegen {
 
         let mut is_option = "";
 
-        for field in &s.fields {
+        for field in &self.flattened_fields(s) {
             let mut field_type = match &field.field_type {
                 StructFieldType::TMap(m) => self.gen_map(m),
                 StructFieldType::TSet(s) => self.gen_set(s),
@@ -164,40 +250,94 @@ impl HackCodegen {
             // reset option so that other fields after it don't all become options
             is_option = "";
 
-            if let Some(doc) = format_docstring(field.docs, CommentStyle::DoubleSlash, indent + 4) {
+            if let Some(d) = field.deprecation() {
+                field_type = format!(
+                    "\n    {}{}{}",
+                    &prefix,
+                    gen_deprecated_comment(d),
+                    field_type
+                );
+            }
+
+            if let Some(doc) = format_docstring(&field.docs, CommentStyle::DoubleSlash, indent + 4)
+            {
                 field_type = format!("\n{}{}", doc, field_type);
             }
 
             fields.push_str(&field_type);
         }
 
+        self.gen_shape(fields, &prefix)
+    }
+
+    fn flattened_fields(&self, s: &TStruct) -> Vec<StructField> {
+        self.flattener
+            .fields(s)
+            .expect("flattened fields are validated before codegen")
+    }
+
+    fn gen_shape(&self, mut fields: String, prefix: &str) -> String {
+        if self.open_shapes.get() {
+            fields.push_str(&format!("\n    {}...", prefix));
+        }
         format!("shape({}\n{})", fields, prefix)
     }
 
-    fn gen_simple_enum(&self, name: &str, variants: &[&str]) -> String {
+    fn gen_simple_enum(&self, name: &str, e: &TSimpleEnum, style: HackEnumStyle) -> String {
+        let t = value_type(e);
+        let (header, member) = match style {
+            HackEnumStyle::Enum => (format!("enum {}: {} as {}", name, t, t), String::new()),
+            HackEnumStyle::EnumClass => (format!("enum class {}: {}", name, t), format!("{} ", t)),
+            HackEnumStyle::ClassConstants => (
+                format!("abstract final class {}", name),
+                format!("const {} ", t),
+            ),
+        };
+
         let mut variant_lines = vec![];
 
-        for name in variants {
-            variant_lines.push(format!(
-                r#"    {} = "{}";{}"#,
-                name.to_case(Case::ScreamingSnake),
-                name,
-                "\n"
-            ));
+        for variant in &e.variants {
+            let value = match variant.discriminant {
+                Some(d) => d.to_string(),
+                None => gen_double_quoted(&variant.value),
+            };
+            let mut line = format!(
+                "    {}{} = {};\n",
+                member,
+                variant.name.to_case(Case::ScreamingSnake),
+                value
+            );
+            if let Some(d) = variant.deprecation() {
+                line = format!("    {}\n{}", gen_deprecated_comment(d), line);
+            }
+            if let Some(doc) = format_docstring(&variant.docs, CommentStyle::DoubleSlash, 4) {
+                line = format!("{}\n{}", doc, line);
+            }
+            variant_lines.push(line);
         }
 
-        format!(
-            "enum {}: string as string {{\n{}}}",
-            name,
-            variant_lines.join("")
-        )
+        format!("{} {{\n{}}}", header, variant_lines.join(""))
     }
 
-    fn gen_enum(&self, name: &str, generic_params: &[TGeneric], e: &TEnum) -> String {
+    fn gen_enum(
+        &self,
+        name: &str,
+        declaration: &TypeDeclaration,
+        e: &TEnum,
+        attribute: &str,
+    ) -> String {
+        let generic_params = &declaration.generic_params;
+        let style = declaration.hack_enum_style();
         let variant_type_enum_name = format!("{}Type", name);
 
-        let variant_names = e.variants.iter().map(|v| v.name).collect::<Vec<_>>();
-        let simple_enum = self.gen_simple_enum(&variant_type_enum_name, &variant_names);
+        let variant_names = TSimpleEnum {
+            variants: e
+                .variants
+                .iter()
+                .map(|v| SimpleEnumVariant::new(v.name.clone()))
+                .collect(),
+        };
+        let simple_enum = self.gen_simple_enum(&variant_type_enum_name, &variant_names, style);
 
         let mut variants = String::new();
 
@@ -208,8 +348,11 @@ impl HackCodegen {
             };
 
             variant_type = format!("\n    ?'{}' => {},", variant.name, variant_type);
+            if let Some(d) = variant.deprecation() {
+                variant_type = format!("\n    {}{}", gen_deprecated_comment(d), variant_type);
+            }
 
-            if let Some(doc) = format_docstring(variant.docs, CommentStyle::DoubleSlash, 4) {
+            if let Some(doc) = format_docstring(&variant.docs, CommentStyle::DoubleSlash, 4) {
                 variant_type = format!("\n{}{}", doc, variant_type);
             }
 
@@ -218,16 +361,91 @@ impl HackCodegen {
 
         format!(
             "
-{}
+{}{}
 
-type {}{} = shape({}\n);",
+{}type {}{} = {};
+
+{}",
+            attribute,
             simple_enum,
+            attribute,
             name,
-            shared::generic_params(generic_params, |g| self.gen_generic(g)),
-            variants
+            self.gen_generic_definitions(generic_params),
+            self.gen_shape(variants, ""),
+            self.gen_enum_accessors(name, generic_params, e, style)
         )
     }
 
+    // `Name_getType()` that tells which variant is set and a
+    // `Name_getVariant()` per variant that returns its value if it's set, so
+    // the externally tagged shape doesn't have to be indexed by strings
+    fn gen_enum_accessors(
+        &self,
+        name: &str,
+        generic_params: &[TGeneric],
+        e: &TEnum,
+        style: HackEnumStyle,
+    ) -> String {
+        let generic_definitions = self.gen_generic_definitions(generic_params);
+        let enum_type = format!(
+            "{}{}",
+            name,
+            shared::generic_params(generic_params, |g| self.gen_generic(g))
+        );
+        let variant_type_enum_name = format!("{}Type", name);
+        let variant_type = match style {
+            HackEnumStyle::Enum => variant_type_enum_name.clone(),
+            HackEnumStyle::EnumClass => format!("HH\\MemberOf<{}, string>", variant_type_enum_name),
+            HackEnumStyle::ClassConstants => "string".to_string(),
+        };
+
+        let mut checks = String::new();
+        let mut accessors = vec![];
+
+        for variant in &e.variants {
+            checks.push_str(&format!(
+                "    if (Shapes::keyExists($x, '{}')) {{
+        return {}::{};
+    }}
+",
+                variant.name,
+                variant_type_enum_name,
+                variant.name.to_case(Case::ScreamingSnake)
+            ));
+
+            let mut value_type = match &variant.variant_type {
+                EnumVariantType::TStruct(s) => self.gen_struct(s, 0),
+                EnumVariantType::TPrimitive(p) => self.gen_primitive_type(p),
+            };
+            if !value_type.starts_with('?') {
+                value_type = format!("?{}", value_type);
+            }
+
+            let mut accessor = format!(
+                "function {}_get{}{}({} $x): {} {{
+    return Shapes::idx($x, '{}');
+}}",
+                name, variant.name, generic_definitions, enum_type, value_type, variant.name
+            );
+            if let Some(d) = variant.deprecation() {
+                accessor = format!("{}\n{}", gen_deprecated(d), accessor);
+            }
+            accessors.push(accessor);
+        }
+
+        let get_type = format!(
+            "function {}_getType{}({} $x): {} {{
+{}    invariant_violation('No variant of {} is set');
+}}",
+            name, generic_definitions, enum_type, variant_type, checks, name
+        );
+
+        std::iter::once(get_type)
+            .chain(accessors)
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
     fn gen_tuple(&self, t: &TTuple) -> String {
         let mut values = String::new();
 
@@ -263,6 +481,9 @@ type {}{} = shape({}\n);",
             TPrimitive::TOption(o) => self.gen_option(o),
             TPrimitive::TDifferentPerLanguage { hack, .. } => self.gen_primitive_type(&hack),
             TPrimitive::TGeneric(g) => self.gen_generic(g),
+            TPrimitive::TReference(r) if self.untyped_enums.contains_key(r.get_name()) => {
+                self.untyped_enums[r.get_name()].to_string()
+            }
             TPrimitive::TReference(r) => {
                 format!(
                     "{}{}",
@@ -284,7 +505,259 @@ type {}{} = shape({}\n);",
             TGeneric::TReference(r, ..) => {
                 self.gen_primitive_type(&TPrimitive::TReference(r.clone()))
             }
+            TGeneric::TPrimitive(p) => self.gen_primitive_type(p),
+        }
+    }
+
+    fn gen_generic_definitions(&self, params: &[TGeneric]) -> String {
+        shared::generic_params(params, |g| self.gen_generic_definition(g))
+    }
+
+    // `T as Bound`. Hack has no defaults, references always provide all
+    // params (see `generics::instantiate`).
+    fn gen_generic_definition(&self, g: &TGeneric) -> String {
+        match g {
+            TGeneric::TDefinition {
+                name, hack_bounds, ..
+            } => match hack_bounds {
+                Some(bounds) => format!("{} as {}", name, bounds),
+                None => name.to_string(),
+            },
+            _ => self.gen_generic(g),
+        }
+    }
+
+    // TypeAssert-style `validateName(mixed $x): Name` that throws if `$x`
+    // doesn't match the type. Validators of generic types take a check for
+    // every generic param.
+    fn gen_validator(&self, declaration: &TypeDeclaration) -> Option<String> {
+        let check = match &declaration.value {
+            DeclarationValue::TPrimitive(p) => self.gen_check(p),
+            DeclarationValue::TMap(m) => self.gen_map_check(m),
+            DeclarationValue::TVec(v) => self.gen_vec_check(v),
+            DeclarationValue::TOption(o) => {
+                self.gen_helper_call("optionOf", &self.gen_option_check(o))
+            }
+            DeclarationValue::TTuple(t) => self.gen_tuple_check(t),
+            DeclarationValue::TStruct(s) => self.gen_struct_check(s, 4),
+            DeclarationValue::TEnum(e) => self.gen_enum_check(e),
+            DeclarationValue::TSimpleEnum(e) => self.gen_helper_call(
+                "oneOf",
+                &format!(
+                    "keyset[{}]",
+                    e.variants
+                        .iter()
+                        .map(|v| match v.discriminant {
+                            Some(d) => d.to_string(),
+                            None => gen_single_quoted(&v.value),
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            ),
+            DeclarationValue::Docs | DeclarationValue::CodeBlock(_) => return None,
+        };
+
+        let name = self.gen_namespaced_name(&declaration.name);
+        let generic_definitions = self.gen_generic_definitions(&declaration.generic_params);
+        let generics = shared::generic_params(&declaration.generic_params, |g| self.gen_generic(g));
+        let return_type = match self.untyped_enums.get(declaration.name.as_ref()) {
+            Some(t) => t.to_string(),
+            None => format!("{}{}", name, generics),
+        };
+        let generic_checks = declaration
+            .generic_params
+            .iter()
+            .filter_map(|g| match g {
+                TGeneric::TDefinition { name, .. } => Some(format!(
+                    "{} $validate_{}, ",
+                    self.gen_namespaced_name("GullCheck"),
+                    name
+                )),
+                TGeneric::TReference(_) | TGeneric::TPrimitive(_) => None,
+            })
+            .collect::<String>();
+
+        // Lambdas of references have to be wrapped to be called right away
+        let check = if check.starts_with('(') {
+            format!("({})", check)
+        } else {
+            check
+        };
+
+        Some(format!(
+            "function validate{}{}(mixed $x, {}string $path = '$'): {} {{
+    {}($x, $path);
+    /* HH_FIXME[4110] checked above */
+    return $x;
+}}",
+            name, generic_definitions, generic_checks, return_type, check
+        ))
+    }
+
+    fn gen_helper(&self, helper: &str) -> String {
+        format!("{}::{}()", self.gen_namespaced_name("Gull"), helper)
+    }
+
+    fn gen_helper_call(&self, helper: &str, arg: &str) -> String {
+        format!("{}::{}({})", self.gen_namespaced_name("Gull"), helper, arg)
+    }
+
+    fn gen_check(&self, ty: &TPrimitive) -> String {
+        match ty {
+            TPrimitive::String => self.gen_helper("isString"),
+            TPrimitive::Tbool => self.gen_helper("isBool"),
+            TPrimitive::Tf64 => self.gen_helper("isFloat"),
+            TPrimitive::Ti64 | TPrimitive::Ti32 | TPrimitive::Tu32 | TPrimitive::Tusize => {
+                self.gen_helper("isInt")
+            }
+            // Hardcoded types are opaque, there's nothing we can check
+            TPrimitive::THardcoded(_) => self.gen_helper("any"),
+            TPrimitive::TVec(v) => self.gen_vec_check(v),
+            TPrimitive::TMap(m) => self.gen_map_check(m),
+            TPrimitive::TOption(o) => self.gen_helper_call("optionOf", &self.gen_option_check(o)),
+            TPrimitive::TDifferentPerLanguage { hack, .. } => self.gen_check(&hack),
+            TPrimitive::TGeneric(g) => self.gen_generic_check(g),
+            TPrimitive::TReference(r) => self.gen_reference_check(r),
+        }
+    }
+
+    fn gen_generic_check(&self, g: &TGeneric) -> String {
+        match g {
+            TGeneric::TDefinition { name, .. } => format!("$validate_{}", name),
+            TGeneric::TReference(r) => self.gen_reference_check(r),
+            TGeneric::TPrimitive(p) => self.gen_check(p),
+        }
+    }
+
+    fn gen_reference_check(&self, r: &TReference) -> String {
+        let generic_checks = r
+            .generic_params
+            .iter()
+            .map(|g| format!("{}, ", self.gen_generic_check(g)))
+            .collect::<String>();
+        format!(
+            "($x, $path) ==> {{ validate{}($x, {}$path); }}",
+            self.gen_namespaced_name(r.get_name()),
+            generic_checks
+        )
+    }
+
+    fn gen_vec_check(&self, v: &TVec) -> String {
+        match v {
+            TVec::TPrimitive(p) => self.gen_helper_call("vecOf", &self.gen_check(p)),
+        }
+    }
+
+    fn gen_set_check(&self, s: &TSet) -> String {
+        match s {
+            TSet::TPrimitive(p) => self.gen_helper_call("keysetOf", &self.gen_check(p)),
+        }
+    }
+
+    fn gen_map_check(&self, m: &TMap) -> String {
+        let value = match &m.value {
+            TMapValue::TPrimitive(p) => self.gen_check(p),
+            TMapValue::TSet(s) => self.gen_set_check(s),
+        };
+        match self.keys.kind(&m.key) {
+            MapKey::Enum(_) => {
+                self.gen_helper_call("dictOf", &format!("{}, {}", value, self.gen_check(&m.key)))
+            }
+            _ => self.gen_helper_call("dictOf", &value),
+        }
+    }
+
+    // check of the value inside of the option
+    fn gen_option_check(&self, o: &TOption) -> String {
+        match o {
+            TOption::TPrimitive(p) => self.gen_check(p),
+            TOption::TMap(m) => self.gen_map_check(m),
+            TOption::TVec(v) => self.gen_vec_check(v),
+            TOption::TSet(s) => self.gen_set_check(s),
+            TOption::TTuple(t) => self.gen_tuple_check(t),
+        }
+    }
+
+    fn gen_tuple_check(&self, t: &TTuple) -> String {
+        let items = t
+            .items
+            .iter()
+            .map(|item| match item {
+                TupleItem::TPrimitive(p) => self.gen_check(p),
+                TupleItem::TOption(o) => {
+                    self.gen_helper_call("optionOf", &self.gen_option_check(o))
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        self.gen_helper_call("tupleOf", &format!("vec[{}]", items))
+    }
+
+    fn gen_struct_check(&self, s: &TStruct, indent: usize) -> String {
+        let prefix = " ".repeat(indent);
+        let mut fields = String::new();
+
+        for field in &self.flattened_fields(s) {
+            let check = match &field.field_type {
+                StructFieldType::TMap(m) => self.gen_map_check(m),
+                StructFieldType::TSet(s) => self.gen_set_check(s),
+                StructFieldType::TPrimitive(p) => self.gen_check(p),
+                StructFieldType::TTuple(t) => self.gen_tuple_check(t),
+                StructFieldType::TVec(v) => self.gen_vec_check(v),
+                StructFieldType::TOption(o) => {
+                    self.gen_helper_call("optionOf", &self.gen_option_check(o))
+                }
+            };
+            let check = self.gen_constrained(check, &field.constraints());
+            fields.push_str(&format!("\n{}    '{}' => {},", prefix, field.name, check));
         }
+
+        self.gen_helper_call("shapeOf", &format!("dict[{}\n{}]", fields, prefix))
+    }
+
+    // Wraps the check of a field with `constrained` if it has constraints
+    fn gen_constrained(&self, check: String, constraints: &[&FieldConstraint]) -> String {
+        if constraints.is_empty() {
+            return check;
+        }
+        let constraints = constraints
+            .iter()
+            .map(|c| match c {
+                FieldConstraint::MinLength(n) => format!("'min_length' => {}", n),
+                FieldConstraint::MaxLength(n) => format!("'max_length' => {}", n),
+                FieldConstraint::Pattern(p) => format!("'pattern' => {}", gen_single_quoted(p)),
+                FieldConstraint::Minimum(n) => format!("'minimum' => {}", n),
+                FieldConstraint::Maximum(n) => format!("'maximum' => {}", n),
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        self.gen_helper_call("constrained", &format!("{}, shape({})", check, constraints))
+    }
+
+    fn gen_enum_check(&self, e: &TEnum) -> String {
+        let mut variants = String::new();
+
+        for variant in &e.variants {
+            let check = match &variant.variant_type {
+                EnumVariantType::TStruct(s) => self.gen_struct_check(s, 8),
+                EnumVariantType::TPrimitive(p) => self.gen_check(p),
+            };
+            variants.push_str(&format!("\n        '{}' => {},", variant.name, check));
+        }
+
+        self.gen_helper_call("enumOf", &format!("dict[{}\n    ]", variants))
+    }
+
+    // Emitted once at the end of the file when validators are enabled. Helper
+    // names are namespaced so that multiple generated files can coexist
+    fn gen_validator_helpers(&self) -> String {
+        VALIDATOR_HELPERS
+            .replace("GullCheck", &self.gen_namespaced_name("GullCheck"))
+            .replace(
+                "class Gull",
+                &format!("class {}", self.gen_namespaced_name("Gull")),
+            )
     }
 
     fn gen_code_block(&self, b: &CodeBlock) -> String {
@@ -299,3 +772,229 @@ type {}{} = shape({}\n);",
         }
     }
 }
+
+// Every check is a `(function(mixed, string): void)` that throws an exception
+// with the path to the invalid value
+const VALIDATOR_HELPERS: &str = r#"
+type GullCheck = (function(mixed, string): void);
+
+abstract final class Gull {
+    public static function fail(mixed $x, string $path, string $expected): nothing {
+        throw new \UnexpectedValueException(
+            $path.': expected '.$expected.', got '.\gettype($x),
+        );
+    }
+
+    public static function any(): GullCheck {
+        return ($x, $path) ==> {};
+    }
+
+    public static function isString(): GullCheck {
+        return ($x, $path) ==> {
+            if (!($x is string)) {
+                self::fail($x, $path, 'string');
+            }
+        };
+    }
+
+    public static function isInt(): GullCheck {
+        return ($x, $path) ==> {
+            if (!($x is int)) {
+                self::fail($x, $path, 'int');
+            }
+        };
+    }
+
+    public static function isFloat(): GullCheck {
+        return ($x, $path) ==> {
+            if (!($x is num)) {
+                self::fail($x, $path, 'float');
+            }
+        };
+    }
+
+    public static function isBool(): GullCheck {
+        return ($x, $path) ==> {
+            if (!($x is bool)) {
+                self::fail($x, $path, 'bool');
+            }
+        };
+    }
+
+    public static function vecOf(GullCheck $item): GullCheck {
+        return ($x, $path) ==> {
+            if (!($x is vec<_>)) {
+                self::fail($x, $path, 'vec');
+            }
+            foreach ($x as $i => $v) {
+                $item($v, $path.'['.$i.']');
+            }
+        };
+    }
+
+    public static function keysetOf(GullCheck $item): GullCheck {
+        return ($x, $path) ==> {
+            if (!($x is vec<_>) && !($x is keyset<_>)) {
+                self::fail($x, $path, 'keyset');
+            }
+            foreach ($x as $i => $v) {
+                $item($v, $path.'['.$i.']');
+            }
+        };
+    }
+
+    public static function dictOf(GullCheck $value, ?GullCheck $key = null): GullCheck {
+        return ($x, $path) ==> {
+            if (!($x is dict<_, _>)) {
+                self::fail($x, $path, 'dict');
+            }
+            foreach ($x as $k => $v) {
+                if ($key is nonnull) {
+                    $key($k, $path.'.'.$k);
+                }
+                $value($v, $path.'.'.$k);
+            }
+        };
+    }
+
+    public static function optionOf(GullCheck $inner): GullCheck {
+        return ($x, $path) ==> {
+            if ($x is nonnull) {
+                $inner($x, $path);
+            }
+        };
+    }
+
+    public static function tupleOf(vec<GullCheck> $items): GullCheck {
+        return ($x, $path) ==> {
+            if (!($x is vec<_>) || \count($x) !== \count($items)) {
+                self::fail($x, $path, \count($items).' items');
+            }
+            foreach ($items as $i => $item) {
+                $item($x[$i], $path.'['.$i.']');
+            }
+        };
+    }
+
+    public static function shapeOf(dict<string, GullCheck> $fields): GullCheck {
+        return ($x, $path) ==> {
+            if (!($x is dict<_, _>)) {
+                self::fail($x, $path, 'shape');
+            }
+            foreach ($fields as $k => $field) {
+                $field(idx($x, $k), $path.'.'.$k);
+            }
+        };
+    }
+
+    public static function enumOf(dict<string, GullCheck> $variants): GullCheck {
+        return ($x, $path) ==> {
+            if (!($x is dict<_, _>) || \count($x) !== 1) {
+                self::fail($x, $path, 'one of '.\implode(', ', \array_keys($variants)));
+            }
+            foreach ($x as $k => $v) {
+                $variant = idx($variants, (string)$k);
+                if ($variant is null) {
+                    self::fail($x, $path, 'one of '.\implode(', ', \array_keys($variants)));
+                }
+                $variant($v, $path.'.'.$k);
+            }
+        };
+    }
+
+    public static function oneOf(keyset<arraykey> $values): GullCheck {
+        return ($x, $path) ==> {
+            if (!($x is arraykey) || !\array_key_exists($x, $values)) {
+                self::fail($x, $path, \implode(' | ', $values));
+            }
+        };
+    }
+
+    public static function violated(string $path, string $expected): nothing {
+        throw new \UnexpectedValueException($path.': expected '.$expected);
+    }
+
+    // Strings are measured in characters, like in Rust and Flow
+    public static function constrained(
+        GullCheck $inner,
+        shape(
+            ?'min_length' => int,
+            ?'max_length' => int,
+            ?'pattern' => string,
+            ?'minimum' => num,
+            ?'maximum' => num,
+        ) $c,
+    ): GullCheck {
+        return ($x, $path) ==> {
+            $inner($x, $path);
+            if ($x is null) {
+                return;
+            }
+            $length = $x is string
+                ? \mb_strlen($x, 'UTF-8')
+                : ($x is Container<_> ? \count($x) : 0);
+            $min_length = Shapes::idx($c, 'min_length');
+            if ($min_length is nonnull && $length < $min_length) {
+                self::violated($path, 'length of at least '.$min_length);
+            }
+            $max_length = Shapes::idx($c, 'max_length');
+            if ($max_length is nonnull && $length > $max_length) {
+                self::violated($path, 'length of at most '.$max_length);
+            }
+            $pattern = Shapes::idx($c, 'pattern');
+            if (
+                $pattern is nonnull &&
+                $x is string &&
+                \preg_match('#'.\str_replace('#', '\\#', $pattern).'#u', $x) !== 1
+            ) {
+                self::violated($path, 'a match of '.$pattern);
+            }
+            if ($x is num) {
+                $minimum = Shapes::idx($c, 'minimum');
+                if ($minimum is nonnull && $x < $minimum) {
+                    self::violated($path, 'at least '.$minimum);
+                }
+                $maximum = Shapes::idx($c, 'maximum');
+                if ($maximum is nonnull && $x > $maximum) {
+                    self::violated($path, 'at most '.$maximum);
+                }
+            }
+        };
+    }
+}
+"#;
+
+// The type of the values of a simple enum
+fn value_type(e: &TSimpleEnum) -> &'static str {
+    if e.is_integer() {
+        "int"
+    } else {
+        "string"
+    }
+}
+
+// `__Deprecated` requires a message
+fn gen_deprecated(d: &Deprecation) -> String {
+    let note = d.note().unwrap_or_else(|| "deprecated".to_string());
+    format!("<<__Deprecated({})>>", gen_single_quoted(&note))
+}
+
+fn gen_deprecated_comment(d: &Deprecation) -> String {
+    match d.note() {
+        Some(note) => format!("// @deprecated {}", note),
+        None => "// @deprecated".to_string(),
+    }
+}
+
+fn gen_double_quoted(s: &str) -> String {
+    format!(
+        "\"{}\"",
+        s.replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('$', "\\$")
+    )
+}
+
+fn gen_single_quoted(s: &str) -> String {
+    format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'"))
+}
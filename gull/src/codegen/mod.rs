@@ -1,3 +1,4 @@
+mod api_docs;
 mod docs;
 mod flow;
 mod hack;
@@ -7,6 +8,7 @@ mod shared;
 use crate::definitions::Declarations;
 use anyhow::Result;
 
+pub use api_docs::{HtmlCodegen, MarkdownCodegen};
 pub use flow::FlowCodegen;
 pub use hack::HackCodegen;
 pub use rust::RustCodegen;
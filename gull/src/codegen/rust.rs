@@ -1,25 +1,48 @@
 use super::docs::{format_docstring, CommentStyle};
 use super::shared;
 use super::Codegen;
+use crate::constraints::Constrained;
+use crate::definitions::declarations::DeclarationsConfig;
 use crate::definitions::*;
+use crate::graph::DependencyGraph;
 use anyhow::Result;
-use std::cell::RefCell;
-use std::collections::BTreeSet;
+use convert_case::{Case, Casing};
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, BTreeSet};
 
 pub struct RustCodegen {
     imports: RefCell<BTreeSet<&'static str>>,
+    // Type name -> index of the cycle of direct references it belongs to.
+    // References between types of the same cycle are boxed, otherwise
+    // the types would have infinite size.
+    cycles: BTreeMap<String, usize>,
+    // Cycle of the declaration that is being generated
+    current_cycle: Cell<Option<usize>>,
+    // Number of collections around the type that is being generated.
+    // Anything inside of a collection is already on the heap.
+    collection_depth: Cell<usize>,
+    // Box every direct reference of the current field (`StructFieldConfig::RustBox`)
+    box_all: Cell<bool>,
+    // `DeclarationsConfig::RustImpls`
+    impls: bool,
 }
 
 impl Codegen for RustCodegen {
     fn gen_declarations(declarations: &Declarations) -> Result<String> {
-        let rc = RustCodegen::new();
+        let rc = RustCodegen::new(declarations);
 
         let mut declarations_code = String::new();
 
-        for declaration in &declarations.declarations {
+        for declaration in declarations.ordered() {
             declarations_code.push('\n');
             declarations_code.push_str(&rc.gen_declaration(declaration)?);
             declarations_code.push('\n');
+
+            if rc.impls {
+                if let Some(impls) = rc.gen_impls(declaration) {
+                    declarations_code.push_str(&format!("\n{}\n", impls));
+                }
+            }
         }
 
         let mut result = String::new();
@@ -36,9 +59,28 @@ impl Codegen for RustCodegen {
 }
 
 impl RustCodegen {
-    fn new() -> Self {
+    fn new(declarations: &Declarations) -> Self {
+        let mut cycles = BTreeMap::new();
+        for (i, cycle) in DependencyGraph::new(declarations)
+            .boxed_cycles()
+            .into_iter()
+            .enumerate()
+        {
+            for name in cycle {
+                cycles.insert(name.to_string(), i);
+            }
+        }
+
         Self {
             imports: RefCell::new(BTreeSet::new()),
+            cycles,
+            current_cycle: Cell::new(None),
+            collection_depth: Cell::new(0),
+            box_all: Cell::new(false),
+            impls: declarations
+                .config
+                .iter()
+                .any(|c| matches!(c, DeclarationsConfig::RustImpls)),
         }
     }
 
@@ -47,6 +89,9 @@ impl RustCodegen {
     }
 
     fn gen_declaration(&self, declaration: &TypeDeclaration) -> Result<String> {
+        self.current_cycle
+            .set(self.cycles.get(declaration.name.as_ref()).copied());
+
         let mut prefix = String::new();
 
         for config in &declaration.config {
@@ -55,19 +100,34 @@ impl RustCodegen {
                     prefix.push_str(attr);
                     prefix.push('\n')
                 }
+                TypeDeclarationConfig::Deprecated(d) => {
+                    prefix.push_str(&gen_deprecated(d));
+                    prefix.push('\n')
+                }
+                TypeDeclarationConfig::Targets(_)
+                | TypeDeclarationConfig::RustVisibility(_)
+                | TypeDeclarationConfig::FlowExactObjects
+                | TypeDeclarationConfig::FlowReadOnlyObjects
+                | TypeDeclarationConfig::FlowReadOnlyArrays
+                | TypeDeclarationConfig::HackOpenShapes
+                | TypeDeclarationConfig::HackEnumStyle(_)
+                | TypeDeclarationConfig::Include(_) => {}
             }
         }
 
+        let visibility = declaration.rust_visibility().prefix();
         let mut r = match &declaration.value {
             DeclarationValue::TPrimitive(p) => format!(
-                "pub type {}{} = {};",
+                "{}type {}{} = {};",
+                visibility,
                 declaration.name,
                 self.gen_generic_param_definitions(&declaration.generic_params),
                 self.gen_primitive_type(p)
             ),
             DeclarationValue::TMap(m) => {
                 format!(
-                    "pub type {}{} = {};",
+                    "{}type {}{} = {};",
+                    visibility,
                     declaration.name,
                     self.gen_generic_param_definitions(&declaration.generic_params),
                     self.gen_map(m)
@@ -75,7 +135,8 @@ impl RustCodegen {
             }
             DeclarationValue::TTuple(t) => {
                 format!(
-                    "pub type {}{} = {};",
+                    "{}type {}{} = {};",
+                    visibility,
                     declaration.name,
                     self.gen_generic_param_definitions(&declaration.generic_params),
                     self.gen_tuple(t)
@@ -83,7 +144,8 @@ impl RustCodegen {
             }
             DeclarationValue::TVec(v) => {
                 format!(
-                    "pub type {}{} = {};",
+                    "{}type {}{} = {};",
+                    visibility,
                     declaration.name,
                     self.gen_generic_param_definitions(&declaration.generic_params),
                     self.gen_vec(v)
@@ -91,7 +153,8 @@ impl RustCodegen {
             }
             DeclarationValue::TOption(o) => {
                 format!(
-                    "pub type {}{} = {};",
+                    "{}type {}{} = {};",
+                    visibility,
                     declaration.name,
                     self.gen_generic_param_definitions(&declaration.generic_params),
                     self.gen_option(o)
@@ -99,7 +162,8 @@ impl RustCodegen {
             }
             DeclarationValue::TStruct(s) => {
                 format!(
-                    "pub struct {}{} {}",
+                    "{}struct {}{} {}",
+                    visibility,
                     declaration.name,
                     self.gen_generic_param_definitions(&declaration.generic_params),
                     self.gen_struct(s, 0, true)
@@ -107,14 +171,26 @@ impl RustCodegen {
             }
             DeclarationValue::TEnum(e) => {
                 format!(
-                    "pub enum {}{} {}",
+                    "{}enum {}{} {}",
+                    visibility,
                     declaration.name,
                     self.gen_generic_param_definitions(&declaration.generic_params),
                     self.gen_enum(e)
                 )
             }
             DeclarationValue::TSimpleEnum(e) => {
-                format!("pub enum {} {}", declaration.name, self.gen_simple_enum(e))
+                let repr = if e.is_integer() {
+                    "#[derive(serde_repr::Serialize_repr, serde_repr::Deserialize_repr)]\n#[repr(i32)]\n"
+                } else {
+                    ""
+                };
+                format!(
+                    "{}{}enum {} {}",
+                    repr,
+                    visibility,
+                    declaration.name,
+                    self.gen_simple_enum(e)
+                )
             }
             DeclarationValue::Docs => String::new(),
             DeclarationValue::CodeBlock(b) => self.gen_code_block(b),
@@ -126,14 +202,25 @@ impl RustCodegen {
             CommentStyle::TripleSlash
         };
 
-        if let Some(doc) = format_docstring(declaration.docs, comment_style, 0) {
+        if let Some(doc) = format_docstring(&declaration.docs, comment_style, 0) {
             r = format!("{}\n{}", doc, r);
         }
 
         Ok(format!("{}{}", prefix, r))
     }
 
+    fn in_collection(&self, f: impl FnOnce() -> String) -> String {
+        self.collection_depth.set(self.collection_depth.get() + 1);
+        let result = f();
+        self.collection_depth.set(self.collection_depth.get() - 1);
+        result
+    }
+
     fn gen_map(&self, m: &TMap) -> String {
+        self.in_collection(|| self.gen_map_inner(m))
+    }
+
+    fn gen_map_inner(&self, m: &TMap) -> String {
         let value = match &m.value {
             TMapValue::TPrimitive(p) => self.gen_primitive_type(p),
             TMapValue::TSet(s) => self.gen_set(s),
@@ -160,14 +247,14 @@ impl RustCodegen {
 
     fn gen_vec(&self, v: &TVec) -> String {
         let value = match &v {
-            TVec::TPrimitive(p) => self.gen_primitive_type(p),
+            TVec::TPrimitive(p) => self.in_collection(|| self.gen_primitive_type(p)),
         };
         format!("Vec<{}>", value)
     }
 
     fn gen_set(&self, s: &TSet) -> String {
         let value = match &s {
-            TSet::TPrimitive(p) => self.gen_primitive_type(p),
+            TSet::TPrimitive(p) => self.in_collection(|| self.gen_primitive_type(p)),
         };
 
         self.add_import("use std::collections::BTreeSet;");
@@ -192,25 +279,25 @@ impl RustCodegen {
 
         for field in s.fields.iter() {
             let mut field_prefix = String::new();
-            let mut value_override = None;
 
             for config in &field.config {
                 match config {
                     StructFieldConfig::RustAttribute(attr) => {
                         field_prefix.push_str(&format!("\n    {}{}", indent, attr))
                     }
-                    StructFieldConfig::RustOverride(o) => value_override = Some(o.to_string()),
+                    StructFieldConfig::Deprecated(d) => {
+                        field_prefix.push_str(&format!("\n    {}{}", indent, gen_deprecated(d)))
+                    }
+                    StructFieldConfig::Flatten => {
+                        field_prefix.push_str(&format!("\n    {}#[serde(flatten)]", indent))
+                    }
+                    StructFieldConfig::RustOverride(_)
+                    | StructFieldConfig::RustBox
+                    | StructFieldConfig::Constraint(_) => {}
                 }
             }
 
-            let field_type = value_override.unwrap_or_else(|| match &field.field_type {
-                StructFieldType::TMap(m) => self.gen_map(m),
-                StructFieldType::TSet(s) => self.gen_set(s),
-                StructFieldType::TOption(o) => self.gen_option(o),
-                StructFieldType::TPrimitive(p) => self.gen_primitive_type(&p),
-                StructFieldType::TTuple(t) => self.gen_tuple(t),
-                StructFieldType::TVec(v) => self.gen_vec(v),
-            });
+            let field_type = self.gen_field_type(field);
 
             let visibility = if pub_fields { "pub " } else { "" };
             let mut field_str = format!(
@@ -219,7 +306,7 @@ impl RustCodegen {
             );
 
             if let Some(doc) =
-                format_docstring(field.docs, CommentStyle::TripleSlash, indent_level + 4)
+                format_docstring(&field.docs, CommentStyle::TripleSlash, indent_level + 4)
             {
                 field_str = format!("\n{}{}", doc, field_str);
             }
@@ -230,6 +317,31 @@ impl RustCodegen {
         format!("{{{}\n{}}}", fields, indent)
     }
 
+    fn gen_field_type(&self, field: &StructField) -> String {
+        let mut value_override = None;
+        for config in &field.config {
+            match config {
+                StructFieldConfig::RustOverride(o) => value_override = Some(o.to_string()),
+                StructFieldConfig::RustBox => self.box_all.set(true),
+                StructFieldConfig::RustAttribute(_)
+                | StructFieldConfig::Deprecated(_)
+                | StructFieldConfig::Flatten
+                | StructFieldConfig::Constraint(_) => {}
+            }
+        }
+
+        let field_type = value_override.unwrap_or_else(|| match &field.field_type {
+            StructFieldType::TMap(m) => self.gen_map(m),
+            StructFieldType::TSet(s) => self.gen_set(s),
+            StructFieldType::TOption(o) => self.gen_option(o),
+            StructFieldType::TPrimitive(p) => self.gen_primitive_type(&p),
+            StructFieldType::TTuple(t) => self.gen_tuple(t),
+            StructFieldType::TVec(v) => self.gen_vec(v),
+        });
+        self.box_all.set(false);
+        field_type
+    }
+
     fn gen_enum(&self, e: &TEnum) -> String {
         let mut variants = String::new();
 
@@ -240,7 +352,10 @@ impl RustCodegen {
             };
 
             variant_type = format!("\n    {}{},", variant.name, variant_type);
-            if let Some(doc) = format_docstring(variant.docs, CommentStyle::TripleSlash, 4) {
+            if let Some(d) = variant.deprecation() {
+                variant_type = format!("\n    {}{}", gen_deprecated(d), variant_type);
+            }
+            if let Some(doc) = format_docstring(&variant.docs, CommentStyle::TripleSlash, 4) {
                 variant_type = format!("\n{}{}", doc, variant_type);
             }
 
@@ -253,7 +368,21 @@ impl RustCodegen {
     fn gen_simple_enum(&self, e: &TSimpleEnum) -> String {
         let mut variants = String::new();
         for variant in &e.variants {
-            variants.push_str(&format!("\n    {},", variant));
+            if let Some(doc) = format_docstring(&variant.docs, CommentStyle::TripleSlash, 4) {
+                variants.push_str(&format!("\n{}", doc));
+            }
+            if let Some(d) = variant.deprecation() {
+                variants.push_str(&format!("\n    {}", gen_deprecated(d)));
+            }
+            match variant.discriminant {
+                Some(d) => variants.push_str(&format!("\n    {} = {},", variant.name, d)),
+                None => {
+                    if variant.value != variant.name {
+                        variants.push_str(&format!("\n    #[serde(rename = {:?})]", variant.value));
+                    }
+                    variants.push_str(&format!("\n    {},", variant.name));
+                }
+            }
         }
 
         format!("{{{}\n}}", variants)
@@ -293,23 +422,43 @@ impl RustCodegen {
             TPrimitive::TMap(m) => self.gen_map(m),
             TPrimitive::TVec(v) => self.gen_vec(v),
             TPrimitive::TOption(o) => self.gen_option(o),
-            TPrimitive::TGeneric(g) => self.gen_generic(g),
+            TPrimitive::TGeneric(g) => {
+                let generic = self.gen_generic(g);
+                match g {
+                    TGeneric::TDefinition { .. } => self.maybe_box(generic, false),
+                    TGeneric::TReference(..) | TGeneric::TPrimitive(_) => generic,
+                }
+            }
             TPrimitive::TReference(r) => {
-                format!(
-                    "{}{}",
-                    r.get_name(),
-                    shared::generic_params(&r.generic_params, |g| self.gen_generic(g))
-                )
+                // Only the reference itself is boxed on request, not its generic arguments
+                let box_all = self.box_all.replace(false);
+                let generic_params =
+                    shared::generic_params(&r.generic_params, |g| self.gen_generic(g));
+                self.box_all.set(box_all);
+
+                let reference = format!("{}{}", r.get_name(), generic_params);
+                let recursive = self.current_cycle.get().is_some()
+                    && self.cycles.get(r.get_name()).copied() == self.current_cycle.get();
+                self.maybe_box(reference, recursive)
             }
         }
     }
 
+    fn maybe_box(&self, value: String, recursive: bool) -> String {
+        if self.collection_depth.get() == 0 && (recursive || self.box_all.get()) {
+            format!("Box<{}>", value)
+        } else {
+            value
+        }
+    }
+
     fn gen_generic(&self, g: &TGeneric) -> String {
         match g {
             TGeneric::TDefinition { name, .. } => name.to_string(),
             TGeneric::TReference(r, ..) => {
                 self.gen_primitive_type(&TPrimitive::TReference(r.clone()))
             }
+            TGeneric::TPrimitive(p) => self.gen_primitive_type(p),
         }
     }
 
@@ -320,11 +469,23 @@ impl RustCodegen {
             let p = params
                 .iter()
                 .map(|g| {
-                    if let TGeneric::TDefinition { name, bounds } = g {
+                    if let TGeneric::TDefinition {
+                        name,
+                        bounds,
+                        default,
+                        ..
+                    } = g
+                    {
                         format!(
-                            "{}{}",
+                            "{}{}{}",
                             name,
-                            bounds.map_or(String::new(), |b| format!(": {}", b))
+                            bounds
+                                .as_ref()
+                                .map_or(String::new(), |b| format!(": {}", b)),
+                            default.as_ref().map_or(String::new(), |d| format!(
+                                " = {}",
+                                self.gen_primitive_type(d)
+                            ))
                         )
                     } else {
                         panic!("Generic param definitiens only accept TGeneric::TDefinition!");
@@ -336,6 +497,232 @@ impl RustCodegen {
         }
     }
 
+    // `impl<T: Bound>` and `Name<T>`
+    fn gen_impl_generics(&self, params: &[TGeneric]) -> (String, String) {
+        let definitions = shared::generic_params(params, |g| match g {
+            TGeneric::TDefinition {
+                name,
+                bounds: Some(bounds),
+                ..
+            } => format!("{}: {}", name, bounds),
+            _ => self.gen_generic(g),
+        });
+        (
+            definitions,
+            shared::generic_params(params, |g| self.gen_generic(g)),
+        )
+    }
+
+    // Convenience impls (`DeclarationsConfig::RustImpls`). Must be generated
+    // right after the declaration, so that references are boxed the same way.
+    fn gen_impls(&self, declaration: &TypeDeclaration) -> Option<String> {
+        let (impl_generics, type_generics) = self.gen_impl_generics(&declaration.generic_params);
+        let name = format!("{}{}", declaration.name, type_generics);
+
+        let (impls, deprecated) = match &declaration.value {
+            DeclarationValue::TStruct(s) => (
+                std::iter::once(self.gen_struct_new(&impl_generics, &name, s))
+                    .chain(gen_struct_validate(&impl_generics, &name, s))
+                    .collect(),
+                s.fields.iter().any(|f| f.deprecation().is_some()),
+            ),
+            DeclarationValue::TEnum(e) => (
+                self.gen_enum_impls(&impl_generics, &name, e),
+                e.variants.iter().any(|v| v.deprecation().is_some()),
+            ),
+            DeclarationValue::TSimpleEnum(e) => (
+                self.gen_simple_enum_impls(&name, e),
+                e.variants.iter().any(|v| v.deprecation().is_some()),
+            ),
+            _ => return None,
+        };
+
+        // Impls of deprecated items would warn in the crate they're generated in
+        let allow = if deprecated || declaration.deprecation().is_some() {
+            "#[allow(deprecated)]\n"
+        } else {
+            ""
+        };
+        Some(
+            impls
+                .iter()
+                .map(|i| format!("{}{}", allow, i))
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+        )
+    }
+
+    // `new()` that takes every field except for optional ones
+    fn gen_struct_new(&self, impl_generics: &str, name: &str, s: &TStruct) -> String {
+        let mut args = vec![];
+        let mut fields = String::new();
+
+        for field in &s.fields {
+            let overridden = field
+                .config
+                .iter()
+                .any(|c| matches!(c, StructFieldConfig::RustOverride(_)));
+            match field.field_type {
+                StructFieldType::TOption(_) if !overridden => {
+                    fields.push_str(&format!("\n            {}: None,", field.name))
+                }
+                _ => {
+                    args.push(format!("{}: {}", field.name, self.gen_field_type(field)));
+                    fields.push_str(&format!("\n            {},", field.name));
+                }
+            }
+        }
+
+        let allow = match args.len() {
+            0 => "    #[allow(clippy::new_without_default)]\n",
+            n if n > 7 => "    #[allow(clippy::too_many_arguments)]\n",
+            _ => "",
+        };
+        format!(
+            "impl{} {} {{
+{}    pub fn new({}) -> Self {{
+        Self {{{}
+        }}
+    }}
+}}",
+            impl_generics,
+            name,
+            allow,
+            args.join(", "),
+            fields
+        )
+    }
+
+    // `From<Payload>` for every variant with a payload that no other variant
+    // has, `is_variant()` for every variant and `as_variant()` for variants
+    // that aren't structs
+    fn gen_enum_impls(&self, impl_generics: &str, name: &str, e: &TEnum) -> Vec<String> {
+        let payloads = e
+            .variants
+            .iter()
+            .map(|v| match &v.variant_type {
+                EnumVariantType::TPrimitive(p) => Some(self.gen_primitive_type(p)),
+                EnumVariantType::TStruct(_) => None,
+            })
+            .collect::<Vec<_>>();
+
+        let mut impls = vec![];
+        let mut methods = vec![];
+
+        for (variant, payload) in e.variants.iter().zip(&payloads) {
+            let method = variant.name.to_case(Case::Snake);
+            let pattern = match payload {
+                Some(_) => "(..)",
+                None => " { .. }",
+            };
+            methods.push(format!(
+                "    pub fn is_{}(&self) -> bool {{
+        matches!(self, Self::{}{})
+    }}",
+                method, variant.name, pattern
+            ));
+
+            let payload = match payload {
+                Some(payload) => payload,
+                None => continue,
+            };
+            // Recursive payloads are boxed, accessors and `From` use the
+            // type inside of the box
+            let (inner, value, boxed_value) = match payload
+                .strip_prefix("Box<")
+                .and_then(|p| p.strip_suffix('>'))
+            {
+                Some(inner) => (inner, "&**value", "Box::new(value)"),
+                None => (payload.as_str(), "value", "value"),
+            };
+            // Matching a single variant is irrefutable
+            let fallback = if e.variants.len() > 1 {
+                "\n            _ => None,"
+            } else {
+                ""
+            };
+            methods.push(format!(
+                "    pub fn as_{}(&self) -> Option<&{}> {{
+        match self {{
+            Self::{}(value) => Some({}),{}
+        }}
+    }}",
+                method, inner, variant.name, value, fallback
+            ));
+
+            // `From<Self>` would conflict with the blanket impl
+            let unique = payloads
+                .iter()
+                .filter(|p| p.as_ref() == Some(payload))
+                .count()
+                == 1;
+            if unique && inner != name {
+                impls.push(format!(
+                    "impl{} From<{}> for {} {{
+    fn from(value: {}) -> Self {{
+        Self::{}({})
+    }}
+}}",
+                    impl_generics, inner, name, inner, variant.name, boxed_value
+                ));
+            }
+        }
+
+        impls.push(format!(
+            "impl{} {} {{
+{}
+}}",
+            impl_generics,
+            name,
+            methods.join("\n\n")
+        ));
+        impls
+    }
+
+    // `Display` and `FromStr` with the same strings as the JSON representation
+    fn gen_simple_enum_impls(&self, name: &str, e: &TSimpleEnum) -> Vec<String> {
+        let display_arms = e
+            .variants
+            .iter()
+            .map(|v| format!("\n            Self::{} => {:?},", v.name, v.wire_value()))
+            .collect::<String>();
+        let from_str_arms = e
+            .variants
+            .iter()
+            .map(|v| {
+                format!(
+                    "\n            {:?} => Ok(Self::{}),",
+                    v.wire_value(),
+                    v.name
+                )
+            })
+            .collect::<String>();
+
+        vec![
+            format!(
+                "impl std::fmt::Display for {} {{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {{
+        f.write_str(match self {{{}
+        }})
+    }}
+}}",
+                name, display_arms
+            ),
+            format!(
+                "impl std::str::FromStr for {} {{
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {{
+        match s {{{}
+            _ => Err(format!(\"unknown {} `{{}}`\", s)),
+        }}
+    }}
+}}",
+                name, from_str_arms, name
+            ),
+        ]
+    }
+
     fn gen_code_block(&self, b: &CodeBlock) -> String {
         match b {
             CodeBlock::Hack(_) => String::new(),
@@ -348,3 +735,124 @@ impl RustCodegen {
         }
     }
 }
+
+fn gen_deprecated(d: &Deprecation) -> String {
+    match (&d.since, &d.message) {
+        (Some(since), Some(note)) => {
+            format!("#[deprecated(since = {:?}, note = {:?})]", since, note)
+        }
+        (Some(since), None) => format!("#[deprecated(since = {:?})]", since),
+        (None, Some(note)) => format!("#[deprecated(note = {:?})]", note),
+        (None, None) => "#[deprecated]".to_string(),
+    }
+}
+
+// `validate()` that checks the constraints of every field, with the same
+// messages as the Hack and Flow validators. Patterns need the `regex` crate
+// and are compiled into a `OnceLock` static on the first call.
+// Fields with a `RustOverride` type are skipped, their type is unknown.
+fn gen_struct_validate(impl_generics: &str, name: &str, s: &TStruct) -> Option<String> {
+    let mut checks = String::new();
+    for field in &s.fields {
+        let constraints = field.constraints();
+        let overridden = field
+            .config
+            .iter()
+            .any(|c| matches!(c, StructFieldConfig::RustOverride(_)));
+        let kind = match Constrained::of(field) {
+            Some(kind) if !constraints.is_empty() && !overridden => kind,
+            _ => continue,
+        };
+
+        let optional = matches!(field.field_type, StructFieldType::TOption(_));
+        let (value, indent) = if optional {
+            ("value".to_string(), "            ")
+        } else {
+            (format!("self.{}", field.name), "        ")
+        };
+        let mut field_checks = String::new();
+        let mut patterns = 0;
+        for constraint in constraints {
+            let condition = match (constraint, kind) {
+                (FieldConstraint::MinLength(1), _) => format!("{}.is_empty()", value),
+                (FieldConstraint::MinLength(n), Constrained::String) => {
+                    format!("{}.chars().count() < {}", value, n)
+                }
+                (FieldConstraint::MaxLength(n), Constrained::String) => {
+                    format!("{}.chars().count() > {}", value, n)
+                }
+                (FieldConstraint::MinLength(n), _) => format!("{}.len() < {}", value, n),
+                (FieldConstraint::MaxLength(n), _) => format!("{}.len() > {}", value, n),
+                (FieldConstraint::Pattern(p), _) => {
+                    // Compiled once, on the first call
+                    let mut pattern =
+                        format!("{}_PATTERN", field.name.to_case(Case::ScreamingSnake));
+                    if patterns > 0 {
+                        pattern = format!("{}_{}", pattern, patterns);
+                    }
+                    patterns += 1;
+                    field_checks.push_str(&format!(
+                        "\n{}static {}: std::sync::OnceLock<regex::Regex> = std::sync::OnceLock::new();",
+                        indent, pattern
+                    ));
+                    format!(
+                        "!{}.get_or_init(|| regex::Regex::new({:?}).unwrap()).is_match(&{})",
+                        pattern, p, value
+                    )
+                }
+                (FieldConstraint::Minimum(n), _) => {
+                    format!("{} < {}", deref(&value, optional), gen_bound(*n, kind))
+                }
+                (FieldConstraint::Maximum(n), _) => {
+                    format!("{} > {}", deref(&value, optional), gen_bound(*n, kind))
+                }
+            };
+            field_checks.push_str(&format!(
+                "\n{}if {} {{\n{}    return Err({:?}.to_string());\n{}}}",
+                indent,
+                condition,
+                indent,
+                format!("$.{}: expected {}", field.name, constraint.expected()),
+                indent
+            ));
+        }
+
+        if optional {
+            checks.push_str(&format!(
+                "\n        if let Some(value) = &self.{} {{{}\n        }}",
+                field.name, field_checks
+            ));
+        } else {
+            checks.push_str(&field_checks);
+        }
+    }
+
+    if checks.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "impl{} {} {{
+    /// Checks the constraints of every field
+    pub fn validate(&self) -> Result<(), String> {{{}
+        Ok(())
+    }}
+}}",
+        impl_generics, name, checks
+    ))
+}
+
+fn deref(value: &str, optional: bool) -> String {
+    if optional {
+        format!("*{}", value)
+    } else {
+        value.to_string()
+    }
+}
+
+// Bounds of integer fields are whole numbers (see `constraints::validate`)
+fn gen_bound(n: f64, kind: Constrained) -> String {
+    match kind {
+        Constrained::Integer => format!("{}", n as i64),
+        _ => format!("{:?}", n),
+    }
+}
@@ -0,0 +1,725 @@
+/*!
+Compare two snapshots of `Declarations` and classify every change by whether
+data serialized to JSON (with serde) by one version can be read by the other.
+
+- **backward compatible**: new code can read data written by old code
+- **forward compatible**: old code can read data written by new code
+- **breaking**: neither of the above
+
+Changes that keep both directions working (e.g. adding an optional field) are
+reported as compatible. Documentation and code blocks are ignored.
+
+```text
+let report = check_compatibility(&load_schema(old)?, &load_schema(new)?);
+if !report.satisfies(Compatibility::Backward) {
+    panic!("{}", report);
+}
+```
+*/
+
+use crate::definitions::*;
+use crate::flatten::Flattener;
+use crate::generics::{walk_declaration, Binding, Substitute};
+use crate::includes;
+use std::collections::BTreeSet;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compatibility {
+    /// Old and new code can read data written by each other
+    Full,
+    /// New code can read data written by old code
+    Backward,
+    /// Old code can read data written by new code
+    Forward,
+    Breaking,
+}
+
+impl Compatibility {
+    fn and(self, other: Compatibility) -> Compatibility {
+        match (self, other) {
+            (Compatibility::Full, c) | (c, Compatibility::Full) => c,
+            (a, b) if a == b => a,
+            _ => Compatibility::Breaking,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Compatibility::Full => "COMPATIBLE",
+            Compatibility::Backward => "BACKWARD",
+            Compatibility::Forward => "FORWARD",
+            Compatibility::Breaking => "BREAKING",
+        }
+    }
+}
+
+impl fmt::Display for Compatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Compatibility::Full => "fully compatible",
+            Compatibility::Backward => "backward compatible",
+            Compatibility::Forward => "forward compatible",
+            Compatibility::Breaking => "breaking",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Change {
+    /// e.g. `Operation.Fetch.items`
+    pub path: String,
+    pub message: String,
+    pub compatibility: Compatibility,
+}
+
+#[derive(Debug, Clone)]
+pub struct CompatReport {
+    pub changes: Vec<Change>,
+}
+
+impl CompatReport {
+    /// Compatibility of all changes combined
+    pub fn compatibility(&self) -> Compatibility {
+        self.changes
+            .iter()
+            .fold(Compatibility::Full, |acc, c| acc.and(c.compatibility))
+    }
+
+    /// Whether all changes together provide at least the `required`
+    /// compatibility. `Compatibility::Breaking` accepts any change.
+    pub fn satisfies(&self, required: Compatibility) -> bool {
+        match (required, self.compatibility()) {
+            (Compatibility::Breaking, _) | (_, Compatibility::Full) => true,
+            (required, actual) => required == actual,
+        }
+    }
+
+    pub fn is_breaking(&self) -> bool {
+        self.compatibility() == Compatibility::Breaking
+    }
+}
+
+impl fmt::Display for CompatReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            writeln!(
+                f,
+                "[{}] {}: {}",
+                change.compatibility.label(),
+                change.path,
+                change.message
+            )?;
+        }
+        if self.changes.is_empty() {
+            write!(f, "No changes")
+        } else {
+            write!(f, "Overall: {}", self.compatibility())
+        }
+    }
+}
+
+/// Compare `old` and `new` declarations and report every change that affects
+/// the JSON representation of the types.
+pub fn check_compatibility(old: &Declarations, new: &Declarations) -> CompatReport {
+    // Invalid includes fail codegen anyway, compare what was declared then
+    let old = &includes::merge(old).unwrap_or_else(|_| old.clone());
+    let new = &includes::merge(new).unwrap_or_else(|_| new.clone());
+    let mut checker = Checker {
+        changes: vec![],
+        old_flattener: Flattener::new(old),
+        new_flattener: Flattener::new(new),
+    };
+
+    let old_types = types(old);
+    let new_types = types(new);
+
+    for old_type in &old_types {
+        match new_types.iter().find(|t| t.name == old_type.name) {
+            Some(new_type) => checker.declaration(old_type, new_type),
+            None => checker.push(
+                &old_type.name,
+                "type was removed".to_string(),
+                Compatibility::Breaking,
+            ),
+        }
+    }
+
+    for new_type in &new_types {
+        if !old_types.iter().any(|t| t.name == new_type.name) {
+            checker.push(
+                &new_type.name,
+                "type was added".to_string(),
+                Compatibility::Full,
+            );
+        }
+    }
+
+    CompatReport {
+        changes: checker.changes,
+    }
+}
+
+fn types(declarations: &Declarations) -> Vec<&TypeDeclaration> {
+    declarations
+        .declarations
+        .iter()
+        .filter(|d| {
+            !matches!(
+                d.value,
+                DeclarationValue::Docs | DeclarationValue::CodeBlock(_)
+            )
+        })
+        .collect()
+}
+
+struct Checker {
+    changes: Vec<Change>,
+    old_flattener: Flattener,
+    new_flattener: Flattener,
+}
+
+impl Checker {
+    fn push(&mut self, path: &str, message: String, compatibility: Compatibility) {
+        self.changes.push(Change {
+            path: path.to_string(),
+            message,
+            compatibility,
+        });
+    }
+
+    fn declaration(&mut self, old: &TypeDeclaration, new: &TypeDeclaration) {
+        let path = &old.name;
+
+        let new = &self.generics(path, old, new);
+
+        match (&old.value, &new.value) {
+            (DeclarationValue::TStruct(a), DeclarationValue::TStruct(b)) => {
+                self.structs(path, a, b)
+            }
+            (DeclarationValue::TEnum(a), DeclarationValue::TEnum(b)) => self.enum_(path, a, b),
+            (DeclarationValue::TSimpleEnum(a), DeclarationValue::TSimpleEnum(b)) => {
+                self.simple_enum(path, a, b)
+            }
+            (DeclarationValue::TPrimitive(a), DeclarationValue::TPrimitive(b)) => {
+                self.type_change(path, describe(a), describe(b), primitive_change(a, b))
+            }
+            (a, b) => {
+                let (old_type, new_type) = (describe_value(a), describe_value(b));
+                self.type_change(path, old_type, new_type, Compatibility::Breaking);
+            }
+        }
+    }
+
+    // Generic params are matched by position. The new declaration is returned
+    // with its params renamed to the old names, so that renaming a param
+    // doesn't show up as a type change of every field that uses it.
+    fn generics(
+        &mut self,
+        path: &str,
+        old: &TypeDeclaration,
+        new: &TypeDeclaration,
+    ) -> TypeDeclaration {
+        let mut bindings = vec![];
+        for (i, param) in new.generic_params.iter().enumerate() {
+            let (name, default) = match param {
+                TGeneric::TDefinition { name, default, .. } => (name, default),
+                _ => continue,
+            };
+            let old_param = match old.generic_params.get(i) {
+                Some(old_param) => old_param,
+                // References that omit the param get its default
+                None if default.is_some() => {
+                    self.push(
+                        path,
+                        format!("generic parameter `{}` was added with a default", name),
+                        Compatibility::Full,
+                    );
+                    continue;
+                }
+                None => {
+                    self.push(
+                        path,
+                        format!("generic parameter `{}` was added", name),
+                        Compatibility::Breaking,
+                    );
+                    continue;
+                }
+            };
+
+            let old_default = match old_param {
+                TGeneric::TDefinition { default, .. } => default,
+                _ => &None,
+            };
+            match (old_default, default) {
+                (Some(a), Some(b)) if describe(a) != describe(b) => self.push(
+                    path,
+                    format!(
+                        "default of generic parameter `{}` changed from `{}` to `{}`",
+                        name,
+                        describe(a),
+                        describe(b)
+                    ),
+                    primitive_change(a, b),
+                ),
+                (Some(_), None) => self.push(
+                    path,
+                    format!("generic parameter `{}` no longer has a default", name),
+                    Compatibility::Breaking,
+                ),
+                _ => {}
+            }
+            bindings.push(Binding {
+                name: name.to_string(),
+                value: old_param.clone(),
+            });
+        }
+        for param in old.generic_params.iter().skip(new.generic_params.len()) {
+            self.push(
+                path,
+                format!(
+                    "generic parameter `{}` was removed",
+                    describe_generic(param)
+                ),
+                Compatibility::Breaking,
+            );
+        }
+
+        let mut new = new.clone();
+        walk_declaration(&mut Substitute(&bindings), &mut new.value)
+            .expect("substituting generic params never fails");
+        new
+    }
+
+    fn type_change(
+        &mut self,
+        path: &str,
+        old_type: String,
+        new_type: String,
+        compatibility: Compatibility,
+    ) {
+        if old_type != new_type {
+            self.push(
+                path,
+                format!("type changed from `{}` to `{}`", old_type, new_type),
+                compatibility,
+            );
+        }
+    }
+
+    // Flattened fields are compared as the fields they inline. Invalid ones
+    // (that codegen would reject) are compared as they're declared
+    fn structs(&mut self, path: &str, old: &TStruct, new: &TStruct) {
+        let old = self
+            .old_flattener
+            .fields(old)
+            .unwrap_or_else(|_| old.fields.clone());
+        let new = self
+            .new_flattener
+            .fields(new)
+            .unwrap_or_else(|_| new.fields.clone());
+        self.fields(path, &old, &new)
+    }
+
+    fn fields(&mut self, path: &str, old: &[StructField], new: &[StructField]) {
+        let field_path = |name: &str| format!("{}.{}", path, name);
+        let mut renamed = BTreeSet::new();
+
+        for (i, old_field) in old.iter().enumerate() {
+            if let Some(new_field) = new.iter().find(|f| f.name == old_field.name) {
+                self.field(&field_path(&old_field.name), old_field, new_field);
+                continue;
+            }
+
+            let alias = new
+                .iter()
+                .find(|f| serde_aliases(&f.config).any(|a| a == old_field.name));
+            // Same position and type, most likely a rename
+            let moved = new.get(i).filter(|f| {
+                !old.iter().any(|o| o.name == f.name)
+                    && describe_field(f) == describe_field(old_field)
+            });
+
+            match alias.or(moved) {
+                Some(new_field) => {
+                    renamed.insert(new_field.name.clone());
+                    let has_alias = alias.is_some();
+                    // Old code always needs the old name, new code finds the
+                    // old name only through an alias
+                    let compatibility = if has_alias {
+                        removed_field(old_field)
+                    } else {
+                        removed_field(old_field).and(added_field(new_field))
+                    };
+                    self.push(
+                        &field_path(&old_field.name),
+                        format!(
+                            "field was renamed to `{}`{}",
+                            new_field.name,
+                            if has_alias {
+                                ""
+                            } else {
+                                " without a `#[serde(alias)]`"
+                            }
+                        ),
+                        compatibility,
+                    );
+                    self.field_type(&field_path(&old_field.name), old_field, new_field);
+                }
+                None => self.push(
+                    &field_path(&old_field.name),
+                    "field was removed".to_string(),
+                    removed_field(old_field),
+                ),
+            }
+        }
+
+        for new_field in new {
+            if !old.iter().any(|f| f.name == new_field.name) && !renamed.contains(&new_field.name) {
+                let message = if can_be_missing(new_field) {
+                    "optional field was added"
+                } else {
+                    "required field was added"
+                };
+                self.push(
+                    &field_path(&new_field.name),
+                    message.to_string(),
+                    added_field(new_field),
+                );
+            }
+        }
+    }
+
+    fn field(&mut self, path: &str, old: &StructField, new: &StructField) {
+        match (can_be_missing(old), can_be_missing(new)) {
+            (true, false) => self.push(
+                path,
+                "field became required".to_string(),
+                Compatibility::Forward,
+            ),
+            (false, true) => self.push(
+                path,
+                "field became optional".to_string(),
+                Compatibility::Backward,
+            ),
+            _ => {}
+        }
+        self.constraints(path, old, new);
+        self.field_type(path, old, new);
+    }
+
+    // New constraints reject data that old code wrote, removed ones accept
+    // data that old code rejects. A changed constraint is both.
+    fn constraints(&mut self, path: &str, old: &StructField, new: &StructField) {
+        let (old, new) = (old.constraints(), new.constraints());
+        for constraint in new.iter().filter(|c| !old.contains(c)) {
+            self.push(
+                path,
+                format!("constraint `{}` was added", constraint),
+                Compatibility::Forward,
+            );
+        }
+        for constraint in old.iter().filter(|c| !new.contains(c)) {
+            self.push(
+                path,
+                format!("constraint `{}` was removed", constraint),
+                Compatibility::Backward,
+            );
+        }
+    }
+
+    fn field_type(&mut self, path: &str, old: &StructField, new: &StructField) {
+        let compatibility = match (&old.field_type, &new.field_type) {
+            (StructFieldType::TPrimitive(a), StructFieldType::TPrimitive(b)) => {
+                primitive_change(a, b)
+            }
+            (
+                StructFieldType::TVec(TVec::TPrimitive(a)),
+                StructFieldType::TVec(TVec::TPrimitive(b)),
+            )
+            | (
+                StructFieldType::TSet(TSet::TPrimitive(a)),
+                StructFieldType::TSet(TSet::TPrimitive(b)),
+            ) => primitive_change(a, b),
+            (StructFieldType::TOption(TOption::TPrimitive(a)), StructFieldType::TPrimitive(b))
+            | (StructFieldType::TPrimitive(a), StructFieldType::TOption(TOption::TPrimitive(b)))
+                if describe(a) == describe(b) =>
+            {
+                // Optionality changes are reported by `field`
+                return;
+            }
+            _ => Compatibility::Breaking,
+        };
+        self.type_change(
+            path,
+            describe_field(old),
+            describe_field(new),
+            compatibility,
+        );
+    }
+
+    fn enum_(&mut self, path: &str, old: &TEnum, new: &TEnum) {
+        for old_variant in &old.variants {
+            let variant_path = format!("{}.{}", path, old_variant.name);
+            match new.variants.iter().find(|v| v.name == old_variant.name) {
+                Some(new_variant) => match (&old_variant.variant_type, &new_variant.variant_type) {
+                    (EnumVariantType::TStruct(a), EnumVariantType::TStruct(b)) => {
+                        self.structs(&variant_path, a, b)
+                    }
+                    (EnumVariantType::TPrimitive(a), EnumVariantType::TPrimitive(b)) => self
+                        .type_change(
+                            &variant_path,
+                            describe(a),
+                            describe(b),
+                            primitive_change(a, b),
+                        ),
+                    (a, b) => self.type_change(
+                        &variant_path,
+                        describe_variant(a),
+                        describe_variant(b),
+                        Compatibility::Breaking,
+                    ),
+                },
+                None => self.push(
+                    &variant_path,
+                    "variant was removed".to_string(),
+                    Compatibility::Forward,
+                ),
+            }
+        }
+
+        for new_variant in &new.variants {
+            if !old.variants.iter().any(|v| v.name == new_variant.name) {
+                self.push(
+                    &format!("{}.{}", path, new_variant.name),
+                    "variant was added".to_string(),
+                    Compatibility::Backward,
+                );
+            }
+        }
+    }
+
+    // Only values matter on the wire, renaming a variant is compatible
+    fn simple_enum(&mut self, path: &str, old: &TSimpleEnum, new: &TSimpleEnum) {
+        if old.is_integer() != new.is_integer() {
+            self.push(
+                path,
+                "values changed between strings and integers".to_string(),
+                Compatibility::Breaking,
+            );
+            return;
+        }
+        let (old, new) = (old.values(), new.values());
+        for variant in &old {
+            if !new.contains(variant) {
+                self.push(
+                    &format!("{}.{}", path, variant),
+                    "variant was removed".to_string(),
+                    Compatibility::Forward,
+                );
+            }
+        }
+        for variant in &new {
+            if !old.contains(variant) {
+                self.push(
+                    &format!("{}.{}", path, variant),
+                    "variant was added".to_string(),
+                    Compatibility::Backward,
+                );
+            }
+        }
+    }
+}
+
+// Old code reading new data can't find the field anymore
+fn removed_field(old: &StructField) -> Compatibility {
+    if can_be_missing(old) {
+        Compatibility::Full
+    } else {
+        Compatibility::Backward
+    }
+}
+
+// New code reading old data can't find the field. Old code ignores unknown fields
+fn added_field(new: &StructField) -> Compatibility {
+    if can_be_missing(new) {
+        Compatibility::Full
+    } else {
+        Compatibility::Forward
+    }
+}
+
+fn can_be_missing(field: &StructField) -> bool {
+    match field.field_type {
+        StructFieldType::TOption(_) => true,
+        _ => serde_attributes(&field.config).any(|a| a.contains("default")),
+    }
+}
+
+fn serde_attributes(config: &[StructFieldConfig]) -> impl Iterator<Item = &str> {
+    config.iter().filter_map(|c| match c {
+        StructFieldConfig::RustAttribute(attr) if attr.contains("serde(") => Some(attr.as_ref()),
+        _ => None,
+    })
+}
+
+// Values of `alias = "..."` in serde attributes
+fn serde_aliases(config: &[StructFieldConfig]) -> impl Iterator<Item = &str> {
+    serde_attributes(config).flat_map(|attr| {
+        attr.split("alias")
+            .skip(1)
+            .filter_map(|rest| rest.trim_start().strip_prefix('='))
+            .filter_map(|rest| rest.trim_start().strip_prefix('"'))
+            .filter_map(|rest| rest.split('"').next())
+    })
+}
+
+// Every value of `from` can be read as `to`
+fn widens(from: &TPrimitive, to: &TPrimitive) -> bool {
+    use TPrimitive::*;
+
+    matches!(
+        (from, to),
+        (Ti32, Ti64) | (Ti32, Tf64) | (Tu32, Ti64) | (Tu32, Tusize) | (Tu32, Tf64) | (Ti64, Tf64)
+    )
+}
+
+fn primitive_change(old: &TPrimitive, new: &TPrimitive) -> Compatibility {
+    if widens(old, new) {
+        Compatibility::Backward
+    } else if widens(new, old) {
+        Compatibility::Forward
+    } else {
+        Compatibility::Breaking
+    }
+}
+
+// Types are described using the schema syntax (see `crate::schema`)
+fn describe(p: &TPrimitive) -> String {
+    match p {
+        TPrimitive::String => "string".to_string(),
+        TPrimitive::Ti64 => "i64".to_string(),
+        TPrimitive::Tf64 => "f64".to_string(),
+        TPrimitive::Ti32 => "i32".to_string(),
+        TPrimitive::Tu32 => "u32".to_string(),
+        TPrimitive::Tusize => "usize".to_string(),
+        TPrimitive::Tbool => "bool".to_string(),
+        TPrimitive::TGeneric(g) => describe_generic(g),
+        TPrimitive::TReference(r) => describe_reference(r),
+        TPrimitive::THardcoded(s) => format!("raw({:?})", s),
+        TPrimitive::TDifferentPerLanguage { hack, rust, flow } => format!(
+            "per_language(rust: {}, hack: {}, flow: {})",
+            describe(rust),
+            describe(hack),
+            describe(flow)
+        ),
+        TPrimitive::TVec(v) => describe_vec(v),
+        TPrimitive::TMap(m) => describe_map(m),
+        TPrimitive::TOption(o) => describe_option(o),
+    }
+}
+
+fn describe_reference(r: &TReference) -> String {
+    if r.generic_params.is_empty() {
+        r.get_name().to_string()
+    } else {
+        format!("{}{}", r.get_name(), describe_generics(&r.generic_params))
+    }
+}
+
+fn describe_generic(g: &TGeneric) -> String {
+    match g {
+        TGeneric::TDefinition { name, .. } => name.to_string(),
+        TGeneric::TReference(r) => describe_reference(r),
+        TGeneric::TPrimitive(p) => describe(p),
+    }
+}
+
+fn describe_generics(generics: &[TGeneric]) -> String {
+    if generics.is_empty() {
+        return String::new();
+    }
+    let generics: Vec<_> = generics.iter().map(describe_generic).collect();
+    format!("<{}>", generics.join(", "))
+}
+
+fn describe_vec(v: &TVec) -> String {
+    match v {
+        TVec::TPrimitive(p) => format!("vec<{}>", describe(p)),
+    }
+}
+
+fn describe_set(s: &TSet) -> String {
+    match s {
+        TSet::TPrimitive(p) => format!("set<{}>", describe(p)),
+    }
+}
+
+fn describe_map(m: &TMap) -> String {
+    let value = match &m.value {
+        TMapValue::TPrimitive(p) => describe(p),
+        TMapValue::TSet(s) => describe_set(s),
+    };
+    let map = match m.t {
+        TMapType::BTree => "map",
+        TMapType::Hash => "hash_map",
+    };
+    format!("{}<{}, {}>", map, describe(&m.key), value)
+}
+
+fn describe_option(o: &TOption) -> String {
+    let inner = match o {
+        TOption::TMap(m) => describe_map(m),
+        TOption::TPrimitive(p) => describe(p),
+        TOption::TSet(s) => describe_set(s),
+        TOption::TVec(v) => describe_vec(v),
+        TOption::TTuple(t) => describe_tuple(t),
+    };
+    format!("option<{}>", inner)
+}
+
+fn describe_tuple(t: &TTuple) -> String {
+    let items: Vec<_> = t
+        .items
+        .iter()
+        .map(|item| match item {
+            TupleItem::TPrimitive(p) => describe(p),
+            TupleItem::TOption(o) => describe_option(o),
+        })
+        .collect();
+    format!("({})", items.join(", "))
+}
+
+fn describe_field(field: &StructField) -> String {
+    match &field.field_type {
+        StructFieldType::TMap(m) => describe_map(m),
+        StructFieldType::TSet(s) => describe_set(s),
+        StructFieldType::TOption(o) => describe_option(o),
+        StructFieldType::TPrimitive(p) => describe(p),
+        StructFieldType::TTuple(t) => describe_tuple(t),
+        StructFieldType::TVec(v) => describe_vec(v),
+    }
+}
+
+fn describe_variant(v: &EnumVariantType) -> String {
+    match v {
+        EnumVariantType::TStruct(_) => "struct".to_string(),
+        EnumVariantType::TPrimitive(p) => describe(p),
+    }
+}
+
+fn describe_value(value: &DeclarationValue) -> String {
+    match value {
+        DeclarationValue::TEnum(_) => "enum".to_string(),
+        DeclarationValue::TSimpleEnum(_) => "simple enum".to_string(),
+        DeclarationValue::TMap(m) => describe_map(m),
+        DeclarationValue::TVec(v) => describe_vec(v),
+        DeclarationValue::TOption(o) => describe_option(o),
+        DeclarationValue::TPrimitive(p) => describe(p),
+        DeclarationValue::TStruct(_) => "struct".to_string(),
+        DeclarationValue::TTuple(t) => describe_tuple(t),
+        DeclarationValue::Docs => "docs".to_string(),
+        DeclarationValue::CodeBlock(_) => "code block".to_string(),
+    }
+}
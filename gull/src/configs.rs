@@ -0,0 +1,108 @@
+/*!
+Configs that can only be set once.
+
+Getters like `TypeDeclaration::rust_visibility()` use the last config of a
+kind, same as codegen does for `DeclarationsConfig`, but setting one twice is
+almost always a mistake (e.g. two `@deprecated` with different messages), so
+codegen rejects it. Rust attributes, includes, target filters and constraints
+of different kinds can be repeated.
+*/
+
+use crate::definitions::declarations::DeclarationsConfig;
+use crate::definitions::*;
+use anyhow::{bail, Result};
+use std::collections::BTreeSet;
+
+/// Fails if a config that can only be set once is set more than once
+pub(crate) fn validate(declarations: &Declarations) -> Result<()> {
+    check(
+        "the declarations",
+        declarations.config.iter().map(declarations_config),
+    )?;
+
+    for declaration in &declarations.declarations {
+        let name = &declaration.name;
+        check(
+            &format!("`{}`", name),
+            declaration.config.iter().map(declaration_config),
+        )?;
+        for field in declaration.structs().iter().flat_map(|s| &s.fields) {
+            check(
+                &format!("field `{}` of `{}`", field.name, name),
+                field.config.iter().map(field_config),
+            )?;
+        }
+
+        let variants = match &declaration.value {
+            DeclarationValue::TEnum(e) => e.variants.iter().map(|v| (&v.name, &v.config)).collect(),
+            DeclarationValue::TSimpleEnum(e) => {
+                e.variants.iter().map(|v| (&v.name, &v.config)).collect()
+            }
+            _ => vec![],
+        };
+        for (variant, config) in variants {
+            check(
+                &format!("variant `{}` of `{}`", variant, name),
+                config.iter().map(variant_config),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn check(what: &str, configs: impl Iterator<Item = Option<&'static str>>) -> Result<()> {
+    let mut seen = BTreeSet::new();
+    for config in configs.flatten() {
+        if !seen.insert(config) {
+            bail!("`{}` is set more than once on {}", config, what);
+        }
+    }
+    Ok(())
+}
+
+// Names of the configs that can only be set once, `None` for the others
+fn declarations_config(c: &DeclarationsConfig) -> Option<&'static str> {
+    Some(match c {
+        DeclarationsConfig::FileHeader(_) => "FileHeader",
+        DeclarationsConfig::HackNamespace(_) => "HackNamespace",
+        DeclarationsConfig::Validators => "Validators",
+        DeclarationsConfig::EmitOrder(_) => "EmitOrder",
+        DeclarationsConfig::RustImpls => "RustImpls",
+    })
+}
+
+fn declaration_config(c: &TypeDeclarationConfig) -> Option<&'static str> {
+    Some(match c {
+        TypeDeclarationConfig::RustAttribute(_)
+        | TypeDeclarationConfig::Targets(_)
+        | TypeDeclarationConfig::Include(_) => return None,
+        TypeDeclarationConfig::RustVisibility(_) => "RustVisibility",
+        TypeDeclarationConfig::Deprecated(_) => "Deprecated",
+        TypeDeclarationConfig::FlowExactObjects => "FlowExactObjects",
+        TypeDeclarationConfig::FlowReadOnlyObjects => "FlowReadOnlyObjects",
+        TypeDeclarationConfig::FlowReadOnlyArrays => "FlowReadOnlyArrays",
+        TypeDeclarationConfig::HackOpenShapes => "HackOpenShapes",
+        TypeDeclarationConfig::HackEnumStyle(_) => "HackEnumStyle",
+    })
+}
+
+fn field_config(c: &StructFieldConfig) -> Option<&'static str> {
+    Some(match c {
+        StructFieldConfig::RustAttribute(_) => return None,
+        StructFieldConfig::RustOverride(_) => "RustOverride",
+        StructFieldConfig::RustBox => "RustBox",
+        StructFieldConfig::Deprecated(_) => "Deprecated",
+        StructFieldConfig::Flatten => "Flatten",
+        StructFieldConfig::Constraint(FieldConstraint::MinLength(_)) => "MinLength",
+        StructFieldConfig::Constraint(FieldConstraint::MaxLength(_)) => "MaxLength",
+        StructFieldConfig::Constraint(FieldConstraint::Pattern(_)) => "Pattern",
+        StructFieldConfig::Constraint(FieldConstraint::Minimum(_)) => "Minimum",
+        StructFieldConfig::Constraint(FieldConstraint::Maximum(_)) => "Maximum",
+    })
+}
+
+fn variant_config(c: &EnumVariantConfig) -> Option<&'static str> {
+    Some(match c {
+        EnumVariantConfig::Deprecated(_) => "Deprecated",
+    })
+}
@@ -0,0 +1,147 @@
+/*!
+Field constraints (`StructFieldConfig::Constraint`).
+
+Lengths apply to strings (counted in characters, not bytes or UTF-16 units)
+and to vecs, sets and maps, patterns to strings and bounds to numbers.
+Constraints are checked against the declared type of the field, so fields
+that reference other declarations can't have any. Patterns have to be valid
+for the `regex` crate, which Rust's `validate()` uses.
+*/
+
+use crate::definitions::*;
+use anyhow::{anyhow, bail, Result};
+
+/// What kind of value a constraint is checked on
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Constrained {
+    String,
+    Integer,
+    Float,
+    /// Vecs, sets and maps
+    Collection,
+}
+
+impl Constrained {
+    /// Kind of the field's value, looking through options
+    pub(crate) fn of(field: &StructField) -> Option<Self> {
+        match &field.field_type {
+            StructFieldType::TMap(_) | StructFieldType::TSet(_) | StructFieldType::TVec(_) => {
+                Some(Constrained::Collection)
+            }
+            StructFieldType::TPrimitive(p) => Self::of_primitive(p),
+            StructFieldType::TOption(o) => match o {
+                TOption::TPrimitive(p) => Self::of_primitive(p),
+                TOption::TMap(_) | TOption::TVec(_) | TOption::TSet(_) => {
+                    Some(Constrained::Collection)
+                }
+                TOption::TTuple(_) => None,
+            },
+            StructFieldType::TTuple(_) => None,
+        }
+    }
+
+    fn of_primitive(p: &TPrimitive) -> Option<Self> {
+        match p {
+            TPrimitive::String => Some(Constrained::String),
+            TPrimitive::Ti64 | TPrimitive::Ti32 | TPrimitive::Tu32 | TPrimitive::Tusize => {
+                Some(Constrained::Integer)
+            }
+            TPrimitive::Tf64 => Some(Constrained::Float),
+            TPrimitive::TVec(_) | TPrimitive::TMap(_) => Some(Constrained::Collection),
+            TPrimitive::Tbool
+            | TPrimitive::TOption(_)
+            | TPrimitive::THardcoded(_)
+            | TPrimitive::TDifferentPerLanguage { .. }
+            | TPrimitive::TGeneric(_)
+            | TPrimitive::TReference(_) => None,
+        }
+    }
+}
+
+/// Checks that every constraint fits the type of its field and that bounds
+/// don't contradict each other
+pub(crate) fn validate(declarations: &Declarations) -> Result<()> {
+    for declaration in &declarations.declarations {
+        for field in declaration.structs().iter().flat_map(|s| &s.fields) {
+            validate_field(field).map_err(|e| anyhow!("{} in `{}`", e, declaration.name))?;
+        }
+    }
+    Ok(())
+}
+
+fn validate_field(field: &StructField) -> Result<()> {
+    let constraints = field.constraints();
+    if constraints.is_empty() {
+        return Ok(());
+    }
+
+    let kind = Constrained::of(field);
+    let (mut min_length, mut max_length) = (None, None);
+    let (mut minimum, mut maximum) = (None, None);
+    for constraint in constraints {
+        let fits = match constraint {
+            FieldConstraint::MinLength(n) => {
+                min_length = Some(*n);
+                matches!(kind, Some(Constrained::String | Constrained::Collection))
+            }
+            FieldConstraint::MaxLength(n) => {
+                max_length = Some(*n);
+                matches!(kind, Some(Constrained::String | Constrained::Collection))
+            }
+            FieldConstraint::Pattern(p) => {
+                // Rust compiles patterns on first use, a broken one would
+                // only show up as a panic in `validate()`
+                if regex::Regex::new(p).is_err() {
+                    bail!(
+                        "`{}` of field `{}` isn't a valid regex",
+                        constraint,
+                        field.name
+                    );
+                }
+                kind == Some(Constrained::String)
+            }
+            FieldConstraint::Minimum(n) | FieldConstraint::Maximum(n) => {
+                if !n.is_finite() {
+                    bail!("`{}` of field `{}` must be finite", constraint, field.name);
+                }
+                if kind == Some(Constrained::Integer) && n.fract() != 0.0 {
+                    bail!(
+                        "`{}` of integer field `{}` must be a whole number",
+                        constraint,
+                        field.name
+                    );
+                }
+                match constraint {
+                    FieldConstraint::Minimum(_) => minimum = Some(*n),
+                    _ => maximum = Some(*n),
+                }
+                matches!(kind, Some(Constrained::Integer | Constrained::Float))
+            }
+        };
+        if !fits {
+            let expected = match constraint {
+                FieldConstraint::MinLength(_) | FieldConstraint::MaxLength(_) => {
+                    "strings, vecs, sets and maps"
+                }
+                FieldConstraint::Pattern(_) => "strings",
+                FieldConstraint::Minimum(_) | FieldConstraint::Maximum(_) => "numbers",
+            };
+            bail!(
+                "`{}` can only be used on {}, field `{}` isn't one",
+                constraint.name(),
+                expected,
+                field.name
+            );
+        }
+    }
+
+    if matches!((min_length, max_length), (Some(min), Some(max)) if min > max)
+        || matches!((minimum, maximum), (Some(min), Some(max)) if min > max)
+    {
+        bail!(
+            "field `{}` has a minimum greater than its maximum",
+            field.name
+        );
+    }
+    Ok(())
+}
@@ -1,6 +1,7 @@
 #![allow(clippy::return_self_not_must_use)]
 
 use super::*;
+use std::borrow::Cow;
 use std::iter::IntoIterator;
 
 macro_rules! unwrap_opt {
@@ -24,11 +25,14 @@ macro_rules! unwrap_opt {
 /********************************************************************************/
 /********************************************************************************/
 
-pub fn decl<'a>(d: &'a mut Declarations, name: &'static str) -> TypeDeclarationBuilder<'a> {
+pub fn decl<'a>(
+    d: &'a mut Declarations,
+    name: impl Into<Cow<'static, str>>,
+) -> TypeDeclarationBuilder<'a> {
     TypeDeclarationBuilder {
         d,
-        name,
-        docs: "",
+        name: name.into(),
+        docs: "".into(),
         value: None,
         config: vec![build_derives(vec![
             "serde::Serialize",
@@ -43,8 +47,8 @@ pub fn decl<'a>(d: &'a mut Declarations, name: &'static str) -> TypeDeclarationB
 #[derive(Debug)]
 pub struct TypeDeclarationBuilder<'a> {
     d: &'a mut Declarations,
-    name: &'static str,
-    docs: &'static str,
+    name: Cow<'static, str>,
+    docs: Cow<'static, str>,
     value: Option<DeclarationValue>,
     config: Vec<TypeDeclarationConfig>,
     generic_params: Vec<TGeneric>,
@@ -57,10 +61,11 @@ impl<'a> TypeDeclarationBuilder<'a> {
     }
 
     pub fn build(self) -> TReference {
+        let value = unwrap_opt!(self, value);
         self.d.add(TypeDeclaration {
             name: self.name,
             docs: self.docs,
-            value: unwrap_opt!(self, value),
+            value,
             config: self.config,
             generic_params: self.generic_params,
         })
@@ -128,27 +133,28 @@ impl TStruct {
 /********************************************************************************/
 /********************************************************************************/
 
-pub fn build_struct_field(name: &'static str) -> StructFieldBuilder {
+pub fn build_struct_field(name: impl Into<Cow<'static, str>>) -> StructFieldBuilder {
     StructFieldBuilder {
-        name,
+        name: name.into(),
         ..Default::default()
     }
 }
 
 #[derive(Default, Debug)]
 pub struct StructFieldBuilder {
-    name: &'static str,
-    pub docs: &'static str,
+    name: Cow<'static, str>,
+    pub docs: Cow<'static, str>,
     pub field_type: Option<StructFieldType>,
     pub config: Vec<StructFieldConfig>,
 }
 
 impl StructFieldBuilder {
     pub fn build(self) -> StructField {
+        let field_type = unwrap_opt!(self, field_type);
         StructField {
             name: self.name,
             docs: self.docs,
-            field_type: unwrap_opt!(self, field_type),
+            field_type,
             config: self.config,
         }
     }
@@ -242,27 +248,31 @@ impl TEnumBuilder {
 /********************************************************************************/
 /********************************************************************************/
 
-pub fn build_enum_variant(name: &'static str) -> EnumVariantBuilder {
+pub fn build_enum_variant(name: impl Into<Cow<'static, str>>) -> EnumVariantBuilder {
     EnumVariantBuilder {
-        name,
-        docs: "",
+        name: name.into(),
+        docs: "".into(),
         variant_type: None,
+        config: vec![],
     }
 }
 
 #[derive(Debug)]
 pub struct EnumVariantBuilder {
-    pub name: &'static str,
-    pub docs: &'static str,
+    pub name: Cow<'static, str>,
+    pub docs: Cow<'static, str>,
     pub variant_type: Option<EnumVariantType>,
+    pub config: Vec<EnumVariantConfig>,
 }
 
 impl EnumVariantBuilder {
     pub fn build(self) -> EnumVariant {
+        let variant_type = unwrap_opt!(self, variant_type);
         EnumVariant {
             name: self.name,
             docs: self.docs,
-            variant_type: unwrap_opt!(self, variant_type),
+            variant_type,
+            config: self.config,
         }
     }
 
@@ -324,6 +334,18 @@ impl From<TReference> for TPrimitive {
     }
 }
 
+/// Generic params and references stay `TGeneric::TDefinition` and
+/// `TGeneric::TReference`, everything else is a concrete `TGeneric::TPrimitive`
+impl From<TPrimitive> for TGeneric {
+    fn from(p: TPrimitive) -> Self {
+        match p {
+            TPrimitive::TGeneric(g) => g,
+            TPrimitive::TReference(r) => TGeneric::TReference(r),
+            p => TGeneric::TPrimitive(Box::new(p)),
+        }
+    }
+}
+
 /********************************************************************************/
 /********************************************************************************/
 /********************************************************************************/
@@ -334,8 +356,7 @@ pub fn build_derives(derives: Vec<&str>) -> TypeDeclarationConfig {
         .map(|s| s.to_string())
         .collect::<Vec<_>>()
         .join(", ");
-    let s = Box::leak(format!("#[derive({})]", derives).into_boxed_str());
-    TypeDeclarationConfig::RustAttribute(s)
+    TypeDeclarationConfig::RustAttribute(format!("#[derive({})]", derives).into())
 }
 /********************************************************************************/
 /********************************************************************************/
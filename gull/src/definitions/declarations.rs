@@ -1,8 +1,32 @@
-use super::{TReference, TypeDeclaration};
-use crate::codegen::{Codegen, FlowCodegen, HackCodegen, RustCodegen};
-use anyhow::Result;
+use super::{Gull, TReference, TargetLanguage, TypeDeclaration};
+use crate::codegen::{
+    Codegen, FlowCodegen, HackCodegen, HtmlCodegen, MarkdownCodegen, RustCodegen,
+};
+use crate::configs;
+use crate::constraints;
+use crate::fixtures::{FixtureGenerator, FixtureKind};
+use crate::flatten;
+use crate::generics;
+use crate::graph::{self, EmitOrder};
+use crate::includes;
+use crate::map_keys;
+use crate::targets;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Cow;
 
-#[derive(Debug)]
+/// Version of the JSON representation produced by `Declarations::to_json`.
+///
+/// Changes to the IR within a version are additive only: new fields are
+/// `#[serde(default)]` and new variants or representations keep accepting
+/// what was serialized before, so every document of this version keeps
+/// loading. Anything else (removing or renaming a field or variant, changing
+/// what a value means) bumps the version. Documents are only guaranteed to
+/// load into the same or a newer gull, older ones reject variants they don't
+/// know. `e2e/json_test.rs` pins a document written by the first v1 release.
+pub const DECLARATIONS_JSON_VERSION: u32 = 1;
+
+#[derive(Debug, Clone)]
 pub struct Declarations {
     pub(crate) declarations: Vec<TypeDeclaration>,
     pub(crate) config: Vec<DeclarationsConfig>,
@@ -22,28 +46,150 @@ impl Declarations {
 
     pub fn add(&mut self, type_declaration: TypeDeclaration) -> TReference {
         let reference = TReference {
-            name: type_declaration.name,
+            name: type_declaration.name.clone(),
             generic_params: vec![],
         };
         self.declarations.push(type_declaration);
         reference
     }
 
+    /// Add a declaration of a type that implements `Gull` (usually through
+    /// `#[derive(Gull)]`)
+    pub fn add_derived<T: Gull>(&mut self) -> TReference {
+        self.add(T::type_declaration())
+    }
+
+    /// Serialize all declarations and config into a versioned JSON document
+    /// that can be consumed by other tools or loaded back with `from_json`.
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        #[derive(Deserialize)]
+        struct Version {
+            version: u32,
+        }
+
+        let Version { version } =
+            serde_json::from_str(json).context("Declarations JSON must have a `version` field")?;
+        check_version(version).map_err(anyhow::Error::msg)?;
+
+        serde_json::from_str(json).context("Failed to parse declarations JSON")
+    }
+
+    /// Declarations in the order codegen emits them in, see `EmitOrder`
+    pub fn ordered(&self) -> Vec<&TypeDeclaration> {
+        graph::emit_order(self)
+    }
+
+    /// Example JSON value of a declared type, see `FixtureKind`
+    pub fn fixture(&self, name: &str, kind: FixtureKind) -> Result<serde_json::Value> {
+        FixtureGenerator::new(self, kind).generate(name)
+    }
+
     pub fn codegen_rust(&self) -> Result<String> {
-        RustCodegen::gen_declarations(&self)
+        RustCodegen::gen_declarations(&self.for_language(TargetLanguage::Rust)?)
     }
 
     pub fn codegen_hack(&self) -> Result<String> {
-        HackCodegen::gen_declarations(&self)
+        HackCodegen::gen_declarations(&self.for_language(TargetLanguage::Hack)?)
     }
 
     pub fn codegen_flow(&self) -> Result<String> {
-        FlowCodegen::gen_declarations(&self)
+        FlowCodegen::gen_declarations(&self.for_language(TargetLanguage::Flow)?)
+    }
+
+    // Declarations that are generated for the given language, with generic
+    // defaults filled in, included structs merged and repeated configs, map
+    // keys, flattened fields and field constraints validated
+    fn for_language(&self, language: TargetLanguage) -> Result<Declarations> {
+        configs::validate(self)?;
+        let declarations = generics::instantiate(&targets::for_target(self, language)?)?;
+        let declarations = includes::merge(&declarations)?;
+        map_keys::validate(&declarations)?;
+        flatten::validate(&declarations)?;
+        constraints::validate(&declarations)?;
+        Ok(declarations)
+    }
+
+    /// API reference with docs, per language signatures and example JSON of
+    /// every type
+    pub fn codegen_markdown(&self) -> Result<String> {
+        MarkdownCodegen::gen_declarations(&includes::merge(&generics::instantiate(self)?)?)
+    }
+
+    /// Same as `codegen_markdown`, as a single HTML page
+    pub fn codegen_html(&self) -> Result<String> {
+        HtmlCodegen::gen_declarations(&includes::merge(&generics::instantiate(self)?)?)
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value")]
 pub enum DeclarationsConfig {
-    FileHeader(&'static str),
-    HackNamespace(&'static str),
+    FileHeader(Cow<'static, str>),
+    HackNamespace(Cow<'static, str>),
+    /// Generate a runtime validator (e.g. `validateFrame(x: mixed): Frame`)
+    /// for every declaration in Hack and Flow
+    Validators,
+    /// Order codegen emits declarations in. Defaults to insertion order
+    EmitOrder(EmitOrder),
+    /// Generate convenience `impl` blocks in Rust: `From` and `is_x()` /
+    /// `as_x()` for enums with data, `Display` and `FromStr` for simple enums
+    /// and `new()` for structs
+    RustImpls,
+}
+
+impl DeclarationsConfig {
+    pub fn hack_namespace(namespace: impl Into<Cow<'static, str>>) -> Self {
+        DeclarationsConfig::HackNamespace(namespace.into())
+    }
+}
+
+fn check_version(version: u32) -> Result<(), String> {
+    if version != DECLARATIONS_JSON_VERSION {
+        return Err(format!(
+            "Unsupported declarations JSON version `{}`. Expected version `{}`",
+            version, DECLARATIONS_JSON_VERSION
+        ));
+    }
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct VersionedDeclarationsRef<'a> {
+    version: u32,
+    config: &'a [DeclarationsConfig],
+    declarations: &'a [TypeDeclaration],
+}
+
+#[derive(Deserialize)]
+struct VersionedDeclarations {
+    version: u32,
+    config: Vec<DeclarationsConfig>,
+    declarations: Vec<TypeDeclaration>,
+}
+
+impl Serialize for Declarations {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        VersionedDeclarationsRef {
+            version: DECLARATIONS_JSON_VERSION,
+            config: &self.config,
+            declarations: &self.declarations,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Declarations {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let versioned = VersionedDeclarations::deserialize(deserializer)?;
+        check_version(versioned.version).map_err(serde::de::Error::custom)?;
+
+        Ok(Declarations {
+            config: versioned.config,
+            declarations: versioned.declarations,
+        })
+    }
 }
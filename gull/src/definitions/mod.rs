@@ -2,8 +2,12 @@ pub mod builders;
 pub mod declarations;
 
 pub use declarations::Declarations;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::fmt;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value")]
 pub enum TPrimitive {
     String,
     Ti64,
@@ -14,7 +18,7 @@ pub enum TPrimitive {
     Tbool,
     TGeneric(TGeneric),
     TReference(TReference),
-    THardcoded(&'static str),
+    THardcoded(Cow<'static, str>),
     TDifferentPerLanguage {
         hack: Box<TPrimitive>,
         rust: Box<TPrimitive>,
@@ -25,16 +29,33 @@ pub enum TPrimitive {
     TOption(Box<TOption>),
 }
 
-#[derive(Debug, Clone)]
+impl TPrimitive {
+    /// Type that is emitted as is, e.g. `crate::types::ID`
+    pub fn hardcoded(t: impl Into<Cow<'static, str>>) -> Self {
+        TPrimitive::THardcoded(t.into())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TReference {
     // private field. must be returned by declarations
-    name: &'static str,
+    name: Cow<'static, str>,
     pub generic_params: Vec<TGeneric>,
 }
 
 impl TReference {
-    pub fn get_name(&self) -> &'static str {
-        self.name
+    /// Reference a declaration by its name. Prefer using the reference returned
+    /// from `Declarations::add`, this is only needed when the referenced
+    /// declaration is added separately (e.g. by `#[derive(Gull)]`).
+    pub fn new(name: impl Into<Cow<'static, str>>) -> Self {
+        TReference {
+            name: name.into(),
+            generic_params: vec![],
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
     }
 
     pub fn primitive(&self) -> TPrimitive {
@@ -42,7 +63,8 @@ impl TReference {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value")]
 pub enum TOption {
     TMap(TMap),
     TPrimitive(TPrimitive),
@@ -51,73 +73,344 @@ pub enum TOption {
     TTuple(TTuple),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value")]
 pub enum TVec {
     TPrimitive(TPrimitive),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value")]
 pub enum TSet {
     TPrimitive(TPrimitive),
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum TMapType {
     Hash,
     BTree,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TMap {
     pub key: TPrimitive,
     pub value: TMapValue,
     pub t: TMapType,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value")]
 pub enum TGeneric {
     // Params that are defined and used within struct/type. no namespacing
     // needed etc.
     TDefinition {
-        name: &'static str,
-        bounds: Option<&'static str>,
+        name: Cow<'static, str>,
+        /// Rust bounds, e.g. `Ord + Clone`
+        bounds: Option<Cow<'static, str>>,
+        /// Hack `as` constraint, e.g. `arraykey`
+        #[serde(default)]
+        hack_bounds: Option<Cow<'static, str>>,
+        /// Flow bound, e.g. `string | number`
+        #[serde(default)]
+        flow_bounds: Option<Cow<'static, str>>,
+        /// Used when a reference omits this param. Params with defaults must
+        /// go after all params without them. Hack doesn't support defaults, so
+        /// omitted params are always filled in before codegen.
+        #[serde(default)]
+        default: Option<Box<TPrimitive>>,
     },
     // generic param that references ather thing. e.g. Vec<OtherTypeReference>
     TReference(TReference),
+    // generic param instantiated with a concrete type, e.g. Vec<string>
+    TPrimitive(Box<TPrimitive>),
 }
 
-#[derive(Debug, Clone)]
+impl TGeneric {
+    /// Generic param without bounds or default. Used both to define a param
+    /// and to refer to it.
+    pub fn param(name: impl Into<Cow<'static, str>>) -> Self {
+        TGeneric::TDefinition {
+            name: name.into(),
+            bounds: None,
+            hack_bounds: None,
+            flow_bounds: None,
+            default: None,
+        }
+    }
+
+    /// Generic param with Rust bounds, e.g. `Ord + Clone`
+    pub fn bounded(
+        name: impl Into<Cow<'static, str>>,
+        bounds: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        TGeneric::TDefinition {
+            name: name.into(),
+            bounds: Some(bounds.into()),
+            hack_bounds: None,
+            flow_bounds: None,
+            default: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value")]
 pub enum TMapValue {
     TPrimitive(TPrimitive),
     TSet(TSet),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TTuple {
     pub items: Vec<TupleItem>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value")]
 pub enum TupleItem {
     TPrimitive(TPrimitive),
     TOption(TOption),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TypeDeclaration {
-    pub name: &'static str,
-    pub docs: &'static str,
+    pub name: Cow<'static, str>,
+    pub docs: Cow<'static, str>,
     pub value: DeclarationValue,
     pub config: Vec<TypeDeclarationConfig>,
     pub generic_params: Vec<TGeneric>,
 }
 
-#[derive(Debug, Clone, Copy)]
+impl TypeDeclaration {
+    /// Declaration without docs, config or generic params
+    pub fn new(name: impl Into<Cow<'static, str>>, value: impl Into<DeclarationValue>) -> Self {
+        TypeDeclaration {
+            name: name.into(),
+            docs: "".into(),
+            value: value.into(),
+            config: vec![],
+            generic_params: vec![],
+        }
+    }
+
+    pub fn docs(mut self, docs: impl Into<Cow<'static, str>>) -> Self {
+        self.docs = docs.into();
+        self
+    }
+
+    pub fn config(mut self, config: TypeDeclarationConfig) -> Self {
+        self.config.push(config);
+        self
+    }
+
+    pub fn generic_params(mut self, generic_params: Vec<TGeneric>) -> Self {
+        self.generic_params = generic_params;
+        self
+    }
+
+    /// Whether the declaration is generated for the given language
+    pub fn is_generated_for(&self, language: TargetLanguage) -> bool {
+        self.config.iter().all(|c| match c {
+            TypeDeclarationConfig::Targets(filter) => filter.includes(language),
+            _ => true,
+        })
+    }
+
+    pub fn rust_visibility(&self) -> RustVisibility {
+        self.config
+            .iter()
+            .rev()
+            .find_map(|c| match c {
+                TypeDeclarationConfig::RustVisibility(v) => Some(*v),
+                _ => None,
+            })
+            .unwrap_or(RustVisibility::Pub)
+    }
+
+    pub fn deprecation(&self) -> Option<&Deprecation> {
+        self.config.iter().rev().find_map(|c| match c {
+            TypeDeclarationConfig::Deprecated(d) => Some(d),
+            _ => None,
+        })
+    }
+
+    pub fn hack_enum_style(&self) -> HackEnumStyle {
+        self.config
+            .iter()
+            .rev()
+            .find_map(|c| match c {
+                TypeDeclarationConfig::HackEnumStyle(style) => Some(*style),
+                _ => None,
+            })
+            .unwrap_or(HackEnumStyle::Enum)
+    }
+
+    /// Structs included with `TypeDeclarationConfig::Include`, in order
+    pub fn includes(&self) -> Vec<&TReference> {
+        self.config
+            .iter()
+            .filter_map(|c| match c {
+                TypeDeclarationConfig::Include(r) => Some(r),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The struct of a struct declaration or the structs of the struct
+    /// variants of an enum
+    pub fn structs(&self) -> Vec<&TStruct> {
+        match &self.value {
+            DeclarationValue::TStruct(s) => vec![s],
+            DeclarationValue::TEnum(e) => e
+                .variants
+                .iter()
+                .filter_map(|v| match &v.variant_type {
+                    EnumVariantType::TStruct(s) => Some(s),
+                    EnumVariantType::TPrimitive(_) => None,
+                })
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Whether a flag like `TypeDeclarationConfig::HackOpenShapes` is set
+    pub fn has_config(&self, flag: &TypeDeclarationConfig) -> bool {
+        self.config
+            .iter()
+            .any(|c| std::mem::discriminant(c) == std::mem::discriminant(flag))
+    }
+}
+
+/// Types that can describe themselves as a gull `TypeDeclaration`.
+/// Usually implemented with `#[derive(Gull)]` (requires the `derive` feature)
+pub trait Gull {
+    fn type_declaration() -> TypeDeclaration;
+}
+
+/// Configs that can only be set once are rejected by codegen when they're
+/// repeated. Getters use the last one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value")]
 pub enum TypeDeclarationConfig {
-    RustAttribute(&'static str),
+    RustAttribute(Cow<'static, str>),
+    /// Languages the declaration is generated for. Declarations are generated
+    /// for every language by default.
+    Targets(TargetFilter),
+    /// Visibility of the generated Rust type. `pub` by default.
+    RustVisibility(RustVisibility),
+    Deprecated(Deprecation),
+    /// Flow exact objects `{| |}` for structs and enums
+    FlowExactObjects,
+    /// Wrap Flow objects (structs, enums and maps) in `$ReadOnly<>`
+    FlowReadOnlyObjects,
+    /// `$ReadOnlyArray<>` for vecs and sets in Flow
+    FlowReadOnlyArrays,
+    /// Hack open shapes `shape(..., ...)` that allow unknown fields
+    HackOpenShapes,
+    /// How a simple enum (or the variant type enum of an enum with data) is
+    /// generated in Hack
+    HackEnumStyle(HackEnumStyle),
+    /// Struct whose fields are merged into this struct, ahead of its own
+    /// fields. Flow spreads the included type instead.
+    Include(TReference),
+}
+
+impl TypeDeclarationConfig {
+    pub fn rust_attribute(attribute: impl Into<Cow<'static, str>>) -> Self {
+        TypeDeclarationConfig::RustAttribute(attribute.into())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HackEnumStyle {
+    /// `enum X: string as string`, the default
+    Enum,
+    /// `enum class X: string`. Values are typed as `string`
+    EnumClass,
+    /// `abstract final class X` with a string constant per variant. Values
+    /// are typed as `string`
+    ClassConstants,
+}
+
+/// Deprecation of a declaration, struct field or enum variant. Emitted in a
+/// form that each language's tooling warns about.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Deprecation {
+    /// `None` for a bare `#[deprecated]`
+    pub message: Option<Cow<'static, str>>,
+    /// Version the item was deprecated in
+    pub since: Option<Cow<'static, str>>,
 }
 
-#[derive(Debug, Clone)]
+impl Deprecation {
+    pub fn new(message: impl Into<Cow<'static, str>>) -> Self {
+        Deprecation {
+            message: Some(message.into()),
+            since: None,
+        }
+    }
+
+    pub fn since(mut self, version: impl Into<Cow<'static, str>>) -> Self {
+        self.since = Some(version.into());
+        self
+    }
+
+    /// Message with the version appended, for languages that don't have a
+    /// separate place for it. `None` if there's neither
+    pub fn note(&self) -> Option<String> {
+        match (&self.message, &self.since) {
+            (Some(message), Some(since)) => Some(format!("{} (since {})", message, since)),
+            (Some(message), None) => Some(message.to_string()),
+            (None, Some(since)) => Some(format!("since {}", since)),
+            (None, None) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TargetLanguage {
+    Rust,
+    Hack,
+    Flow,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value")]
+pub enum TargetFilter {
+    Only(Vec<TargetLanguage>),
+    Except(Vec<TargetLanguage>),
+}
+
+impl TargetFilter {
+    pub fn includes(&self, language: TargetLanguage) -> bool {
+        match self {
+            TargetFilter::Only(languages) => languages.contains(&language),
+            TargetFilter::Except(languages) => !languages.contains(&language),
+        }
+    }
+}
+
+/// Ordered from the least to the most visible
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum RustVisibility {
+    Private,
+    PubCrate,
+    Pub,
+}
+
+impl RustVisibility {
+    /// Keyword(s) a declaration starts with, including the trailing space
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            RustVisibility::Private => "",
+            RustVisibility::PubCrate => "pub(crate) ",
+            RustVisibility::Pub => "pub ",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value")]
 pub enum DeclarationValue {
     TEnum(TEnum),
     TSimpleEnum(TSimpleEnum),
@@ -131,33 +424,173 @@ pub enum DeclarationValue {
     CodeBlock(CodeBlock),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value")]
 pub enum CodeBlock {
-    Rust(Vec<&'static str>),
-    Hack(Vec<&'static str>),
-    Flow(Vec<&'static str>),
+    Rust(Vec<Cow<'static, str>>),
+    Hack(Vec<Cow<'static, str>>),
+    Flow(Vec<Cow<'static, str>>),
+}
+
+impl CodeBlock {
+    pub fn rust<S: Into<Cow<'static, str>>>(lines: impl IntoIterator<Item = S>) -> Self {
+        CodeBlock::Rust(lines.into_iter().map(Into::into).collect())
+    }
+
+    pub fn hack<S: Into<Cow<'static, str>>>(lines: impl IntoIterator<Item = S>) -> Self {
+        CodeBlock::Hack(lines.into_iter().map(Into::into).collect())
+    }
+
+    pub fn flow<S: Into<Cow<'static, str>>>(lines: impl IntoIterator<Item = S>) -> Self {
+        CodeBlock::Flow(lines.into_iter().map(Into::into).collect())
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TStruct {
     pub fields: Vec<StructField>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StructField {
-    pub name: &'static str,
-    pub docs: &'static str,
+    pub name: Cow<'static, str>,
+    pub docs: Cow<'static, str>,
     pub field_type: StructFieldType,
     pub config: Vec<StructFieldConfig>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value")]
 pub enum StructFieldConfig {
-    RustAttribute(&'static str),
-    RustOverride(&'static str),
+    RustAttribute(Cow<'static, str>),
+    RustOverride(Cow<'static, str>),
+    /// Wrap references stored by value in `Box` in Rust. Recursive
+    /// references are boxed automatically, this is for the rest (e.g. to
+    /// make a large enum variant smaller).
+    RustBox,
+    Deprecated(Deprecation),
+    /// The field references a struct whose fields are serialized inline, as
+    /// if they were fields of this struct (`#[serde(flatten)]`)
+    Flatten,
+    Constraint(FieldConstraint),
+}
+
+impl StructFieldConfig {
+    pub fn rust_attribute(attribute: impl Into<Cow<'static, str>>) -> Self {
+        StructFieldConfig::RustAttribute(attribute.into())
+    }
+
+    /// Type of the field in Rust instead of the generated one
+    pub fn rust_override(t: impl Into<Cow<'static, str>>) -> Self {
+        StructFieldConfig::RustOverride(t.into())
+    }
+}
+
+/// Invariant of a field's value. Checked by the Hack and Flow validators and
+/// by `validate()` of Rust structs (`DeclarationsConfig::RustImpls`).
+/// Constraints of optional fields only apply to values that are set.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value")]
+pub enum FieldConstraint {
+    /// Minimum number of characters of a string or items of a vec, set or map
+    MinLength(u32),
+    /// Maximum number of characters of a string or items of a vec, set or map
+    MaxLength(u32),
+    /// Regex that a string has to contain a match of, like JSON Schema's
+    /// `pattern`. Anchor it with `^...$` to match the whole string.
+    Pattern(Cow<'static, str>),
+    /// Inclusive lower bound of a number
+    Minimum(f64),
+    /// Inclusive upper bound of a number
+    Maximum(f64),
+}
+
+impl FieldConstraint {
+    /// Name of the schema attribute, e.g. `min_length`
+    pub fn name(&self) -> &'static str {
+        match self {
+            FieldConstraint::MinLength(_) => "min_length",
+            FieldConstraint::MaxLength(_) => "max_length",
+            FieldConstraint::Pattern(_) => "pattern",
+            FieldConstraint::Minimum(_) => "min",
+            FieldConstraint::Maximum(_) => "max",
+        }
+    }
+
+    /// What a value that violates the constraint was expected to be, e.g.
+    /// `length of at least 1`. Every language reports the same message.
+    pub fn expected(&self) -> String {
+        match self {
+            FieldConstraint::MinLength(n) => format!("length of at least {}", n),
+            FieldConstraint::MaxLength(n) => format!("length of at most {}", n),
+            FieldConstraint::Pattern(p) => format!("a match of {}", p),
+            FieldConstraint::Minimum(n) => format!("at least {}", n),
+            FieldConstraint::Maximum(n) => format!("at most {}", n),
+        }
+    }
+}
+
+impl fmt::Display for FieldConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldConstraint::MinLength(n) | FieldConstraint::MaxLength(n) => {
+                write!(f, "{}({})", self.name(), n)
+            }
+            FieldConstraint::Pattern(p) => write!(f, "{}({:?})", self.name(), p),
+            FieldConstraint::Minimum(n) | FieldConstraint::Maximum(n) => {
+                write!(f, "{}({})", self.name(), n)
+            }
+        }
+    }
 }
 
-#[derive(Debug, Clone)]
+impl StructField {
+    /// Field without docs or config
+    pub fn new(name: impl Into<Cow<'static, str>>, field_type: impl Into<StructFieldType>) -> Self {
+        StructField {
+            name: name.into(),
+            docs: "".into(),
+            field_type: field_type.into(),
+            config: vec![],
+        }
+    }
+
+    pub fn docs(mut self, docs: impl Into<Cow<'static, str>>) -> Self {
+        self.docs = docs.into();
+        self
+    }
+
+    pub fn config(mut self, config: StructFieldConfig) -> Self {
+        self.config.push(config);
+        self
+    }
+
+    pub fn deprecation(&self) -> Option<&Deprecation> {
+        self.config.iter().rev().find_map(|c| match c {
+            StructFieldConfig::Deprecated(d) => Some(d),
+            _ => None,
+        })
+    }
+
+    pub fn is_flattened(&self) -> bool {
+        self.config
+            .iter()
+            .any(|c| matches!(c, StructFieldConfig::Flatten))
+    }
+
+    pub fn constraints(&self) -> Vec<&FieldConstraint> {
+        self.config
+            .iter()
+            .filter_map(|c| match c {
+                StructFieldConfig::Constraint(c) => Some(c),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value")]
 pub enum StructFieldType {
     TMap(TMap),
     TSet(TSet),
@@ -167,19 +600,62 @@ pub enum StructFieldType {
     TVec(TVec),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TEnum {
     pub variants: Vec<EnumVariant>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EnumVariant {
-    pub name: &'static str,
-    pub docs: &'static str,
+    pub name: Cow<'static, str>,
+    pub docs: Cow<'static, str>,
     pub variant_type: EnumVariantType,
+    #[serde(default)]
+    pub config: Vec<EnumVariantConfig>,
+}
+
+impl EnumVariant {
+    /// Variant without docs or config
+    pub fn new(
+        name: impl Into<Cow<'static, str>>,
+        variant_type: impl Into<EnumVariantType>,
+    ) -> Self {
+        EnumVariant {
+            name: name.into(),
+            docs: "".into(),
+            variant_type: variant_type.into(),
+            config: vec![],
+        }
+    }
+
+    pub fn docs(mut self, docs: impl Into<Cow<'static, str>>) -> Self {
+        self.docs = docs.into();
+        self
+    }
+
+    pub fn config(mut self, config: EnumVariantConfig) -> Self {
+        self.config.push(config);
+        self
+    }
+
+    // Same as the other `deprecation()`s, `Deprecated` just happens to be
+    // the only variant config so far
+    #[allow(clippy::unnecessary_find_map)]
+    pub fn deprecation(&self) -> Option<&Deprecation> {
+        self.config.iter().rev().find_map(|c| match c {
+            EnumVariantConfig::Deprecated(d) => Some(d),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value")]
+pub enum EnumVariantConfig {
+    Deprecated(Deprecation),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value")]
 pub enum EnumVariantType {
     // WARNING: This enum is pretty limited in what it can represent. Since hack
     // and flow don't have enums, we need to be careful at how we represent the
@@ -207,7 +683,118 @@ pub enum EnumVariantType {
 /// It will serialize in a single string value (instead of the object/string
 /// stuff that we get with full featured enums). This easily maps to hack enums
 /// and flow string union types.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TSimpleEnum {
-    pub variants: Vec<&'static str>,
+    pub variants: Vec<SimpleEnumVariant>,
+}
+
+impl TSimpleEnum {
+    /// Enum of variants that serialize to their names, e.g.
+    /// `TSimpleEnum::new(["Full", "Delta"])`
+    pub fn new<V: Into<SimpleEnumVariant>>(variants: impl IntoIterator<Item = V>) -> Self {
+        TSimpleEnum {
+            variants: variants.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Whether the variants serialize to their integer discriminants
+    /// (`serde_repr` in Rust) instead of strings
+    pub fn is_integer(&self) -> bool {
+        !self.variants.is_empty() && self.variants.iter().all(|v| v.discriminant.is_some())
+    }
+
+    /// Values the variants serialize to, integers are formatted as strings
+    pub fn values(&self) -> Vec<Cow<'static, str>> {
+        self.variants.iter().map(|v| v.wire_value()).collect()
+    }
+}
+
+/// Variant of a simple enum. `name` is the identifier in Rust (and the
+/// SCREAMING_SNAKE constant in Hack), `value` is the string it serializes to.
+/// Integer enums serialize to the `discriminant` instead, either every
+/// variant of an enum has one or none does.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "SimpleEnumVariantRepr")]
+pub struct SimpleEnumVariant {
+    pub name: Cow<'static, str>,
+    pub value: Cow<'static, str>,
+    pub docs: Cow<'static, str>,
+    pub config: Vec<EnumVariantConfig>,
+    pub discriminant: Option<i32>,
+}
+
+impl SimpleEnumVariant {
+    /// Variant that serializes to its name
+    pub fn new(name: impl Into<Cow<'static, str>>) -> Self {
+        let name = name.into();
+        SimpleEnumVariant {
+            value: name.clone(),
+            name,
+            docs: "".into(),
+            config: vec![],
+            discriminant: None,
+        }
+    }
+
+    /// `value` or, in integer enums, the discriminant
+    pub fn wire_value(&self) -> Cow<'static, str> {
+        match self.discriminant {
+            Some(d) => d.to_string().into(),
+            None => self.value.clone(),
+        }
+    }
+
+    // Same as the other `deprecation()`s, `Deprecated` just happens to be
+    // the only variant config so far
+    #[allow(clippy::unnecessary_find_map)]
+    pub fn deprecation(&self) -> Option<&Deprecation> {
+        self.config.iter().rev().find_map(|c| match c {
+            EnumVariantConfig::Deprecated(d) => Some(d),
+        })
+    }
+}
+
+impl From<&'static str> for SimpleEnumVariant {
+    fn from(name: &'static str) -> Self {
+        SimpleEnumVariant::new(name)
+    }
+}
+
+// Variants used to be plain strings, which is still accepted when reading
+// declarations JSON
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SimpleEnumVariantRepr {
+    Name(Cow<'static, str>),
+    Variant {
+        name: Cow<'static, str>,
+        value: Option<Cow<'static, str>>,
+        #[serde(default)]
+        docs: Cow<'static, str>,
+        #[serde(default)]
+        config: Vec<EnumVariantConfig>,
+        #[serde(default)]
+        discriminant: Option<i32>,
+    },
+}
+
+impl From<SimpleEnumVariantRepr> for SimpleEnumVariant {
+    fn from(repr: SimpleEnumVariantRepr) -> Self {
+        match repr {
+            SimpleEnumVariantRepr::Name(name) => SimpleEnumVariant::new(name),
+            SimpleEnumVariantRepr::Variant {
+                name,
+                value,
+                docs,
+                config,
+                discriminant,
+            } => SimpleEnumVariant {
+                value: value.unwrap_or_else(|| name.clone()),
+                name,
+                docs,
+                config,
+                discriminant,
+            },
+        }
+    }
 }
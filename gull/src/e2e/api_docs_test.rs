@@ -0,0 +1,386 @@
+use crate::prelude::*;
+use anyhow::Result;
+
+const SCHEMA: &str = r#"
+hack_namespace "Graph";
+
+//! ==============================
+//! Graph types
+//! ==============================
+//! Types shared by the graph service
+
+/// Node identifier
+type NodeID = i64;
+
+/// A single node in the graph
+struct Node<T> {
+    /// Node identifier
+    id: NodeID,
+    value: T,
+    /// Links to other nodes,
+    /// keyed by their ID
+    edges: map<NodeID, vec<NodeID>>,
+    parent?: NodeID,
+}
+
+enum StorageType { Full, Delta }
+
+enum Operation {
+    /// Read nodes
+    Fetch { items: vec<NodeID>, storage: StorageType },
+    Store(Node<NodeID>),
+}
+"#;
+
+#[test]
+fn markdown_test() -> Result<()> {
+    let declarations = parse_schema(SCHEMA)?;
+    k9::snapshot!(
+        declarations.codegen_markdown()?,
+        r#"
+# API reference
+
+- [Graph types](#graph-types)
+  - [NodeID](#nodeid)
+  - [Node](#node)
+  - [StorageType](#storagetype)
+  - [Operation](#operation)
+
+## Graph types
+
+Types shared by the graph service
+
+### NodeID
+
+Node identifier
+
+Type: i64
+
+**Rust**
+
+```rust
+pub type NodeID = i64;
+```
+
+**Hack**
+
+```hack
+type GraphNodeID = int;
+```
+
+**Flow**
+
+```js
+export type NodeID = number;
+```
+
+**Example**
+
+```json
+9007199254740991
+```
+
+### Node
+
+A single node in the graph
+
+| Field | Type | Description |
+| --- | --- | --- |
+| `id` | [NodeID](#nodeid) | Node identifier |
+| `value` | T |  |
+| `edges` | map&lt;[NodeID](#nodeid), vec&lt;[NodeID](#nodeid)&gt;&gt; | Links to other nodes, keyed by their ID |
+| `parent` | option&lt;[NodeID](#nodeid)&gt; |  |
+
+**Rust**
+
+```rust
+pub struct Node<T> {
+    pub id: NodeID,
+    pub value: T,
+    pub edges: BTreeMap<NodeID, Vec<NodeID>>,
+    pub parent: Option<NodeID>,
+}
+```
+
+**Hack**
+
+```hack
+type GraphNode<T> = shape(
+    'id' => GraphNodeID,
+    'value' => T,
+    'edges' => dict<GraphNodeID, vec<GraphNodeID>>,
+    ?'parent' => GraphNodeID,
+);
+```
+
+**Flow**
+
+```js
+export type Node<T> = {
+    'id': NodeID,
+    'value': T,
+    'edges': {[key: NodeID]: Array<NodeID>},
+    'parent'?: NodeID,
+};
+```
+
+### StorageType
+
+| Value |
+| --- |
+| `"Full"` |
+| `"Delta"` |
+
+**Rust**
+
+```rust
+pub enum StorageType {
+    Full,
+    Delta,
+}
+```
+
+**Hack**
+
+```hack
+enum GraphStorageType: string as string {
+    FULL = "Full";
+    DELTA = "Delta";
+}
+```
+
+**Flow**
+
+```js
+export type StorageType = "Full" | "Delta";
+
+export enum StorageTypeEnum {Full, Delta};
+```
+
+**Example**
+
+```json
+"Delta"
+```
+
+### Operation
+
+| Variant | Type | Description |
+| --- | --- | --- |
+| `Fetch` | { items: vec&lt;[NodeID](#nodeid)&gt;, storage: [StorageType](#storagetype) } | Read nodes |
+| `Store` | [Node](#node)&lt;[NodeID](#nodeid)&gt; |  |
+
+**Rust**
+
+```rust
+pub enum Operation {
+    Fetch {
+        items: Vec<NodeID>,
+        storage: StorageType,
+    },
+    Store(Node<NodeID>),
+}
+```
+
+**Hack**
+
+```hack
+enum GraphOperationType: string as string {
+    FETCH = "Fetch";
+    STORE = "Store";
+}
+
+type GraphOperation = shape(
+    ?'Fetch' =>  shape(
+        'items' => vec<GraphNodeID>,
+        'storage' => GraphStorageType,
+    ),
+    ?'Store' => GraphNode<GraphNodeID>,
+);
+
+function GraphOperation_getType(GraphOperation $x): GraphOperationType {
+    if (Shapes::keyExists($x, 'Fetch')) {
+        return GraphOperationType::FETCH;
+    }
+    if (Shapes::keyExists($x, 'Store')) {
+        return GraphOperationType::STORE;
+    }
+    invariant_violation('No variant of GraphOperation is set');
+}
+
+function GraphOperation_getFetch(GraphOperation $x): ?shape(
+    'items' => vec<GraphNodeID>,
+    'storage' => GraphStorageType,
+) {
+    return Shapes::idx($x, 'Fetch');
+}
+
+function GraphOperation_getStore(GraphOperation $x): ?GraphNode<GraphNodeID> {
+    return Shapes::idx($x, 'Store');
+}
+```
+
+**Flow**
+
+```js
+export type OperationType = "Fetch" | "Store";
+
+export type Operation = {
+    'Fetch'?:  {
+        'items': Array<NodeID>,
+        'storage': StorageType,
+    },
+    'Store'?: Node<NodeID>,
+};
+
+export function getOperationType(x: Operation): OperationType {
+    if (x.Fetch !== undefined) {
+        return 'Fetch';
+    }
+    if (x.Store !== undefined) {
+        return 'Store';
+    }
+    throw new Error('No variant of Operation is set');
+}
+
+export function operationFetch(value: {
+    'items': Array<NodeID>,
+    'storage': StorageType,
+}): Operation {
+    return {'Fetch': value};
+}
+
+export function operationStore(value: Node<NodeID>): Operation {
+    return {'Store': value};
+}
+
+export function matchOperation<TResult>(
+    x: Operation,
+    cases: {|
+        'Fetch': (value: {
+            'items': Array<NodeID>,
+            'storage': StorageType,
+        }) => TResult,
+        'Store': (value: Node<NodeID>) => TResult,
+    |},
+): TResult {
+    if (x.Fetch !== undefined) {
+        return cases.Fetch(x.Fetch);
+    }
+    if (x.Store !== undefined) {
+        return cases.Store(x.Store);
+    }
+    throw new Error('No variant of Operation is set');
+}
+```
+
+**Example**
+
+```json
+{
+  "Store": {
+    "id": 9007199254740991,
+    "value": 9007199254740991,
+    "edges": {
+      "9007199254740991": [
+        9007199254740991
+      ]
+    },
+    "parent": 9007199254740991
+  }
+}
+```
+
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn html_test() -> Result<()> {
+    let declarations = parse_schema(
+        r#"
+        //! Graph types
+        /// A <b>node</b> & its links
+        struct Node { id: i64, edges: vec<Node> }
+        "#,
+    )?;
+    k9::snapshot!(
+        declarations.codegen_html()?,
+        r##"
+<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>API reference</title>
+<style>
+body { font-family: sans-serif; max-width: 960px; margin: 0 auto; padding: 1em; }
+table { border-collapse: collapse; }
+td, th { border: 1px solid #ddd; padding: 4px 8px; text-align: left; }
+pre { background: #f6f8fa; padding: 8px; overflow-x: auto; }
+</style>
+</head>
+<body>
+<h1 id="api-reference">API reference</h1>
+<ul>
+<li><a href="#graph-types">Graph types</a>
+<ul>
+<li><a href="#node">Node</a></li>
+</ul>
+</li>
+</ul>
+<h2 id="graph-types">Graph types</h2>
+<h3 id="node">Node</h3>
+<p>A &lt;b&gt;node&lt;/b&gt; &amp; its links</p>
+<table>
+<tr><th>Field</th><th>Type</th><th>Description</th></tr>
+<tr><td><code>id</code></td><td>i64</td><td></td></tr>
+<tr><td><code>edges</code></td><td>vec&lt;<a href="#node">Node</a>&gt;</td><td></td></tr>
+</table>
+<p><strong>Rust</strong></p>
+<pre><code class="language-rust">pub struct Node {
+    pub id: i64,
+    pub edges: Vec&lt;Node&gt;,
+}</code></pre>
+<p><strong>Hack</strong></p>
+<pre><code class="language-hack">type Node = shape(
+    'id' =&gt; int,
+    'edges' =&gt; vec&lt;Node&gt;,
+);</code></pre>
+<p><strong>Flow</strong></p>
+<pre><code class="language-js">export type Node = {
+    'id': number,
+    'edges': Array&lt;Node&gt;,
+};</code></pre>
+<p><strong>Example</strong></p>
+<pre><code class="language-json">{
+  &quot;id&quot;: 9007199254740991,
+  &quot;edges&quot;: [
+    {
+      &quot;id&quot;: 9007199254740991,
+      &quot;edges&quot;: [
+        {
+          &quot;id&quot;: 9007199254740991,
+          &quot;edges&quot;: [
+            {
+              &quot;id&quot;: 9007199254740991,
+              &quot;edges&quot;: [
+                {
+                  &quot;id&quot;: 0,
+                  &quot;edges&quot;: []
+                }
+              ]
+            }
+          ]
+        }
+      ]
+    }
+  ]
+}</code></pre>
+</body>
+</html>
+
+"##
+    );
+    Ok(())
+}
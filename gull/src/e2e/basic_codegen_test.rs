@@ -4,189 +4,183 @@ use anyhow::Result;
 fn make_declarations() -> Declarations {
     let mut c = Declarations::new();
 
-    c.add_config(DeclarationsConfig::HackNamespace("GraphiteIngester"));
+    c.add_config(DeclarationsConfig::hack_namespace("GraphiteIngester"));
 
-    c.add(TypeDeclaration {
-        name: "",
-        docs: "Hello world
+    c.add(TypeDeclaration::new("", DeclarationValue::Docs).docs(
+        "Hello world
         ==========================================================================
         THIS IS AN INDEPENDENT DOCUMENTATION BLOCK
         ==========================================================================",
-        config: vec![],
-        generic_params: vec![],
-        value: DeclarationValue::Docs,
-    });
-
-    let t_string_val_no_bounds_gen = TGeneric::TDefinition {
-        name: "TStringVal",
-        bounds: None,
-    };
-
-    let id = c.add(TypeDeclaration {
-        name: "ID",
-        docs: "",
-        config: vec![],
-        generic_params: vec![],
-        value: DeclarationValue::TPrimitive(TPrimitive::TDifferentPerLanguage {
-            rust: Box::new(TPrimitive::THardcoded("crate::types::ID")),
+    ));
+
+    let t_string_val_no_bounds_gen = TGeneric::param("TStringVal");
+
+    let id = c.add(TypeDeclaration::new(
+        "ID",
+        DeclarationValue::TPrimitive(TPrimitive::TDifferentPerLanguage {
+            rust: Box::new(TPrimitive::hardcoded("crate::types::ID")),
             hack: Box::new(TPrimitive::Ti64),
             flow: Box::new(TPrimitive::Ti64),
         }),
-    });
-
-    let indexable_str = c.add(TypeDeclaration {
-        name: "IndexableStr",
-        docs: "",
-        config: vec![],
-        generic_params: vec![],
-        value: DeclarationValue::TPrimitive(TPrimitive::TDifferentPerLanguage {
-            rust: Box::new(TPrimitive::THardcoded("crate::types::IndexableStr")),
+    ));
+
+    let indexable_str = c.add(TypeDeclaration::new(
+        "IndexableStr",
+        DeclarationValue::TPrimitive(TPrimitive::TDifferentPerLanguage {
+            rust: Box::new(TPrimitive::hardcoded("crate::types::IndexableStr")),
             hack: Box::new(TPrimitive::String),
             flow: Box::new(TPrimitive::String),
         }),
-    });
-
-    let frame = c.add(TypeDeclaration {
-        name: "Frame",
-        docs: "Frame represents a tuple of an Timestamp (RFC3339) and an ID",
-        config: vec![TypeDeclarationConfig::RustAttribute("#[derive(Copy)]")],
-        generic_params: vec![],
-        value: DeclarationValue::TTuple(TTuple {
-            items: vec![
-                TupleItem::TPrimitive(TPrimitive::String),
-                TupleItem::TPrimitive(TPrimitive::Ti64),
-            ],
-        }),
-    });
-
-    c.add(TypeDeclaration {
-        name: "StorageType",
-        docs: "",
-        config: vec![],
-        generic_params: vec![],
-        value: DeclarationValue::TSimpleEnum(TSimpleEnum {
-            variants: vec!["Full", "Delta", "Empty", "Broken"],
-        }),
-    });
-
-    c.add(TypeDeclaration {
-        name: "Operation",
-        docs: "Operation is a single unit of transormation logic",
-        config: vec![],
-        generic_params: vec![],
-        value: DeclarationValue::TEnum(TEnum {
-            variants: vec![
-                EnumVariant {
-                    name: "Fetch",
-                    docs: "Fetch items by their IDs",
-                    variant_type: EnumVariantType::TStruct(TStruct {
-                        fields: vec![StructField {
-                            name: "items",
-                            docs: "item IDs",
-                            config: vec![],
-                            field_type: StructFieldType::TVec(TVec::TPrimitive(TPrimitive::Ti64)),
-                        }],
-                    }),
-                },
-                EnumVariant {
-                    name: "Store",
-                    docs: "Store graphs to a storage layer",
-                    variant_type: EnumVariantType::TStruct(TStruct {
-                        fields: vec![StructField {
-                            name: "frames",
-                            docs: "Destination frames for the storage",
-                            config: vec![],
-                            field_type: StructFieldType::TVec(TVec::TPrimitive(
-                                TPrimitive::TReference(frame),
-                            )),
-                        }],
-                    }),
-                },
-                EnumVariant {
-                    name: "Drop",
-                    docs: "Discard all graphs",
-                    variant_type: EnumVariantType::TPrimitive(TPrimitive::Tbool),
-                },
-                EnumVariant {
-                    name: "FakeOp",
-                    docs: "Not a real operation",
-                    variant_type: EnumVariantType::TPrimitive(TPrimitive::Tusize),
-                },
-            ],
-        }),
-    });
-
-    let node_id = c.add(TypeDeclaration {
-        name: "NodeID",
-        docs: "",
-        config: vec![],
-        generic_params: vec![],
-        value: DeclarationValue::TPrimitive(TPrimitive::Ti32),
-    });
-
-    let graph_node = c.add(TypeDeclaration {
-        name: "GraphNode",
-        docs: "",
-        config: vec![],
-        generic_params: vec![],
-        value: DeclarationValue::TStruct(TStruct {
-            fields: vec![StructField {
-                name: "node_id",
-                docs: "",
-                config: vec![],
-                field_type: StructFieldType::TPrimitive(TPrimitive::TReference(node_id)),
-            }],
-        }),
-    });
-
-    let generic_enum = c.add(TypeDeclaration {
-        name: "GenericEnum",
-        docs: "",
-        config: vec![],
-        generic_params: vec![t_string_val_no_bounds_gen.clone()],
-        value: DeclarationValue::TEnum(TEnum {
-            variants: vec![
-                EnumVariant {
-                    name: "A",
-                    docs: "",
-                    variant_type: EnumVariantType::TPrimitive(TPrimitive::TGeneric(
-                        t_string_val_no_bounds_gen,
-                    )),
-                },
-                EnumVariant {
-                    name: "B",
-                    docs: "",
-                    variant_type: EnumVariantType::TPrimitive(TPrimitive::Tu32),
-                },
-            ],
+    ));
+
+    let frame = c.add(
+        TypeDeclaration::new(
+            "Frame",
+            DeclarationValue::TTuple(TTuple {
+                items: vec![
+                    TupleItem::TPrimitive(TPrimitive::String),
+                    TupleItem::TPrimitive(TPrimitive::Ti64),
+                ],
+            }),
+        )
+        .docs("Frame represents a tuple of an Timestamp (RFC3339) and an ID")
+        .config(TypeDeclarationConfig::rust_attribute("#[derive(Copy)]")),
+    );
+
+    c.add(TypeDeclaration::new(
+        "StorageType",
+        DeclarationValue::TSimpleEnum(TSimpleEnum::new(["Full", "Delta", "Empty", "Broken"])),
+    ));
+
+    c.add(
+        TypeDeclaration::new(
+            "Operation",
+            DeclarationValue::TEnum(TEnum {
+                variants: vec![
+                    EnumVariant::new(
+                        "Fetch",
+                        EnumVariantType::TStruct(TStruct {
+                            fields: vec![StructField::new(
+                                "items",
+                                StructFieldType::TVec(TVec::TPrimitive(TPrimitive::Ti64)),
+                            )
+                            .docs("item IDs")],
+                        }),
+                    )
+                    .docs("Fetch items by their IDs"),
+                    EnumVariant::new(
+                        "Store",
+                        EnumVariantType::TStruct(TStruct {
+                            fields: vec![StructField::new(
+                                "frames",
+                                StructFieldType::TVec(TVec::TPrimitive(TPrimitive::TReference(
+                                    frame,
+                                ))),
+                            )
+                            .docs("Destination frames for the storage")],
+                        }),
+                    )
+                    .docs("Store graphs to a storage layer"),
+                    EnumVariant::new("Drop", EnumVariantType::TPrimitive(TPrimitive::Tbool))
+                        .docs("Discard all graphs"),
+                    EnumVariant::new("FakeOp", EnumVariantType::TPrimitive(TPrimitive::Tusize))
+                        .docs("Not a real operation"),
+                ],
+            }),
+        )
+        .docs("Operation is a single unit of transormation logic"),
+    );
+
+    let node_id = c.add(TypeDeclaration::new(
+        "NodeID",
+        DeclarationValue::TPrimitive(TPrimitive::Ti32),
+    ));
+
+    let graph_node = c.add(TypeDeclaration::new(
+        "GraphNode",
+        DeclarationValue::TStruct(TStruct {
+            fields: vec![StructField::new(
+                "node_id",
+                StructFieldType::TPrimitive(TPrimitive::TReference(node_id)),
+            )],
         }),
-    });
+    ));
+
+    let generic_enum = c.add(
+        TypeDeclaration::new(
+            "GenericEnum",
+            DeclarationValue::TEnum(TEnum {
+                variants: vec![
+                    EnumVariant::new(
+                        "A",
+                        EnumVariantType::TPrimitive(TPrimitive::TGeneric(
+                            t_string_val_no_bounds_gen.clone(),
+                        )),
+                    ),
+                    EnumVariant::new("B", EnumVariantType::TPrimitive(TPrimitive::Tu32)),
+                ],
+            }),
+        )
+        .generic_params(vec![t_string_val_no_bounds_gen]),
+    );
 
     let mut generic_enum_indexed = generic_enum.clone();
     generic_enum_indexed.generic_params = vec![TGeneric::TReference(id)];
 
-    c.add(TypeDeclaration {
-        name: "GenericEnumIndexed",
-        docs: "",
-        config: vec![],
-        generic_params: vec![],
-        value: DeclarationValue::TPrimitive(generic_enum_indexed.primitive()),
-    });
+    c.add(TypeDeclaration::new(
+        "GenericEnumIndexed",
+        DeclarationValue::TPrimitive(generic_enum_indexed.primitive()),
+    ));
 
     let mut generic_enum_unindexed = generic_enum;
     generic_enum_unindexed.generic_params = vec![TGeneric::TReference(indexable_str.clone())];
 
-    c.add(TypeDeclaration {
-        name: "GenericEnumUnindexed",
-        docs: "",
-        config: vec![],
-        generic_params: vec![],
-        value: DeclarationValue::TPrimitive(generic_enum_unindexed.primitive()),
-    });
-
-    let graph_data = c.add(TypeDeclaration {
-        name: "GraphData",
-        docs: r#"Wrapper value that represents a graph. It contains various top level
+    c.add(TypeDeclaration::new(
+        "GenericEnumUnindexed",
+        DeclarationValue::TPrimitive(generic_enum_unindexed.primitive()),
+    ));
+
+    let graph_data = c.add(
+        TypeDeclaration::new(
+            "GraphData",
+            DeclarationValue::TStruct(TStruct {
+                fields: vec![
+                    StructField::new(
+                        "entry_points",
+                        StructFieldType::TVec(TVec::TPrimitive(TPrimitive::Ti64)),
+                    )
+                    .docs("Root nodes of the graph"),
+                    StructField::new(
+                        "nodes",
+                        StructFieldType::TMap(TMap {
+                            key: TPrimitive::Ti64,
+                            value: TMapValue::TPrimitive(TPrimitive::TReference(
+                                graph_node.clone(),
+                            )),
+                            t: TMapType::BTree,
+                        }),
+                    ),
+                    StructField::new(
+                        "string_fields",
+                        StructFieldType::TOption(TOption::TMap(TMap {
+                            key: TPrimitive::String,
+                            value: TMapValue::TPrimitive(TPrimitive::String),
+                            t: TMapType::BTree,
+                        })),
+                    )
+                    .docs(
+                        "A bunch of random string fields
+                    that are represented as a map between string and string
+                    and other important lines of documentation.",
+                    )
+                    .config(StructFieldConfig::rust_attribute(
+                        r#"#[serde(skip_serializing_if = "Option::is_none")]"#,
+                    )),
+                ],
+            }),
+        )
+        .docs(
+            r#"Wrapper value that represents a graph. It contains various top level
         data about the graph as well as a collection of nodes. This is a long
         multiline documentation block that is here for testing purposes only. I'll also
         add some Ascii diagram just to make sure nothing gets misaligned.
@@ -204,122 +198,62 @@ fn make_declarations() -> Declarations {
 
         Maybe some extra line after a newline.
         "#,
-        config: vec![],
-        generic_params: vec![],
-        value: DeclarationValue::TStruct(TStruct {
-            fields: vec![
-                StructField {
-                    name: "entry_points",
-                    docs: "Root nodes of the graph",
-                    config: vec![],
-                    field_type: StructFieldType::TVec(TVec::TPrimitive(TPrimitive::Ti64)),
-                },
-                StructField {
-                    name: "nodes",
-                    docs: "",
-                    config: vec![],
-                    field_type: StructFieldType::TMap(TMap {
-                        key: TPrimitive::Ti64,
-                        value: TMapValue::TPrimitive(TPrimitive::TReference(graph_node.clone())),
-                        t: TMapType::BTree,
-                    }),
-                },
-                StructField {
-                    name: "string_fields",
-                    docs: "A bunch of random string fields
-                    that are represented as a map between string and string
-                    and other important lines of documentation.",
-                    config: vec![StructFieldConfig::RustAttribute(
-                        r#"#[serde(skip_serializing_if = "Option::is_none")]"#,
-                    )],
-                    field_type: StructFieldType::TOption(TOption::TMap(TMap {
-                        key: TPrimitive::String,
-                        value: TMapValue::TPrimitive(TPrimitive::String),
-                        t: TMapType::BTree,
-                    })),
-                },
-            ],
-        }),
-    });
-
-    let mut graph_data_unindexed = graph_data;
-    graph_data_unindexed.generic_params = vec![
-        TGeneric::TReference(indexable_str.clone()),
-        TGeneric::TReference(indexable_str),
-    ];
-
-    c.add(TypeDeclaration {
-        name: "GraphDataUnindexed",
-        docs: "",
-        config: vec![],
-        generic_params: vec![],
-        value: DeclarationValue::TPrimitive(TPrimitive::TReference(graph_data_unindexed)),
-    });
-
-    c.add(TypeDeclaration {
-        name: "BasicVec",
-        docs: "",
-        config: vec![],
-        generic_params: vec![],
-        value: DeclarationValue::TVec(Box::new(TVec::TPrimitive(TPrimitive::Tf64))),
-    });
-
-    c.add(TypeDeclaration {
-        name: "BasicMap",
-        docs: "",
-        config: vec![],
-        generic_params: vec![],
-        value: DeclarationValue::TMap(Box::new(TMap {
+        ),
+    );
+
+    let graph_data_unindexed = graph_data;
+
+    c.add(TypeDeclaration::new(
+        "GraphDataUnindexed",
+        DeclarationValue::TPrimitive(TPrimitive::TReference(graph_data_unindexed)),
+    ));
+
+    c.add(TypeDeclaration::new(
+        "BasicVec",
+        DeclarationValue::TVec(Box::new(TVec::TPrimitive(TPrimitive::Tf64))),
+    ));
+
+    c.add(TypeDeclaration::new(
+        "BasicMap",
+        DeclarationValue::TMap(Box::new(TMap {
             key: TPrimitive::Ti64,
             value: TMapValue::TPrimitive(graph_node.primitive()),
             t: TMapType::BTree,
         })),
-    });
-
-    c.add(TypeDeclaration {
-        name: "BasicOption",
-        docs: "",
-        config: vec![],
-        generic_params: vec![],
-        value: DeclarationValue::TOption(Box::new(TOption::TPrimitive(graph_node.primitive()))),
-    });
-
-    c.add(TypeDeclaration {
-        name: "BasicStruct",
-        docs: "",
-        config: vec![],
-        generic_params: vec![],
-        value: DeclarationValue::TStruct(TStruct {
+    ));
+
+    c.add(TypeDeclaration::new(
+        "BasicOption",
+        DeclarationValue::TOption(Box::new(TOption::TPrimitive(graph_node.primitive()))),
+    ));
+
+    c.add(TypeDeclaration::new(
+        "BasicStruct",
+        DeclarationValue::TStruct(TStruct {
             fields: vec![
-                StructField {
-                    name: "map",
-                    docs: "",
-                    config: vec![],
-                    field_type: StructFieldType::TPrimitive(TPrimitive::TMap(Box::new(TMap {
+                StructField::new(
+                    "map",
+                    StructFieldType::TPrimitive(TPrimitive::TMap(Box::new(TMap {
                         key: TPrimitive::Ti64,
                         value: TMapValue::TPrimitive(graph_node.primitive()),
                         t: TMapType::BTree,
                     }))),
-                },
-                StructField {
-                    name: "vec",
-                    docs: "",
-                    config: vec![],
-                    field_type: StructFieldType::TPrimitive(TPrimitive::TVec(Box::new(
-                        TVec::TPrimitive(graph_node.primitive()),
-                    ))),
-                },
-                StructField {
-                    name: "vec",
-                    docs: "",
-                    config: vec![],
-                    field_type: StructFieldType::TPrimitive(TPrimitive::TOption(Box::new(
+                ),
+                StructField::new(
+                    "vec",
+                    StructFieldType::TPrimitive(TPrimitive::TVec(Box::new(TVec::TPrimitive(
+                        graph_node.primitive(),
+                    )))),
+                ),
+                StructField::new(
+                    "vec",
+                    StructFieldType::TPrimitive(TPrimitive::TOption(Box::new(
                         TOption::TPrimitive(graph_node.primitive()),
                     ))),
-                },
+                ),
             ],
         }),
-    });
+    ));
 
     c
 }
@@ -415,7 +349,7 @@ pub struct GraphData {
     pub string_fields: Option<BTreeMap<String, String>>,
 }
 
-pub type GraphDataUnindexed = GraphData<IndexableStr, IndexableStr>;
+pub type GraphDataUnindexed = GraphData;
 
 pub type BasicVec = Vec<f64>;
 
@@ -489,6 +423,44 @@ type GraphiteIngesterOperation = shape(
     ?'FakeOp' => int,
 );
 
+function GraphiteIngesterOperation_getType(GraphiteIngesterOperation $x): GraphiteIngesterOperationType {
+    if (Shapes::keyExists($x, 'Fetch')) {
+        return GraphiteIngesterOperationType::FETCH;
+    }
+    if (Shapes::keyExists($x, 'Store')) {
+        return GraphiteIngesterOperationType::STORE;
+    }
+    if (Shapes::keyExists($x, 'Drop')) {
+        return GraphiteIngesterOperationType::DROP;
+    }
+    if (Shapes::keyExists($x, 'FakeOp')) {
+        return GraphiteIngesterOperationType::FAKE_OP;
+    }
+    invariant_violation('No variant of GraphiteIngesterOperation is set');
+}
+
+function GraphiteIngesterOperation_getFetch(GraphiteIngesterOperation $x): ?shape(
+    // item IDs
+    'items' => vec<int>,
+) {
+    return Shapes::idx($x, 'Fetch');
+}
+
+function GraphiteIngesterOperation_getStore(GraphiteIngesterOperation $x): ?shape(
+    // Destination frames for the storage
+    'frames' => vec<GraphiteIngesterFrame>,
+) {
+    return Shapes::idx($x, 'Store');
+}
+
+function GraphiteIngesterOperation_getDrop(GraphiteIngesterOperation $x): ?bool {
+    return Shapes::idx($x, 'Drop');
+}
+
+function GraphiteIngesterOperation_getFakeOp(GraphiteIngesterOperation $x): ?int {
+    return Shapes::idx($x, 'FakeOp');
+}
+
 type GraphiteIngesterNodeID = int;
 
 type GraphiteIngesterGraphNode = shape(
@@ -506,6 +478,24 @@ type GraphiteIngesterGenericEnum<TStringVal> = shape(
     ?'B' => int,
 );
 
+function GraphiteIngesterGenericEnum_getType<TStringVal>(GraphiteIngesterGenericEnum<TStringVal> $x): GraphiteIngesterGenericEnumType {
+    if (Shapes::keyExists($x, 'A')) {
+        return GraphiteIngesterGenericEnumType::A;
+    }
+    if (Shapes::keyExists($x, 'B')) {
+        return GraphiteIngesterGenericEnumType::B;
+    }
+    invariant_violation('No variant of GraphiteIngesterGenericEnum is set');
+}
+
+function GraphiteIngesterGenericEnum_getA<TStringVal>(GraphiteIngesterGenericEnum<TStringVal> $x): ?TStringVal {
+    return Shapes::idx($x, 'A');
+}
+
+function GraphiteIngesterGenericEnum_getB<TStringVal>(GraphiteIngesterGenericEnum<TStringVal> $x): ?int {
+    return Shapes::idx($x, 'B');
+}
+
 type GraphiteIngesterGenericEnumIndexed = GraphiteIngesterGenericEnum<GraphiteIngesterID>;
 
 type GraphiteIngesterGenericEnumUnindexed = GraphiteIngesterGenericEnum<GraphiteIngesterIndexableStr>;
@@ -530,23 +520,23 @@ type GraphiteIngesterGenericEnumUnindexed = GraphiteIngesterGenericEnum<Graphite
 type GraphiteIngesterGraphData = shape(
     // Root nodes of the graph
     'entry_points' => vec<int>,
-    'nodes' => dict<int, GraphiteIngesterGraphNode>,
+    'nodes' => dict<arraykey, GraphiteIngesterGraphNode>,
     // A bunch of random string fields
     // that are represented as a map between string and string
     // and other important lines of documentation.
-    ?'string_fields' => dict<string, string>,
+    ?'string_fields' => dict<arraykey, string>,
 );
 
-type GraphiteIngesterGraphDataUnindexed = GraphiteIngesterGraphData<GraphiteIngesterIndexableStr, GraphiteIngesterIndexableStr>;
+type GraphiteIngesterGraphDataUnindexed = GraphiteIngesterGraphData;
 
 type GraphiteIngesterBasicVec = vec<float>;
 
-type GraphiteIngesterBasicMap = dict<int, GraphiteIngesterGraphNode>;
+type GraphiteIngesterBasicMap = dict<arraykey, GraphiteIngesterGraphNode>;
 
 type GraphiteIngesterBasicOption = ?GraphiteIngesterGraphNode;
 
 type GraphiteIngesterBasicStruct = shape(
-    'map' => dict<int, GraphiteIngesterGraphNode>,
+    'map' => dict<arraykey, GraphiteIngesterGraphNode>,
     'vec' => vec<GraphiteIngesterGraphNode>,
     'vec' => ?GraphiteIngesterGraphNode,
 );
@@ -601,6 +591,74 @@ export type Operation = {
     'FakeOp'?: number,
 };
 
+export function getOperationType(x: Operation): OperationType {
+    if (x.Fetch !== undefined) {
+        return 'Fetch';
+    }
+    if (x.Store !== undefined) {
+        return 'Store';
+    }
+    if (x.Drop !== undefined) {
+        return 'Drop';
+    }
+    if (x.FakeOp !== undefined) {
+        return 'FakeOp';
+    }
+    throw new Error('No variant of Operation is set');
+}
+
+export function operationFetch(value: {
+    // item IDs
+    'items': Array<number>,
+}): Operation {
+    return {'Fetch': value};
+}
+
+export function operationStore(value: {
+    // Destination frames for the storage
+    'frames': Array<Frame>,
+}): Operation {
+    return {'Store': value};
+}
+
+export function operationDrop(value: boolean): Operation {
+    return {'Drop': value};
+}
+
+export function operationFakeOp(value: number): Operation {
+    return {'FakeOp': value};
+}
+
+export function matchOperation<TResult>(
+    x: Operation,
+    cases: {|
+        'Fetch': (value: {
+            // item IDs
+            'items': Array<number>,
+        }) => TResult,
+        'Store': (value: {
+            // Destination frames for the storage
+            'frames': Array<Frame>,
+        }) => TResult,
+        'Drop': (value: boolean) => TResult,
+        'FakeOp': (value: number) => TResult,
+    |},
+): TResult {
+    if (x.Fetch !== undefined) {
+        return cases.Fetch(x.Fetch);
+    }
+    if (x.Store !== undefined) {
+        return cases.Store(x.Store);
+    }
+    if (x.Drop !== undefined) {
+        return cases.Drop(x.Drop);
+    }
+    if (x.FakeOp !== undefined) {
+        return cases.FakeOp(x.FakeOp);
+    }
+    throw new Error('No variant of Operation is set');
+}
+
 export type NodeID = number;
 
 export type GraphNode = {
@@ -614,6 +672,40 @@ export type GenericEnum<TStringVal> = {
     'B'?: number,
 };
 
+export function getGenericEnumType<TStringVal>(x: GenericEnum<TStringVal>): GenericEnumType {
+    if (x.A !== undefined) {
+        return 'A';
+    }
+    if (x.B !== undefined) {
+        return 'B';
+    }
+    throw new Error('No variant of GenericEnum is set');
+}
+
+export function genericEnumA<TStringVal>(value: TStringVal): GenericEnum<TStringVal> {
+    return {'A': value};
+}
+
+export function genericEnumB<TStringVal>(value: number): GenericEnum<TStringVal> {
+    return {'B': value};
+}
+
+export function matchGenericEnum<TStringVal, TResult>(
+    x: GenericEnum<TStringVal>,
+    cases: {|
+        'A': (value: TStringVal) => TResult,
+        'B': (value: number) => TResult,
+    |},
+): TResult {
+    if (x.A !== undefined) {
+        return cases.A(x.A);
+    }
+    if (x.B !== undefined) {
+        return cases.B(x.B);
+    }
+    throw new Error('No variant of GenericEnum is set');
+}
+
 export type GenericEnumIndexed = GenericEnum<ID>;
 
 export type GenericEnumUnindexed = GenericEnum<IndexableStr>;
@@ -638,23 +730,23 @@ export type GenericEnumUnindexed = GenericEnum<IndexableStr>;
 export type GraphData = {
     // Root nodes of the graph
     'entry_points': Array<number>,
-    'nodes': {[key: number]: GraphNode},
+    'nodes': {[key: string]: GraphNode},
     // A bunch of random string fields
     // that are represented as a map between string and string
     // and other important lines of documentation.
     'string_fields'?: {[key: string]: string},
 };
 
-export type GraphDataUnindexed = GraphData<IndexableStr, IndexableStr>;
+export type GraphDataUnindexed = GraphData;
 
 export type BasicVec = Array<number>;
 
-export type BasicMap = {[key: number]: GraphNode};
+export type BasicMap = {[key: string]: GraphNode};
 
 export type BasicOption = ?GraphNode;
 
 export type BasicStruct = {
-    'map': {[key: number]: GraphNode},
+    'map': {[key: string]: GraphNode},
     'vec': Array<GraphNode>,
     'vec': ?GraphNode,
 };
@@ -0,0 +1,161 @@
+use crate::prelude::*;
+use anyhow::Result;
+
+const OLD: &str = r#"
+enum StorageType { Full, Delta, Empty }
+
+enum Operation {
+    Fetch { items: vec<i32> },
+    Drop(bool),
+}
+
+struct GraphNode {
+    id: i64,
+    name: string,
+    parent?: i64,
+    weight: f64,
+    label: string,
+}
+
+type Removed = string;
+"#;
+
+const NEW: &str = r##"
+/// Docs changes are ignored
+enum StorageType { Full, Delta, Broken }
+
+enum Operation {
+    Fetch { items: vec<i64> },
+    Drop(bool),
+    Store(string),
+}
+
+struct GraphNode {
+    id: i32,
+    node_name: string,
+    parent: i64,
+    weight: string,
+    @rust("#[serde(alias = \"label\")]")
+    title: string,
+    created_at: string,
+    updated_at?: string,
+}
+
+type Added = string;
+"##;
+
+#[test]
+fn compat_test() -> Result<()> {
+    let report = check_compatibility(&parse_schema(OLD)?, &parse_schema(NEW)?);
+
+    k9::snapshot!(
+        report.to_string(),
+        "
+[FORWARD] StorageType.Empty: variant was removed
+[BACKWARD] StorageType.Broken: variant was added
+[BACKWARD] Operation.Fetch.items: type changed from `vec<i32>` to `vec<i64>`
+[BACKWARD] Operation.Store: variant was added
+[FORWARD] GraphNode.id: type changed from `i64` to `i32`
+[BREAKING] GraphNode.name: field was renamed to `node_name` without a `#[serde(alias)]`
+[FORWARD] GraphNode.parent: field became required
+[BREAKING] GraphNode.weight: type changed from `f64` to `string`
+[BACKWARD] GraphNode.label: field was renamed to `title`
+[FORWARD] GraphNode.created_at: required field was added
+[COMPATIBLE] GraphNode.updated_at: optional field was added
+[BREAKING] Removed: type was removed
+[COMPATIBLE] Added: type was added
+Overall: breaking
+"
+    );
+    assert!(report.is_breaking());
+    Ok(())
+}
+
+#[test]
+fn compat_levels_test() -> Result<()> {
+    let check = |old: &str, new: &str| -> Result<CompatReport> {
+        Ok(check_compatibility(
+            &parse_schema(old)?,
+            &parse_schema(new)?,
+        ))
+    };
+
+    let report = check("struct A { a: i64 }", "struct A { a: i64, b?: string }")?;
+    assert_eq!(report.compatibility(), Compatibility::Full);
+    assert!(report.satisfies(Compatibility::Backward));
+    assert!(report.satisfies(Compatibility::Forward));
+
+    let report = check("struct A { a: i64, b: string }", "struct A { a: i64 }")?;
+    assert_eq!(report.compatibility(), Compatibility::Backward);
+    assert!(report.satisfies(Compatibility::Backward));
+    assert!(!report.satisfies(Compatibility::Forward));
+
+    let report = check("struct A { a: i64 }", "struct A { a: i64 }")?;
+    k9::snapshot!(report.to_string(), "No changes");
+    Ok(())
+}
+
+#[test]
+fn generics_test() -> Result<()> {
+    let check = |old: &str, new: &str| -> Result<String> {
+        Ok(check_compatibility(&parse_schema(old)?, &parse_schema(new)?).to_string())
+    };
+
+    // Params are matched by position, so renaming one changes nothing
+    let report = check_compatibility(
+        &parse_schema("struct A<T> { a: vec<T> }")?,
+        &parse_schema("struct A<U> { a: vec<U> }")?,
+    );
+    assert_eq!(report.compatibility(), Compatibility::Full);
+    k9::snapshot!(report.to_string(), "No changes");
+    let report = check(
+        "struct A<T> { a: vec<T> }\nstruct B { b: A<i64> }",
+        "struct A<T, U = string> { a: vec<T>, b?: U }\nstruct B { b: A<i64> }",
+    )?;
+    k9::snapshot!(
+        report,
+        "
+[COMPATIBLE] A: generic parameter `U` was added with a default
+[COMPATIBLE] A.b: optional field was added
+Overall: fully compatible
+"
+    );
+    let report = check(
+        "struct A<T, U> { a: T, b: U }",
+        "struct A<U, T> { a: U, b: T }",
+    )?;
+    k9::snapshot!(report, "No changes");
+    let report = check(
+        "struct A<T, U = i32> { a: T, b: U }",
+        "struct A<T, U = i64> { a: T, b: T }",
+    )?;
+    k9::snapshot!(
+        report,
+        "
+[BACKWARD] A: default of generic parameter `U` changed from `i32` to `i64`
+[BREAKING] A.b: type changed from `U` to `T`
+Overall: breaking
+"
+    );
+    let report = check(
+        "struct A<T, U = i32> { a: T, b?: U }",
+        "struct A<T, V> { a: T }",
+    )?;
+    k9::snapshot!(
+        report,
+        "
+[BREAKING] A: generic parameter `V` no longer has a default
+[COMPATIBLE] A.b: field was removed
+Overall: breaking
+"
+    );
+    let report = check("struct A<T, U> { a: T }", "struct A<T> { a: T }")?;
+    k9::snapshot!(
+        report,
+        "
+[BREAKING] A: generic parameter `U` was removed
+Overall: breaking
+"
+    );
+    Ok(())
+}
@@ -0,0 +1,428 @@
+// JSON conformance harness for the generated Rust types.
+//
+// Generates sample values for every declaration, round trips them through
+// the generated Rust types (serde_json) in a throwaway cargo project and checks
+// that the samples and whatever Rust serializes have the JSON shape the IR
+// declares. Only Rust is exercised, the Hack and Flow output isn't run, so this
+// doesn't verify that Hack or Flow accept the same values.
+
+use super::project::Project;
+use crate::flatten::Flattener;
+use crate::generics::{bind_generics, resolve_generic, Binding};
+use crate::includes;
+use crate::prelude::*;
+use anyhow::Result;
+use serde_json::Value;
+
+pub const CONFORMANCE_CARGO_TOML: &str = r#"
+[package]
+name = "gull_conformance"
+version = "0.1.0"
+edition = "2018"
+
+[dependencies]
+serde = { version = "1", features = ["derive"] }
+serde_json = "1"
+serde_repr = "0.1"
+
+[[bin]]
+name = "conformance"
+path = "main.rs"
+
+[workspace]
+"#;
+
+// Share build artifacts between runs so that serde is only compiled once
+const CONFORMANCE_CARGO_CONFIG: &str = r#"
+[build]
+target-dir = "../target"
+"#;
+
+const ROUND_TRIP_FN: &str = r#"
+fn round_trip<T: serde::de::DeserializeOwned + serde::Serialize>(json: &str) -> String {
+    match serde_json::from_str::<T>(json) {
+        Ok(value) => format!("ok\t{}", serde_json::to_string(&value).unwrap()),
+        Err(e) => format!("err\t{}", e),
+    }
+}
+"#;
+
+// Round trip minimal, maximal and `samples` random values of every non generic
+// declaration through Rust and check the samples and the results against the IR.
+pub fn check_conformance(name: &str, declarations: &Declarations, samples: usize) -> Result<()> {
+    let names = declarations
+        .declarations
+        .iter()
+        .filter(|d| d.generic_params.is_empty())
+        .filter(|d| {
+            !matches!(
+                d.value,
+                DeclarationValue::Docs | DeclarationValue::CodeBlock(_)
+            )
+        })
+        .map(|d| d.name.to_string())
+        .collect::<Vec<_>>();
+
+    let mut cases = vec![];
+    for name in &names {
+        for kind in &[FixtureKind::Minimal, FixtureKind::Maximal] {
+            cases.push((name.as_str(), declarations.fixture(name, *kind)?));
+        }
+        let mut generator = FixtureGenerator::new(declarations, FixtureKind::Random { seed: 0 });
+        for _ in 0..samples {
+            cases.push((name.as_str(), generator.generate(name)?));
+        }
+    }
+
+    let project = Project::new(name)?;
+    project.write_file("Cargo.toml", CONFORMANCE_CARGO_TOML)?;
+    project.write_file(".cargo/config.toml", CONFORMANCE_CARGO_CONFIG)?;
+    project.write_file(
+        "types.rs",
+        &with_serde_derives(declarations).codegen_rust()?,
+    )?;
+    project.write_file("main.rs", &main_rs(&names))?;
+    project.write_file(
+        "samples.txt",
+        &cases
+            .iter()
+            .map(|(name, sample)| format!("{}\t{}\n", name, sample))
+            .collect::<String>(),
+    )?;
+
+    let output = project.run("cargo run --offline --quiet")?;
+    output.assert_success()?;
+
+    let mut failures = vec![];
+    for ((name, sample), line) in cases.iter().zip(output.stdout.lines()) {
+        if let Err(e) = check_value(declarations, name, sample) {
+            failures.push(format!(
+                "{} sample {} doesn't match the IR: {}",
+                name, sample, e
+            ));
+        }
+
+        match line.split_once('\t') {
+            Some(("ok", json)) => {
+                let value: Value = serde_json::from_str(json)?;
+                if let Err(e) = check_value(declarations, name, &value) {
+                    failures.push(format!(
+                        "{} serialized by Rust as {} doesn't match the IR: {}",
+                        name, json, e
+                    ));
+                }
+            }
+            Some(("err", e)) => failures.push(format!(
+                "Rust failed to parse {} sample {}: {}",
+                name, sample, e
+            )),
+            _ => anyhow::bail!("Unexpected output line `{}`", line),
+        }
+    }
+
+    if output.stdout.lines().count() != cases.len() {
+        anyhow::bail!("Expected {} output lines\n{}", cases.len(), output.stdout);
+    }
+
+    if !failures.is_empty() {
+        anyhow::bail!("Conformance check failed:\n{}", failures.join("\n"));
+    }
+    Ok(())
+}
+
+// Check that a JSON value has the shape the IR declares for `name`
+pub fn check_value(declarations: &Declarations, name: &str, value: &Value) -> Result<()> {
+    let declarations = &includes::merge(declarations)?;
+    let oracle = Oracle {
+        declarations,
+        flattener: Flattener::new(declarations),
+    };
+    oracle
+        .check_reference(value, &TReference::new(name.to_string()), &[], "$")
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+fn with_serde_derives(declarations: &Declarations) -> Declarations {
+    let mut declarations = declarations.clone();
+    for declaration in &mut declarations.declarations {
+        // Type aliases get serde impls through the types they point to, and
+        // integer enums get theirs from `serde_repr`
+        let derive = match &declaration.value {
+            DeclarationValue::TSimpleEnum(e) if e.is_integer() => "#[derive(Debug)]",
+            DeclarationValue::TStruct(_)
+            | DeclarationValue::TEnum(_)
+            | DeclarationValue::TSimpleEnum(_) => {
+                "#[derive(Debug, serde::Serialize, serde::Deserialize)]"
+            }
+            _ => continue,
+        };
+        declaration
+            .config
+            .push(TypeDeclarationConfig::RustAttribute(derive.into()));
+    }
+    declarations
+}
+
+fn main_rs(names: &[String]) -> String {
+    let arms = names
+        .iter()
+        .map(|name| format!("            \"{0}\" => round_trip::<{0}>(json),\n", name))
+        .collect::<String>();
+
+    format!(
+        r#"#![allow(dead_code)]
+
+mod types;
+use types::*;
+{}
+fn main() {{
+    let samples = std::fs::read_to_string("samples.txt").unwrap();
+    for line in samples.lines() {{
+        let (name, json) = line.split_once('\t').unwrap();
+        let result = match name {{
+{}            _ => unreachable!(),
+        }};
+        println!("{{}}", result);
+    }}
+}}
+"#,
+        ROUND_TRIP_FN, arms
+    )
+}
+
+// Errors are `path: message` strings, same as the generated validators
+type Check = std::result::Result<(), String>;
+
+fn fail(path: &str, expected: &str, value: &Value) -> Check {
+    Err(format!("{}: expected {}, got {}", path, expected, value))
+}
+
+struct Oracle<'a> {
+    declarations: &'a Declarations,
+    flattener: Flattener,
+}
+
+impl<'a> Oracle<'a> {
+    fn check_reference(
+        &self,
+        value: &Value,
+        r: &TReference,
+        generics: &[Binding],
+        path: &str,
+    ) -> Check {
+        let declaration = self
+            .declarations
+            .declarations
+            .iter()
+            .find(|d| d.name == r.get_name())
+            .ok_or_else(|| format!("{}: unknown type `{}`", path, r.get_name()))?;
+        let bindings =
+            bind_generics(declaration, r, generics).map_err(|e| format!("{}: {}", path, e))?;
+
+        match &declaration.value {
+            DeclarationValue::TPrimitive(p) => self.check_primitive(value, p, &bindings, path),
+            DeclarationValue::TMap(m) => self.check_map(value, m, &bindings, path),
+            DeclarationValue::TVec(v) => self.check_vec(value, v, &bindings, path),
+            DeclarationValue::TOption(o) => self.check_option(value, o, &bindings, path),
+            DeclarationValue::TTuple(t) => self.check_tuple(value, t, &bindings, path),
+            DeclarationValue::TStruct(s) => self.check_struct(value, s, &bindings, path),
+            DeclarationValue::TEnum(e) => {
+                let expected = e
+                    .variants
+                    .iter()
+                    .map(|v| v.name.as_ref())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let object = match value.as_object() {
+                    Some(object) if object.len() == 1 => object,
+                    _ => return fail(path, &format!("one of {}", expected), value),
+                };
+                let (key, inner) = object.iter().next().unwrap();
+                let path = format!("{}.{}", path, key);
+                match e.variants.iter().find(|v| v.name == *key) {
+                    Some(EnumVariant {
+                        variant_type: EnumVariantType::TStruct(s),
+                        ..
+                    }) => self.check_struct(inner, s, &bindings, &path),
+                    Some(EnumVariant {
+                        variant_type: EnumVariantType::TPrimitive(p),
+                        ..
+                    }) => self.check_primitive(inner, p, &bindings, &path),
+                    None => fail(&path, &format!("one of {}", expected), value),
+                }
+            }
+            DeclarationValue::TSimpleEnum(e) if e.is_integer() => match value.as_i64() {
+                Some(i)
+                    if e.variants
+                        .iter()
+                        .any(|v| v.discriminant.map(i64::from) == Some(i)) =>
+                {
+                    Ok(())
+                }
+                _ => fail(path, &e.values().join(" | "), value),
+            },
+            DeclarationValue::TSimpleEnum(e) => match value.as_str() {
+                Some(s) if e.variants.iter().any(|v| v.value == s) => Ok(()),
+                _ => fail(path, &e.values().join(" | "), value),
+            },
+            DeclarationValue::Docs | DeclarationValue::CodeBlock(_) => {
+                Err(format!("{}: `{}` is not a type", path, r.get_name()))
+            }
+        }
+    }
+
+    fn check_primitive(
+        &self,
+        value: &Value,
+        p: &TPrimitive,
+        generics: &[Binding],
+        path: &str,
+    ) -> Check {
+        match p {
+            TPrimitive::String => match value {
+                Value::String(_) => Ok(()),
+                _ => fail(path, "string", value),
+            },
+            TPrimitive::Ti64 | TPrimitive::Ti32 | TPrimitive::Tu32 | TPrimitive::Tusize => {
+                match value {
+                    Value::Number(n) if n.is_i64() || n.is_u64() => Ok(()),
+                    _ => fail(path, "integer", value),
+                }
+            }
+            TPrimitive::Tf64 => match value {
+                Value::Number(_) => Ok(()),
+                _ => fail(path, "number", value),
+            },
+            TPrimitive::Tbool => match value {
+                Value::Bool(_) => Ok(()),
+                _ => fail(path, "bool", value),
+            },
+            TPrimitive::TVec(v) => self.check_vec(value, v, generics, path),
+            TPrimitive::TMap(m) => self.check_map(value, m, generics, path),
+            TPrimitive::TOption(o) => self.check_option(value, o, generics, path),
+            // Samples are round tripped through Rust
+            TPrimitive::TDifferentPerLanguage { rust, .. } => {
+                self.check_primitive(value, rust, generics, path)
+            }
+            TPrimitive::TReference(r) => self.check_reference(value, r, generics, path),
+            TPrimitive::TGeneric(g) => match resolve_generic(g, generics) {
+                TGeneric::TReference(r) => self.check_reference(value, &r, generics, path),
+                TGeneric::TPrimitive(p) => self.check_primitive(value, &p, generics, path),
+                TGeneric::TDefinition { name, .. } => Err(format!(
+                    "{}: generic param `{}` is not bound to a type",
+                    path, name
+                )),
+            },
+            // Nothing is known about hardcoded types
+            TPrimitive::THardcoded(_) => Ok(()),
+        }
+    }
+
+    fn check_items(
+        &self,
+        value: &Value,
+        path: &str,
+        mut check: impl FnMut(&Value, &str) -> Check,
+    ) -> Check {
+        match value.as_array() {
+            Some(items) => items
+                .iter()
+                .enumerate()
+                .try_for_each(|(i, item)| check(item, &format!("{}[{}]", path, i))),
+            None => fail(path, "array", value),
+        }
+    }
+
+    fn check_vec(&self, value: &Value, v: &TVec, generics: &[Binding], path: &str) -> Check {
+        let TVec::TPrimitive(p) = v;
+        self.check_items(value, path, |item, path| {
+            self.check_primitive(item, p, generics, path)
+        })
+    }
+
+    fn check_set(&self, value: &Value, s: &TSet, generics: &[Binding], path: &str) -> Check {
+        let TSet::TPrimitive(p) = s;
+        self.check_items(value, path, |item, path| {
+            self.check_primitive(item, p, generics, path)
+        })
+    }
+
+    fn check_map(&self, value: &Value, m: &TMap, generics: &[Binding], path: &str) -> Check {
+        let object = value
+            .as_object()
+            .ok_or_else(|| format!("{}: expected object, got {}", path, value))?;
+        object.iter().try_for_each(|(key, item)| {
+            let path = format!("{}.{}", path, key);
+            self.check_key(key, &m.key, generics, &path)?;
+            match &m.value {
+                TMapValue::TPrimitive(p) => self.check_primitive(item, p, generics, &path),
+                TMapValue::TSet(s) => self.check_set(item, s, generics, &path),
+            }
+        })
+    }
+
+    // JSON object keys are strings, integer keys are written as decimal numbers
+    fn check_key(&self, key: &str, p: &TPrimitive, generics: &[Binding], path: &str) -> Check {
+        self.check_primitive(&Value::from(key), p, generics, path)
+            .or_else(|e| match key.parse::<i64>() {
+                Ok(n) => self.check_primitive(&Value::from(n), p, generics, path),
+                Err(_) => Err(e),
+            })
+    }
+
+    fn check_option(&self, value: &Value, o: &TOption, generics: &[Binding], path: &str) -> Check {
+        match (value, o) {
+            (Value::Null, _) => Ok(()),
+            (_, TOption::TPrimitive(p)) => self.check_primitive(value, p, generics, path),
+            (_, TOption::TMap(m)) => self.check_map(value, m, generics, path),
+            (_, TOption::TVec(v)) => self.check_vec(value, v, generics, path),
+            (_, TOption::TSet(s)) => self.check_set(value, s, generics, path),
+            (_, TOption::TTuple(t)) => self.check_tuple(value, t, generics, path),
+        }
+    }
+
+    fn check_tuple(&self, value: &Value, t: &TTuple, generics: &[Binding], path: &str) -> Check {
+        match value.as_array() {
+            Some(items) if items.len() == t.items.len() => items
+                .iter()
+                .zip(&t.items)
+                .enumerate()
+                .try_for_each(|(i, (item, ty))| {
+                    let path = format!("{}[{}]", path, i);
+                    match ty {
+                        TupleItem::TPrimitive(p) => self.check_primitive(item, p, generics, &path),
+                        TupleItem::TOption(o) => self.check_option(item, o, generics, &path),
+                    }
+                }),
+            _ => fail(path, &format!("{} items", t.items.len()), value),
+        }
+    }
+
+    fn check_struct(&self, value: &Value, s: &TStruct, generics: &[Binding], path: &str) -> Check {
+        let object = value
+            .as_object()
+            .ok_or_else(|| format!("{}: expected object, got {}", path, value))?;
+        let fields = self.flattener.fields(s).map_err(|e| e.to_string())?;
+
+        // Rust never serializes fields that the IR doesn't declare
+        if let Some(key) = object
+            .keys()
+            .find(|k| !fields.iter().any(|f| f.name == **k))
+        {
+            return Err(format!("{}: unexpected field `{}`", path, key));
+        }
+
+        fields.iter().try_for_each(|field| {
+            let path = format!("{}.{}", path, field.name);
+            let item = object.get(field.name.as_ref()).unwrap_or(&Value::Null);
+            match &field.field_type {
+                StructFieldType::TOption(o) => self.check_option(item, o, generics, &path),
+                StructFieldType::TPrimitive(p) => self.check_primitive(item, p, generics, &path),
+                StructFieldType::TMap(m) => self.check_map(item, m, generics, &path),
+                StructFieldType::TSet(s) => self.check_set(item, s, generics, &path),
+                StructFieldType::TTuple(t) => self.check_tuple(item, t, generics, &path),
+                StructFieldType::TVec(v) => self.check_vec(item, v, generics, &path),
+            }
+        })
+    }
+}
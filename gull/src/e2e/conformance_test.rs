@@ -0,0 +1,252 @@
+use super::conformance::{check_conformance, check_value};
+use crate::prelude::*;
+use anyhow::Result;
+
+// Declarations of every feature that affects JSON, they share a single cargo
+// project because building one is slow
+const SCHEMA: &str = r##"
+type ID = i64;
+
+type Weight = per_language(rust: i32, hack: i64, flow: f64);
+
+enum StorageType { Full, Delta }
+
+struct Node<T> {
+    id: ID,
+    value: T,
+    name: string,
+    count: u32,
+    enabled: bool,
+    weight: Weight,
+    tags: set<string>,
+    weights: map<string, f64>,
+    children: map<i64, vec<ID>>,
+    parent?: ID,
+    frame: (string, option<i32>),
+    storage: StorageType,
+}
+
+enum Operation {
+    Fetch { items: vec<ID>, limit?: usize },
+    Store(Node<ID>),
+    Delete(ID),
+}
+
+type IntNode = Node<ID>;
+
+type Batch = vec<Operation>;
+
+// Boxed recursive references
+enum Expr {
+    Literal(i64),
+    Not(Not),
+    And { left: Expr, right: option<Expr> },
+    Group(vec<Expr>),
+}
+
+struct Not { expr: Expr }
+
+struct LinkedList<T> {
+    value: T,
+    next: option<LinkedList<T>>,
+}
+
+struct Tree {
+    children: vec<Tree>,
+    index: map<string, Tree>,
+    list: LinkedList<ID>,
+}
+
+struct Config {
+    @rust_box
+    tree: Tree,
+    @rust_box
+    parent?: Config,
+}
+
+// Generic params with defaults
+struct Index<K: Ord, V = string> {
+    entries: map<K, vec<V>>,
+    default?: V,
+}
+
+enum Hierarchy<T: Clone = i64> {
+    Leaf(T),
+    Branch { children: vec<Hierarchy<T>> },
+}
+
+type Named = Index<string>;
+
+type Counts = Index<string, u32>;
+
+type Forest = vec<Hierarchy>;
+
+struct Catalog {
+    names: Named,
+    tags: Index<string, option<vec<string>>>,
+    forest: Forest,
+    hierarchies: vec<Hierarchy<map<string, bool>>>,
+}
+
+// Deprecated types, fields and variants
+@deprecated("Use `ID` instead")
+@since("1.4.0")
+type LegacyID = i64;
+
+struct Profile {
+    id: ID,
+    @deprecated("Names aren't unique, use `id`")
+    name: string,
+    @deprecated("Don't rely on the legacy layout")
+    @since("1.2.0")
+    legacy?: vec<LegacyID>,
+}
+
+@deprecated("Replaced by `Command`")
+enum Status { Active, Removed }
+
+enum Command {
+    Add(Profile),
+    @deprecated("Remove profiles instead")
+    @since("2.0.0")
+    Clear { force: bool },
+}
+
+// Map keys
+@rust("#[derive(PartialEq, Eq, PartialOrd, Ord, Hash)]")
+enum Color { Red, Green }
+
+type Shade = Color;
+
+struct Palette<K: Ord> {
+    names: map<string, string>,
+    nodes: map<ID, bool>,
+    counts: hash_map<u32, i64>,
+    colors: map<Color, vec<string>>,
+    shades?: map<Shade, set<i64>>,
+    custom: map<K, i64>,
+}
+
+type DefaultPalette = Palette<string>;
+
+// Simple enum variants with wire values
+@rust("#[derive(PartialEq, Eq, PartialOrd, Ord)]")
+enum Location {
+    /// Kept in memory
+    @value("in-memory")
+    InMemory,
+    @value("disk")
+    @deprecated("Use `InMemory`")
+    @since("2.0")
+    Disk,
+    Remote,
+}
+
+struct Placement {
+    location: Location,
+    by_location: map<Location, i64>,
+}
+
+// Integer enums
+enum Priority {
+    Low = -1,
+    Normal = 0,
+    @deprecated("Use `Normal`")
+    High = 10,
+}
+
+@rust("#[derive(PartialEq, Eq, PartialOrd, Ord)]")
+enum Level { Debug = 1, Info = 2 }
+
+struct Request {
+    priority: Priority,
+    level?: Level,
+    by_level: map<Level, i64>,
+}
+
+// Flattened fields
+struct Metadata {
+    created_at: i64,
+    updated_by?: string,
+}
+
+struct Page<T> {
+    items: vec<T>,
+    cursor?: string,
+}
+
+struct Document {
+    id: ID,
+    @flatten
+    metadata: Metadata,
+}
+
+struct DocumentPage {
+    @flatten
+    page: Page<Document>,
+    total: i64,
+}
+
+enum Event {
+    Created { @flatten document: Document },
+    Deleted(ID),
+}
+
+// Included structs
+struct Folder includes Metadata {
+    id: ID,
+    children: vec<Folder>,
+}
+
+struct FolderPage includes Page<Folder>, Metadata {
+    total: i64,
+}
+"##;
+
+#[test]
+fn round_trip_test() -> Result<()> {
+    let declarations = parse_schema(SCHEMA)?;
+    check_conformance("conformance_round_trip", &declarations, 20)
+}
+
+#[test]
+fn check_value_test() -> Result<()> {
+    let declarations = parse_schema(SCHEMA)?;
+    let error = |name, json: &str| {
+        check_value(&declarations, name, &serde_json::from_str(json).unwrap())
+            .unwrap_err()
+            .to_string()
+    };
+
+    k9::snapshot!(error("ID", "1.0"), "$: expected integer, got 1.0");
+    k9::snapshot!(
+        error("StorageType", r#""Partial""#),
+        r#"$: expected Full | Delta, got "Partial""#
+    );
+    k9::snapshot!(
+        error("Operation", r#"{"Delete": 1, "Fetch": {"items": []}}"#),
+        r#"$: expected one of Fetch, Store, Delete, got {"Delete":1,"Fetch":{"items":[]}}"#
+    );
+    k9::snapshot!(
+        error("Batch", r#"[{"Fetch": {"items": [1, "2"]}}]"#),
+        r#"$[0].Fetch.items[1]: expected integer, got "2""#
+    );
+    k9::snapshot!(
+        error("Operation", r#"{"Fetch": {"items": [], "extra": 1}}"#),
+        "$.Fetch: unexpected field `extra`"
+    );
+    // Per language types are checked against their Rust type
+    k9::snapshot!(error("Weight", "1.5"), "$: expected integer, got 1.5");
+    k9::snapshot!(
+        error("IntNode", r#"{"id": 1}"#),
+        "$.value: expected integer, got null"
+    );
+
+    let mut node = declarations.fixture("IntNode", FixtureKind::Minimal)?;
+    node["children"] = serde_json::json!({"a": []});
+    k9::snapshot!(
+        check_value(&declarations, "IntNode", &node).unwrap_err(),
+        r#"$.children.a: expected integer, got "a""#
+    );
+    Ok(())
+}
@@ -0,0 +1,335 @@
+use super::project::Project;
+use crate::prelude::*;
+use anyhow::Result;
+
+const SCHEMA: &str = r#"
+hack_namespace "C";
+rust_impls;
+validators;
+
+struct User {
+    @min_length(1) @max_length(32) @pattern("^[a-z_]+$")
+    name: string,
+    @min(0) @max(150)
+    age: i32,
+    @non_empty
+    roles: vec<string>,
+    @min(0.5)
+    score?: f64,
+    @max_length(2)
+    labels?: map<string, string>,
+}
+
+enum Event {
+    Renamed { @non_empty name: string },
+    Deleted(i64),
+}
+"#;
+
+// Calls `validate()` of the generated Rust struct, with warnings denied
+const USAGE: &str = r#"
+#![deny(warnings)]
+
+#[test]
+fn validate() {
+    let mut user = User::new("ada".to_string(), 36, vec!["admin".to_string()]);
+    assert_eq!(user.validate(), Ok(()));
+
+    user.name = "Ada".to_string();
+    assert_eq!(user.validate(), Err("$.name: expected a match of ^[a-z_]+$".to_string()));
+    user.name = "a".repeat(32);
+    assert_eq!(user.validate(), Ok(()));
+    user.name.push('a');
+    assert_eq!(user.validate(), Err("$.name: expected length of at most 32".to_string()));
+    user.name = "ada".to_string();
+
+    user.age = 151;
+    assert_eq!(user.validate(), Err("$.age: expected at most 150".to_string()));
+    user.age = 36;
+
+    user.roles.clear();
+    assert_eq!(user.validate(), Err("$.roles: expected length of at least 1".to_string()));
+    user.roles.push("admin".to_string());
+
+    user.score = Some(0.25);
+    assert_eq!(user.validate(), Err("$.score: expected at least 0.5".to_string()));
+    user.score = None;
+
+    user.labels = Some((0..3).map(|i| (i.to_string(), i.to_string())).collect());
+    assert_eq!(user.validate(), Err("$.labels: expected length of at most 2".to_string()));
+}
+"#;
+
+const USAGE_CARGO_TOML: &str = r#"
+[package]
+name = "gull_constraints"
+version = "0.1.0"
+edition = "2018"
+
+[dependencies]
+regex = "1"
+
+[lib]
+name = "test"
+path = "lib.rs"
+
+[workspace]
+"#;
+
+#[test]
+fn rust_test() -> Result<()> {
+    k9::snapshot!(
+        parse_schema(SCHEMA)?.codegen_rust()?,
+        r#"
+use std::collections::BTreeMap;
+
+
+pub struct User {
+    pub name: String,
+    pub age: i32,
+    pub roles: Vec<String>,
+    pub score: Option<f64>,
+    pub labels: Option<BTreeMap<String, String>>,
+}
+
+impl User {
+    pub fn new(name: String, age: i32, roles: Vec<String>) -> Self {
+        Self {
+            name,
+            age,
+            roles,
+            score: None,
+            labels: None,
+        }
+    }
+}
+
+impl User {
+    /// Checks the constraints of every field
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.is_empty() {
+            return Err("$.name: expected length of at least 1".to_string());
+        }
+        if self.name.chars().count() > 32 {
+            return Err("$.name: expected length of at most 32".to_string());
+        }
+        static NAME_PATTERN: std::sync::OnceLock<regex::Regex> = std::sync::OnceLock::new();
+        if !NAME_PATTERN.get_or_init(|| regex::Regex::new("^[a-z_]+$").unwrap()).is_match(&self.name) {
+            return Err("$.name: expected a match of ^[a-z_]+$".to_string());
+        }
+        if self.age < 0 {
+            return Err("$.age: expected at least 0".to_string());
+        }
+        if self.age > 150 {
+            return Err("$.age: expected at most 150".to_string());
+        }
+        if self.roles.is_empty() {
+            return Err("$.roles: expected length of at least 1".to_string());
+        }
+        if let Some(value) = &self.score {
+            if *value < 0.5 {
+                return Err("$.score: expected at least 0.5".to_string());
+            }
+        }
+        if let Some(value) = &self.labels {
+            if value.len() > 2 {
+                return Err("$.labels: expected length of at most 2".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+pub enum Event {
+    Renamed {
+        name: String,
+    },
+    Deleted(i64),
+}
+
+impl From<i64> for Event {
+    fn from(value: i64) -> Self {
+        Self::Deleted(value)
+    }
+}
+
+impl Event {
+    pub fn is_renamed(&self) -> bool {
+        matches!(self, Self::Renamed { .. })
+    }
+
+    pub fn is_deleted(&self) -> bool {
+        matches!(self, Self::Deleted(..))
+    }
+
+    pub fn as_deleted(&self) -> Option<&i64> {
+        match self {
+            Self::Deleted(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn compile_test() -> Result<()> {
+    let project = Project::new("constraints")?;
+    project.write_file("Cargo.toml", USAGE_CARGO_TOML)?;
+    project.write_file(
+        "lib.rs",
+        &format!("{}\n{}", USAGE, parse_schema(SCHEMA)?.codegen_rust()?),
+    )?;
+    project
+        .run("cargo test --offline --quiet")?
+        .assert_success()
+}
+
+#[test]
+fn hack_test() -> Result<()> {
+    let hack = parse_schema(SCHEMA)?.codegen_hack()?;
+    let hack = hack
+        .lines()
+        .skip_while(|l| !l.starts_with("function validateCUser"))
+        .take_while(|l| *l != "}")
+        .collect::<Vec<_>>()
+        .join("\n");
+    k9::snapshot!(
+        hack,
+        r#"
+function validateCUser(mixed $x, string $path = '$'): CUser {
+    CGull::shapeOf(dict[
+        'name' => CGull::constrained(CGull::isString(), shape('min_length' => 1, 'max_length' => 32, 'pattern' => '^[a-z_]+$')),
+        'age' => CGull::constrained(CGull::isInt(), shape('minimum' => 0, 'maximum' => 150)),
+        'roles' => CGull::constrained(CGull::vecOf(CGull::isString()), shape('min_length' => 1)),
+        'score' => CGull::constrained(CGull::optionOf(CGull::isFloat()), shape('minimum' => 0.5)),
+        'labels' => CGull::constrained(CGull::optionOf(CGull::dictOf(CGull::isString())), shape('max_length' => 2)),
+    ])($x, $path);
+    /* HH_FIXME[4110] checked above */
+    return $x;
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn flow_test() -> Result<()> {
+    let flow = parse_schema(SCHEMA)?.codegen_flow()?;
+    let flow = flow
+        .lines()
+        .skip_while(|l| !l.starts_with("export function validateUser"))
+        .take_while(|l| *l != "}")
+        .chain(
+            flow.lines()
+                .skip_while(|l| !l.starts_with("export function validateEvent"))
+                .take_while(|l| *l != "}"),
+        )
+        .collect::<Vec<_>>()
+        .join("\n");
+    k9::snapshot!(
+        flow,
+        r#"
+export function validateUser(x: mixed, path: string = '$'): User {
+    gullStructOf({
+        'name': gullConstrained(gullString, {minLength: 1, maxLength: 32, pattern: "^[a-z_]+$"}),
+        'age': gullConstrained(gullInt, {minimum: 0, maximum: 150}),
+        'roles': gullConstrained(gullArrayOf(gullString), {minLength: 1}),
+        'score': gullConstrained(gullOptionOf(gullNumber), {minimum: 0.5}),
+        'labels': gullConstrained(gullOptionOf(gullMapOf(gullString)), {maxLength: 2}),
+    })(x, path);
+    return (x: any);
+export function validateEvent(x: mixed, path: string = '$'): Event {
+    gullEnumOf({
+        'Renamed': gullStructOf({
+            'name': gullConstrained(gullString, {minLength: 1}),
+        }),
+        'Deleted': gullInt,
+    })(x, path);
+    return (x: any);
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn api_docs_test() -> Result<()> {
+    let markdown = parse_schema(SCHEMA)?.codegen_markdown()?;
+    let markdown = markdown
+        .lines()
+        .filter(|l| l.starts_with("| `"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    k9::snapshot!(
+        markdown,
+        r#"
+| `name` | string `min_length(1)` `max_length(32)` `pattern("^[a-z_]+$")` |  |
+| `age` | i32 `min(0)` `max(150)` |  |
+| `roles` | vec&lt;string&gt; `min_length(1)` |  |
+| `score` | option&lt;f64&gt; `min(0.5)` |  |
+| `labels` | option&lt;map&lt;string, string&gt;&gt; `max_length(2)` |  |
+| `Renamed` | { name: string `min_length(1)` } |  |
+| `Deleted` | i64 |  |
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn compat_test() -> Result<()> {
+    let old = parse_schema(r#"struct A { @max_length("10") x: string, y: i64 }"#)?;
+    let new = parse_schema(r#"struct A { @max_length("20") x: string, @min("0") y: i64 }"#)?;
+    k9::snapshot!(
+        check_compatibility(&old, &new).to_string(),
+        r#"
+[FORWARD] A.x: constraint `max_length(20)` was added
+[BACKWARD] A.x: constraint `max_length(10)` was removed
+[FORWARD] A.y: constraint `min(0)` was added
+Overall: breaking
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn errors_test() {
+    let error = |source: &str| match parse_schema(source) {
+        Ok(declarations) => declarations.codegen_rust().unwrap_err().to_string(),
+        Err(e) => e.to_string(),
+    };
+
+    k9::snapshot!(
+        error(r#"struct A { @pattern("a") x: i64 }"#),
+        "`pattern` can only be used on strings, field `x` isn't one in `A`"
+    );
+    k9::snapshot!(
+        error(r#"struct A { @pattern("(") x: string }"#),
+        r#"`pattern("(")` of field `x` isn't a valid regex in `A`"#
+    );
+    k9::snapshot!(
+        error(r#"struct A { @min(0) x: string }"#),
+        "`min` can only be used on numbers, field `x` isn't one in `A`"
+    );
+    k9::snapshot!(
+        error(r#"struct A { @non_empty x: B } struct B {}"#),
+        "`min_length` can only be used on strings, vecs, sets and maps, field `x` isn't one in `A`"
+    );
+    k9::snapshot!(
+        error(r#"struct A { @min(0.5) x: i64 }"#),
+        "`min(0.5)` of integer field `x` must be a whole number in `A`"
+    );
+    k9::snapshot!(
+        error(r#"struct A { @min(2) @max(1) x: f64 }"#),
+        "field `x` has a minimum greater than its maximum in `A`"
+    );
+    k9::snapshot!(
+        error(r#"enum A { B { @max_length(-1) x: string } }"#),
+        "1:14: `@max_length` expects a number, found `-1`"
+    );
+    k9::snapshot!(
+        error(r#"struct A { @non_empty("1") x: vec<i64> }"#),
+        "1:12: `@non_empty` doesn't take a value"
+    );
+}
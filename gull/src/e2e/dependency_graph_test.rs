@@ -0,0 +1,155 @@
+use crate::prelude::*;
+use anyhow::Result;
+
+const SCHEMA: &str = r#"
+code rust "use std::fmt;";
+
+//! Graph types
+
+struct Graph {
+    nodes: map<NodeID, Node<NodeID>>,
+    root: option<NodeID>,
+}
+
+struct Node<T> {
+    id: NodeID,
+    value: T,
+    children: vec<Node<T>>,
+}
+
+type NodeID = i64;
+
+/// Expressions reference each other by value and need to be boxed in rust
+enum Expr {
+    Literal(i64),
+    Not(Not),
+    And { left: Expr, right: Expr },
+}
+
+struct Not { expr: Expr }
+
+struct Tree { children: vec<Tree>, meta: Wrapper<Tree> }
+
+struct Wrapper<T> { items: vec<T> }
+"#;
+
+#[test]
+fn order_test() -> Result<()> {
+    let declarations = parse_schema(SCHEMA)?;
+    let graph = DependencyGraph::new(&declarations);
+
+    assert_eq!(
+        graph.topological_order(),
+        vec!["Expr", "Not", "NodeID", "Node", "Graph", "Wrapper", "Tree"]
+    );
+    assert_eq!(
+        graph.alphabetical_order(),
+        vec!["Expr", "Graph", "Node", "NodeID", "Not", "Tree", "Wrapper"]
+    );
+    Ok(())
+}
+
+#[test]
+fn cycles_test() -> Result<()> {
+    let declarations = parse_schema(SCHEMA)?;
+    let graph = DependencyGraph::new(&declarations);
+
+    assert_eq!(
+        graph.cycles(),
+        vec![vec!["Expr", "Not"], vec!["Node"], vec!["Tree"]]
+    );
+    // `vec<T>` inside of `Wrapper` makes `Tree` finite
+    assert_eq!(graph.boxed_cycles(), vec![vec!["Expr", "Not"]]);
+    assert_eq!(
+        graph.dependencies("Graph"),
+        vec![("Node", EdgeKind::Indirect), ("NodeID", EdgeKind::Direct)]
+    );
+    Ok(())
+}
+
+#[test]
+fn dot_test() -> Result<()> {
+    let declarations = parse_schema(SCHEMA)?;
+    k9::snapshot!(
+        DependencyGraph::new(&declarations).to_dot(),
+        r#"
+digraph gull {
+    "Expr" [color=red];
+    "Graph";
+    "Node";
+    "NodeID";
+    "Not" [color=red];
+    "Tree";
+    "Wrapper";
+    "Expr" -> "Expr";
+    "Expr" -> "Not";
+    "Graph" -> "Node" [style=dashed];
+    "Graph" -> "NodeID";
+    "Node" -> "Node" [style=dashed];
+    "Node" -> "NodeID";
+    "Not" -> "Expr";
+    "Tree" -> "Tree" [style=dashed];
+    "Tree" -> "Wrapper";
+}
+
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn emit_order_test() -> Result<()> {
+    let schema = |order| {
+        format!(
+            "emit_order {}; code rust \"use std::fmt;\"; struct B {{ a: A }} type C = i64; struct A {{ c: C }}",
+            order
+        )
+    };
+
+    let topological = parse_schema(&schema("topological"))?;
+    k9::snapshot!(
+        topological.codegen_rust()?,
+        r#"
+
+
+use std::fmt;
+
+pub type C = i64;
+
+pub struct A {
+    pub c: C,
+}
+
+pub struct B {
+    pub a: A,
+}
+
+"#
+    );
+
+    let alphabetical = parse_schema(&schema("alphabetical"))?;
+    k9::snapshot!(
+        alphabetical.codegen_flow()?,
+        r#"
+
+
+
+export type A = {
+    'c': C,
+};
+
+export type B = {
+    'a': A,
+};
+
+export type C = number;
+
+"#
+    );
+
+    k9::snapshot!(
+        parse_schema("emit_order random;").unwrap_err().to_string(),
+        "1:1: unknown emit order `random`, expected `insertion`, `topological` or `alphabetical`"
+    );
+    Ok(())
+}
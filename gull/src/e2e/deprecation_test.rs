@@ -0,0 +1,329 @@
+use crate::prelude::*;
+use anyhow::Result;
+
+const SCHEMA: &str = r#"
+hack_namespace "D";
+
+/// Identifier of a node
+@deprecated("Use `NodeID` instead")
+@since("1.4.0")
+type ID = i64;
+
+type NodeID = i64;
+
+struct Node {
+    id: NodeID,
+    /// Human readable name
+    @deprecated("Names aren't unique, use `id`")
+    name: string,
+    @deprecated("Don't rely on the 'legacy' layout")
+    @since("1.2.0")
+    legacy?: vec<string>,
+}
+
+@deprecated("Replaced by `Command`")
+enum Status { Active, Removed }
+
+enum Command {
+    /// Add a node
+    Add(Node),
+    @deprecated("Remove nodes instead")
+    @since("2.0.0")
+    Clear { force: bool },
+}
+"#;
+
+#[test]
+fn rust_test() -> Result<()> {
+    k9::snapshot!(
+        parse_schema(SCHEMA)?.codegen_rust()?,
+        r#"
+
+
+#[deprecated(since = "1.4.0", note = "Use `NodeID` instead")]
+/// Identifier of a node
+pub type ID = i64;
+
+pub type NodeID = i64;
+
+pub struct Node {
+    pub id: NodeID,
+    #[deprecated(note = "Names aren't unique, use `id`")]
+    /// Human readable name
+    pub name: String,
+    #[deprecated(since = "1.2.0", note = "Don't rely on the 'legacy' layout")]
+    pub legacy: Option<Vec<String>>,
+}
+
+#[deprecated(note = "Replaced by `Command`")]
+pub enum Status {
+    Active,
+    Removed,
+}
+
+pub enum Command {
+    /// Add a node
+    Add(Node),
+    #[deprecated(since = "2.0.0", note = "Remove nodes instead")]
+    Clear {
+        force: bool,
+    },
+}
+
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn hack_test() -> Result<()> {
+    k9::snapshot!(
+        parse_schema(SCHEMA)?.codegen_hack()?,
+        r#"
+<?hh
+
+// Identifier of a node
+<<__Deprecated('Use `NodeID` instead (since 1.4.0)')>>
+type DID = int;
+
+type DNodeID = int;
+
+type DNode = shape(
+    'id' => DNodeID,
+    // Human readable name
+    // @deprecated Names aren't unique, use `id`
+    'name' => string,
+    // @deprecated Don't rely on the 'legacy' layout (since 1.2.0)
+    ?'legacy' => vec<string>,
+);
+
+<<__Deprecated('Replaced by `Command`')>>
+enum DStatus: string as string {
+    ACTIVE = "Active";
+    REMOVED = "Removed";
+}
+
+
+enum DCommandType: string as string {
+    ADD = "Add";
+    CLEAR = "Clear";
+}
+
+type DCommand = shape(
+    // Add a node
+    ?'Add' => DNode,
+    // @deprecated Remove nodes instead (since 2.0.0)
+    ?'Clear' =>  shape(
+        'force' => bool,
+    ),
+);
+
+function DCommand_getType(DCommand $x): DCommandType {
+    if (Shapes::keyExists($x, 'Add')) {
+        return DCommandType::ADD;
+    }
+    if (Shapes::keyExists($x, 'Clear')) {
+        return DCommandType::CLEAR;
+    }
+    invariant_violation('No variant of DCommand is set');
+}
+
+function DCommand_getAdd(DCommand $x): ?DNode {
+    return Shapes::idx($x, 'Add');
+}
+
+<<__Deprecated('Remove nodes instead (since 2.0.0)')>>
+function DCommand_getClear(DCommand $x): ?shape(
+    'force' => bool,
+) {
+    return Shapes::idx($x, 'Clear');
+}
+
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn flow_test() -> Result<()> {
+    k9::snapshot!(
+        parse_schema(SCHEMA)?.codegen_flow()?,
+        r#"
+
+// Identifier of a node
+/** @deprecated Use `NodeID` instead (since 1.4.0) */
+export type ID = number;
+
+export type NodeID = number;
+
+export type Node = {
+    'id': NodeID,
+    // Human readable name
+    /** @deprecated Names aren't unique, use `id` */
+    'name': string,
+    /** @deprecated Don't rely on the 'legacy' layout (since 1.2.0) */
+    'legacy'?: Array<string>,
+};
+
+/** @deprecated Replaced by `Command` */
+export type Status = "Active" | "Removed";
+
+/** @deprecated Replaced by `Command` */
+export enum StatusEnum {Active, Removed};
+
+export type CommandType = "Add" | "Clear";
+
+export type Command = {
+    // Add a node
+    'Add'?: Node,
+    /** @deprecated Remove nodes instead (since 2.0.0) */
+    'Clear'?:  {
+        'force': boolean,
+    },
+};
+
+export function getCommandType(x: Command): CommandType {
+    if (x.Add !== undefined) {
+        return 'Add';
+    }
+    if (x.Clear !== undefined) {
+        return 'Clear';
+    }
+    throw new Error('No variant of Command is set');
+}
+
+export function commandAdd(value: Node): Command {
+    return {'Add': value};
+}
+
+/** @deprecated Remove nodes instead (since 2.0.0) */
+export function commandClear(value: {
+    'force': boolean,
+}): Command {
+    return {'Clear': value};
+}
+
+export function matchCommand<TResult>(
+    x: Command,
+    cases: {|
+        'Add': (value: Node) => TResult,
+        'Clear': (value: {
+            'force': boolean,
+        }) => TResult,
+    |},
+): TResult {
+    if (x.Add !== undefined) {
+        return cases.Add(x.Add);
+    }
+    if (x.Clear !== undefined) {
+        return cases.Clear(x.Clear);
+    }
+    throw new Error('No variant of Command is set');
+}
+
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn markdown_test() -> Result<()> {
+    let markdown = parse_schema(SCHEMA)?.codegen_markdown()?;
+    let deprecations = markdown
+        .lines()
+        .filter(|l| l.contains("Deprecated: "))
+        .collect::<Vec<_>>()
+        .join("\n");
+    k9::snapshot!(
+        deprecations,
+        r#"
+Deprecated: Use `NodeID` instead (since 1.4.0)
+| `name` | string | Deprecated: Names aren't unique, use `id`. Human readable name |
+| `legacy` | option&lt;vec&lt;string&gt;&gt; | Deprecated: Don't rely on the 'legacy' layout (since 1.2.0). |
+Deprecated: Replaced by `Command`
+| `Clear` | { force: bool } | Deprecated: Remove nodes instead (since 2.0.0). |
+"#
+    );
+    Ok(())
+}
+
+// e.g. a derived bare `#[deprecated]`
+#[test]
+fn without_note_test() -> Result<()> {
+    let mut declarations = Declarations::new();
+    declarations.add(
+        TypeDeclaration::new("ID", DeclarationValue::TPrimitive(TPrimitive::Ti64)).config(
+            TypeDeclarationConfig::Deprecated(Deprecation {
+                message: None,
+                since: None,
+            }),
+        ),
+    );
+    let deprecations = |code: String| {
+        code.lines()
+            .filter(|l| l.contains("eprecated"))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    k9::snapshot!(deprecations(declarations.codegen_rust()?), "#[deprecated]");
+    k9::snapshot!(
+        deprecations(declarations.codegen_hack()?),
+        r#"<<__Deprecated('deprecated')>>"#
+    );
+    k9::snapshot!(
+        deprecations(declarations.codegen_flow()?),
+        "/** @deprecated */"
+    );
+    Ok(())
+}
+
+#[test]
+fn errors_test() {
+    let error = |source: &str| parse_schema(source).unwrap_err().to_string();
+
+    k9::snapshot!(
+        error("@since(\"1.0\") type A = i64;"),
+        "1:1: `@since` can only be used together with `@deprecated`"
+    );
+    k9::snapshot!(
+        error("@deprecated type A = i64;"),
+        "1:1: `@deprecated` requires a value: `@deprecated(\"...\")`"
+    );
+}
+
+#[test]
+fn repeated_config_test() -> Result<()> {
+    let error = |source: &str| parse_schema(source).unwrap_err().to_string();
+    k9::snapshot!(
+        error("@deprecated(\"a\") @deprecated(\"b\") type A = i64;"),
+        "1:18: `@deprecated` is used more than once"
+    );
+    k9::snapshot!(
+        error("struct A { @since(\"1\") @deprecated(\"a\") @since(\"2\") b: i64 }"),
+        "1:41: `@since` is used more than once"
+    );
+    k9::snapshot!(
+        error("enum A { @deprecated(\"a\") @deprecated(\"b\") B(i64) }"),
+        "1:27: `@deprecated` is used more than once"
+    );
+
+    // Declarations built in Rust are checked before codegen
+    let mut declarations = Declarations::new();
+    declarations.add(
+        TypeDeclaration::new("A", DeclarationValue::TPrimitive(TPrimitive::Ti64))
+            .config(TypeDeclarationConfig::Deprecated(Deprecation::new("a")))
+            .config(TypeDeclarationConfig::Deprecated(Deprecation::new("b"))),
+    );
+    k9::snapshot!(
+        declarations.codegen_rust().unwrap_err(),
+        "`Deprecated` is set more than once on `A`"
+    );
+    k9::snapshot!(
+        parse_schema("hack_namespace \"A\"; hack_namespace \"B\";")?
+            .codegen_hack()
+            .unwrap_err(),
+        "`HackNamespace` is set more than once on the declarations"
+    );
+    Ok(())
+}
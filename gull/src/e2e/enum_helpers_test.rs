@@ -0,0 +1,76 @@
+use crate::prelude::*;
+use anyhow::Result;
+
+const SCHEMA: &str = r#"
+struct Node {
+    id: i64,
+}
+
+@deprecated("Use events instead")
+enum Op {
+    Fetch(Node),
+    Clear(option<i64>),
+}
+"#;
+
+#[test]
+fn flow_test() -> Result<()> {
+    k9::snapshot!(
+        parse_schema(SCHEMA)?.codegen_flow()?,
+        r#"
+
+export type Node = {
+    'id': number,
+};
+
+/** @deprecated Use events instead */
+export type OpType = "Fetch" | "Clear";
+
+/** @deprecated Use events instead */
+export type Op = {
+    'Fetch'?: Node,
+    'Clear'?: ?number,
+};
+
+/** @deprecated Use events instead */
+export function getOpType(x: Op): OpType {
+    if (x.Fetch !== undefined) {
+        return 'Fetch';
+    }
+    if (x.Clear !== undefined) {
+        return 'Clear';
+    }
+    throw new Error('No variant of Op is set');
+}
+
+/** @deprecated Use events instead */
+export function opFetch(value: Node): Op {
+    return {'Fetch': value};
+}
+
+/** @deprecated Use events instead */
+export function opClear(value: ?number): Op {
+    return {'Clear': value};
+}
+
+/** @deprecated Use events instead */
+export function matchOp<TResult>(
+    x: Op,
+    cases: {|
+        'Fetch': (value: Node) => TResult,
+        'Clear': (value: ?number) => TResult,
+    |},
+): TResult {
+    if (x.Fetch !== undefined) {
+        return cases.Fetch(x.Fetch);
+    }
+    if (x.Clear !== undefined) {
+        return cases.Clear(x.Clear);
+    }
+    throw new Error('No variant of Op is set');
+}
+
+"#
+    );
+    Ok(())
+}
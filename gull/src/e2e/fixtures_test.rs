@@ -0,0 +1,154 @@
+use crate::prelude::*;
+use anyhow::Result;
+
+const SCHEMA: &str = r#"
+type ID = i64;
+
+enum StorageType { Full, Delta }
+
+struct Node<T> {
+    id: ID,
+    value: T,
+    tags: set<string>,
+    children: map<i32, vec<ID>>,
+    by_storage: map<StorageType, bool>,
+    parent?: ID,
+    frame: (string, option<f64>),
+}
+
+enum Operation {
+    Fetch { items: vec<ID>, limit?: usize },
+    Store(Node<ID>),
+}
+"#;
+
+fn fixture(name: &str, kind: FixtureKind) -> Result<String> {
+    let declarations = parse_schema(SCHEMA)?;
+    Ok(serde_json::to_string_pretty(
+        &declarations.fixture(name, kind)?,
+    )?)
+}
+
+#[test]
+fn minimal_test() -> Result<()> {
+    k9::snapshot!(
+        fixture("Operation", FixtureKind::Minimal)?,
+        r#"
+{
+  "Fetch": {
+    "items": []
+  }
+}
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn maximal_test() -> Result<()> {
+    k9::snapshot!(
+        fixture("Operation", FixtureKind::Maximal)?,
+        r#"
+{
+  "Store": {
+    "id": 9007199254740991,
+    "value": 9007199254740991,
+    "tags": [
+      "example"
+    ],
+    "children": {
+      "2147483647": [
+        9007199254740991
+      ]
+    },
+    "by_storage": {
+      "Delta": true
+    },
+    "parent": 9007199254740991,
+    "frame": [
+      "example",
+      1000.5
+    ]
+  }
+}
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn random_test() -> Result<()> {
+    let random = |seed| fixture("Operation", FixtureKind::Random { seed });
+    assert_eq!(random(1)?, random(1)?);
+    assert_ne!(random(1)?, random(2)?);
+    k9::snapshot!(
+        random(3)?,
+        r#"
+{
+  "Store": {
+    "id": -4762501258805719,
+    "value": 8748047543533036,
+    "tags": [
+      "my",
+      "pyv",
+      "qhyudy"
+    ],
+    "children": {
+      "637718910": [
+        2788423391715097,
+        2837318958859024,
+        4032083819994510
+      ],
+      "-662478791": []
+    },
+    "by_storage": {
+      "Delta": true
+    },
+    "parent": -4959331505492429,
+    "frame": [
+      "dcbersk",
+      -575.25
+    ]
+  }
+}
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn errors_test() -> Result<()> {
+    let declarations = parse_schema(
+        r#"
+        struct List { next: List }
+        struct Node<T> { value: T }
+        type Raw = raw("crate::Raw");
+        "#,
+    )?;
+    let error = |name| {
+        format!(
+            "{:#}",
+            declarations
+                .fixture(name, FixtureKind::Minimal)
+                .unwrap_err()
+        )
+    };
+
+    k9::snapshot!(
+        error("Missing"),
+        "Failed to generate a value of `Missing`: Unknown type `Missing`"
+    );
+    k9::snapshot!(
+        error("Node"),
+        "Failed to generate a value of `Node`: Generic param `T` of `Node` must be provided"
+    );
+    k9::snapshot!(
+        error("Raw"),
+        r#"Failed to generate a value of `Raw`: Can't generate a value of a hardcoded type `crate::Raw`"#
+    );
+    k9::snapshot!(
+        error("List"),
+        r#"Failed to generate a value of `List`: `List` can't be generated without infinitely referencing itself"#
+    );
+    Ok(())
+}
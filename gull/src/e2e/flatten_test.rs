@@ -0,0 +1,335 @@
+use crate::prelude::*;
+use anyhow::Result;
+
+const SCHEMA: &str = r#"
+hack_namespace "F";
+
+/// Shared by every stored object
+struct Metadata {
+    created_at: i64,
+    /// Who made the last change
+    updated_by?: string,
+}
+
+struct Page<T> {
+    items: vec<T>,
+    cursor?: string,
+}
+
+struct Node {
+    id: i64,
+    /// Inlined into the node
+    @flatten
+    metadata: Metadata,
+}
+
+struct NodePage {
+    @flatten
+    page: Page<Node>,
+    total: i64,
+}
+
+enum Event {
+    Created { @flatten node: Node },
+    Deleted(i64),
+}
+"#;
+
+#[test]
+fn rust_test() -> Result<()> {
+    k9::snapshot!(
+        parse_schema(SCHEMA)?.codegen_rust()?,
+        r#"
+
+
+/// Shared by every stored object
+pub struct Metadata {
+    pub created_at: i64,
+    /// Who made the last change
+    pub updated_by: Option<String>,
+}
+
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub cursor: Option<String>,
+}
+
+pub struct Node {
+    pub id: i64,
+    #[serde(flatten)]
+    /// Inlined into the node
+    pub metadata: Metadata,
+}
+
+pub struct NodePage {
+    #[serde(flatten)]
+    pub page: Page<Node>,
+    pub total: i64,
+}
+
+pub enum Event {
+    Created {
+        #[serde(flatten)]
+        node: Node,
+    },
+    Deleted(i64),
+}
+
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn hack_test() -> Result<()> {
+    k9::snapshot!(
+        parse_schema(SCHEMA)?.codegen_hack()?,
+        r#"
+<?hh
+
+// Shared by every stored object
+type FMetadata = shape(
+    'created_at' => int,
+    // Who made the last change
+    ?'updated_by' => string,
+);
+
+type FPage<T> = shape(
+    'items' => vec<T>,
+    ?'cursor' => string,
+);
+
+type FNode = shape(
+    'id' => int,
+    'created_at' => int,
+    // Who made the last change
+    ?'updated_by' => string,
+);
+
+type FNodePage = shape(
+    'items' => vec<FNode>,
+    ?'cursor' => string,
+    'total' => int,
+);
+
+
+enum FEventType: string as string {
+    CREATED = "Created";
+    DELETED = "Deleted";
+}
+
+type FEvent = shape(
+    ?'Created' =>  shape(
+        'id' => int,
+        'created_at' => int,
+        // Who made the last change
+        ?'updated_by' => string,
+    ),
+    ?'Deleted' => int,
+);
+
+function FEvent_getType(FEvent $x): FEventType {
+    if (Shapes::keyExists($x, 'Created')) {
+        return FEventType::CREATED;
+    }
+    if (Shapes::keyExists($x, 'Deleted')) {
+        return FEventType::DELETED;
+    }
+    invariant_violation('No variant of FEvent is set');
+}
+
+function FEvent_getCreated(FEvent $x): ?shape(
+    'id' => int,
+    'created_at' => int,
+    // Who made the last change
+    ?'updated_by' => string,
+) {
+    return Shapes::idx($x, 'Created');
+}
+
+function FEvent_getDeleted(FEvent $x): ?int {
+    return Shapes::idx($x, 'Deleted');
+}
+
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn flow_test() -> Result<()> {
+    k9::snapshot!(
+        parse_schema(SCHEMA)?.codegen_flow()?,
+        r#"
+
+// Shared by every stored object
+export type Metadata = {
+    'created_at': number,
+    // Who made the last change
+    'updated_by'?: string,
+};
+
+export type Page<T> = {
+    'items': Array<T>,
+    'cursor'?: string,
+};
+
+export type Node = {
+    'id': number,
+    // Inlined into the node
+    ...Metadata,
+};
+
+export type NodePage = {
+    ...Page<Node>,
+    'total': number,
+};
+
+export type EventType = "Created" | "Deleted";
+
+export type Event = {
+    'Created'?:  {
+        ...Node,
+    },
+    'Deleted'?: number,
+};
+
+export function getEventType(x: Event): EventType {
+    if (x.Created !== undefined) {
+        return 'Created';
+    }
+    if (x.Deleted !== undefined) {
+        return 'Deleted';
+    }
+    throw new Error('No variant of Event is set');
+}
+
+export function eventCreated(value: {
+    ...Node,
+}): Event {
+    return {'Created': value};
+}
+
+export function eventDeleted(value: number): Event {
+    return {'Deleted': value};
+}
+
+export function matchEvent<TResult>(
+    x: Event,
+    cases: {|
+        'Created': (value: {
+            ...Node,
+        }) => TResult,
+        'Deleted': (value: number) => TResult,
+    |},
+): TResult {
+    if (x.Created !== undefined) {
+        return cases.Created(x.Created);
+    }
+    if (x.Deleted !== undefined) {
+        return cases.Deleted(x.Deleted);
+    }
+    throw new Error('No variant of Event is set');
+}
+
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn validators_test() -> Result<()> {
+    let declarations = parse_schema(&format!("validators;\n{}", SCHEMA))?;
+    let flow = declarations.codegen_flow()?;
+    let flow = flow.split("type GullCheck").next().unwrap_or_default();
+    let flow = flow
+        .lines()
+        .skip_while(|l| !l.starts_with("export function validateNodePage"))
+        .take_while(|l| *l != "}")
+        .collect::<Vec<_>>()
+        .join("\n");
+    k9::snapshot!(
+        flow,
+        r#"
+export function validateNodePage(x: mixed, path: string = '$'): NodePage {
+    gullStructOf({
+        'items': gullArrayOf(validateNode),
+        'cursor': gullOptionOf(gullString),
+        'total': gullInt,
+    })(x, path);
+    return (x: any);
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn fixture_test() -> Result<()> {
+    let declarations = parse_schema(SCHEMA)?;
+    k9::snapshot!(
+        serde_json::to_string(&declarations.fixture("NodePage", FixtureKind::Maximal)?)?,
+        r#"{"items":[{"id":9007199254740991,"created_at":9007199254740991,"updated_by":"example"}],"cursor":"example","total":9007199254740991}"#
+    );
+    Ok(())
+}
+
+#[test]
+fn compat_test() -> Result<()> {
+    let old = parse_schema("struct A { x: i64, y?: string } struct B { a: i64 }")?;
+    let new = parse_schema("struct A { x: i64, @flatten b: B }  struct B { a: i64 }")?;
+    k9::snapshot!(
+        check_compatibility(&old, &new).to_string(),
+        r#"
+[COMPATIBLE] A.y: field was removed
+[FORWARD] A.a: required field was added
+Overall: forward compatible
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn errors_test() {
+    let error = |source: &str| match parse_schema(source) {
+        Ok(declarations) => declarations.codegen_rust().unwrap_err().to_string(),
+        Err(e) => e.to_string(),
+    };
+
+    k9::snapshot!(
+        error("struct A { @flatten b: option<B> } struct B { c: i64 }"),
+        "flattened field `b` must be a reference to a struct in `A`"
+    );
+    k9::snapshot!(
+        error("struct A { @flatten b: B } enum B { C, D }"),
+        "flattened field `b` references `B`, which isn't a declared struct in `A`"
+    );
+    k9::snapshot!(
+        error("struct A { c: i64, @flatten b: B } struct B { c: string }"),
+        "field `c` is defined more than once after flattening in `A`"
+    );
+    k9::snapshot!(
+        error("struct A { @flatten b: B } struct B { @flatten a: A }"),
+        "flattened field `b` references `B`, which flattens itself in `A`"
+    );
+    k9::snapshot!(
+        error("struct A { @flatten b: B } struct B { @flatten b: B }"),
+        "flattened field `b` references `B`, which flattens itself in `A`"
+    );
+    k9::snapshot!(
+        error("struct A { @flatten(\"x\") b: B } struct B {}"),
+        "1:12: `@flatten` doesn't take a value"
+    );
+}
+
+#[test]
+fn deep_test() -> Result<()> {
+    // A chain of 40 structs that each flatten the next one
+    let schema = (0..40)
+        .map(|i| format!("struct S{} {{ f{}: i64, @flatten s: S{} }}\n", i, i, i + 1))
+        .chain(std::iter::once("struct S40 { f40: i64 }".to_string()))
+        .collect::<String>();
+    let hack = parse_schema(&schema)?.codegen_hack()?;
+    // Every shape ends with the field of the last struct
+    k9::snapshot!(hack.matches("'f40' => int").count(), "41");
+    Ok(())
+}
@@ -0,0 +1,286 @@
+use crate::prelude::*;
+use anyhow::Result;
+
+const SCHEMA: &str = r#"
+hack_namespace "G";
+
+struct Index<@hack("arraykey") @flow("string | number") K: Ord, V = string> {
+    entries: map<K, vec<V>>,
+    default?: V,
+}
+
+enum Tree<T: Clone = i64> {
+    Leaf(T),
+    Branch { children: vec<Tree<T>> },
+}
+
+type Named = Index<string>;
+
+type Counts = Index<string, u32>;
+
+type Forest = vec<Tree>;
+
+struct Page {
+    names: Named,
+    tags: Index<string, option<vec<string>>>,
+    forest: Forest,
+    trees: vec<Tree<map<string, bool>>>,
+}
+"#;
+
+#[test]
+fn rust_test() -> Result<()> {
+    let declarations = parse_schema(SCHEMA)?;
+
+    k9::snapshot!(
+        declarations.codegen_rust()?,
+        r#"
+use std::collections::BTreeMap;
+
+
+pub struct Index<K: Ord, V = String> {
+    pub entries: BTreeMap<K, Vec<V>>,
+    pub default: Option<V>,
+}
+
+pub enum Tree<T: Clone = i64> {
+    Leaf(T),
+    Branch {
+        children: Vec<Tree<T>>,
+    },
+}
+
+pub type Named = Index<String, String>;
+
+pub type Counts = Index<String, u32>;
+
+pub type Forest = Vec<Tree<i64>>;
+
+pub struct Page {
+    pub names: Named,
+    pub tags: Index<String, Option<Vec<String>>>,
+    pub forest: Forest,
+    pub trees: Vec<Tree<BTreeMap<String, bool>>>,
+}
+
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn hack_test() -> Result<()> {
+    let declarations = parse_schema(SCHEMA)?;
+
+    k9::snapshot!(
+        declarations.codegen_hack()?,
+        r#"
+<?hh
+
+type GIndex<K as arraykey, V> = shape(
+    'entries' => dict<K, vec<V>>,
+    ?'default' => V,
+);
+
+
+enum GTreeType: string as string {
+    LEAF = "Leaf";
+    BRANCH = "Branch";
+}
+
+type GTree<T> = shape(
+    ?'Leaf' => T,
+    ?'Branch' =>  shape(
+        'children' => vec<GTree<T>>,
+    ),
+);
+
+function GTree_getType<T>(GTree<T> $x): GTreeType {
+    if (Shapes::keyExists($x, 'Leaf')) {
+        return GTreeType::LEAF;
+    }
+    if (Shapes::keyExists($x, 'Branch')) {
+        return GTreeType::BRANCH;
+    }
+    invariant_violation('No variant of GTree is set');
+}
+
+function GTree_getLeaf<T>(GTree<T> $x): ?T {
+    return Shapes::idx($x, 'Leaf');
+}
+
+function GTree_getBranch<T>(GTree<T> $x): ?shape(
+    'children' => vec<GTree<T>>,
+) {
+    return Shapes::idx($x, 'Branch');
+}
+
+type GNamed = GIndex<string, string>;
+
+type GCounts = GIndex<string, int>;
+
+type GForest = vec<GTree<int>>;
+
+type GPage = shape(
+    'names' => GNamed,
+    'tags' => GIndex<string, ?vec<string>>,
+    'forest' => GForest,
+    'trees' => vec<GTree<dict<arraykey, bool>>>,
+);
+
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn flow_test() -> Result<()> {
+    let declarations = parse_schema(SCHEMA)?;
+
+    k9::snapshot!(
+        declarations.codegen_flow()?,
+        r#"
+
+export type Index<K: string | number, V = string> = {
+    'entries': {[key: K]: Array<V>},
+    'default'?: V,
+};
+
+export type TreeType = "Leaf" | "Branch";
+
+export type Tree<T = number> = {
+    'Leaf'?: T,
+    'Branch'?:  {
+        'children': Array<Tree<T>>,
+    },
+};
+
+export function getTreeType<T>(x: Tree<T>): TreeType {
+    if (x.Leaf !== undefined) {
+        return 'Leaf';
+    }
+    if (x.Branch !== undefined) {
+        return 'Branch';
+    }
+    throw new Error('No variant of Tree is set');
+}
+
+export function treeLeaf<T>(value: T): Tree<T> {
+    return {'Leaf': value};
+}
+
+export function treeBranch<T>(value: {
+    'children': Array<Tree<T>>,
+}): Tree<T> {
+    return {'Branch': value};
+}
+
+export function matchTree<T, TResult>(
+    x: Tree<T>,
+    cases: {|
+        'Leaf': (value: T) => TResult,
+        'Branch': (value: {
+            'children': Array<Tree<T>>,
+        }) => TResult,
+    |},
+): TResult {
+    if (x.Leaf !== undefined) {
+        return cases.Leaf(x.Leaf);
+    }
+    if (x.Branch !== undefined) {
+        return cases.Branch(x.Branch);
+    }
+    throw new Error('No variant of Tree is set');
+}
+
+export type Named = Index<string, string>;
+
+export type Counts = Index<string, number>;
+
+export type Forest = Array<Tree<number>>;
+
+export type Page = {
+    'names': Named,
+    'tags': Index<string, ?Array<string>>,
+    'forest': Forest,
+    'trees': Array<Tree<{[key: string]: boolean}>>,
+};
+
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn errors_test() {
+    let error = |source: &str| parse_schema(source).unwrap_err().to_string();
+
+    k9::snapshot!(
+        error("struct A<K, V = string> { k: K, v: V }\ntype B = A;"),
+        "2:10: `A` expects 1 to 2 generic argument(s), found 0"
+    );
+    k9::snapshot!(
+        error("struct A<T> { t: T }\ntype B = A<string, i64>;"),
+        "2:10: `A` expects 1 generic argument(s), found 2"
+    );
+    k9::snapshot!(
+        error("struct A<K = string, V> { k: K, v: V }"),
+        "1:22: generic param `V` needs a default because it goes after `K` that has one"
+    );
+    k9::snapshot!(
+        error("struct A<K, V = K> { k: K, v: V }"),
+        "1:17: default of generic param `V` can't use other generic params"
+    );
+    k9::snapshot!(
+        error("struct A<T, T> { t: T }"),
+        "1:13: generic param `T` is defined more than once"
+    );
+    k9::snapshot!(
+        error("enum A<T> { B, C }"),
+        "1:1: simple enum `A` can't have generic params"
+    );
+    k9::snapshot!(
+        error("struct A<@rust(\"Ord\") T> { t: T }"),
+        "1:10: unknown attribute `@rust`"
+    );
+
+    let codegen_error =
+        |declarations: Declarations| declarations.codegen_rust().unwrap_err().to_string();
+
+    let mut declarations = Declarations::new();
+    let pair = declarations.add(
+        TypeDeclaration::new(
+            "Pair",
+            DeclarationValue::TTuple(TTuple {
+                items: vec![
+                    TupleItem::TPrimitive(TPrimitive::TGeneric(TGeneric::param("A"))),
+                    TupleItem::TPrimitive(TPrimitive::TGeneric(TGeneric::param("B"))),
+                ],
+            }),
+        )
+        .generic_params(vec![TGeneric::param("A"), TGeneric::param("B")]),
+    );
+    declarations.add(TypeDeclaration::new(
+        "Half",
+        DeclarationValue::TPrimitive(pair.primitive()),
+    ));
+    k9::snapshot!(
+        codegen_error(declarations),
+        "`Pair` expects 2 generic argument(s), found 0 in `Half`"
+    );
+
+    let mut declarations = Declarations::new();
+    declarations.add(
+        TypeDeclaration::new(
+            "Wrapper",
+            DeclarationValue::TVec(Box::new(TVec::TPrimitive(TPrimitive::TGeneric(
+                TGeneric::param("U"),
+            )))),
+        )
+        .generic_params(vec![TGeneric::param("T")]),
+    );
+    k9::snapshot!(
+        codegen_error(declarations),
+        "unknown generic param `U` in `Wrapper`"
+    );
+}
@@ -4,229 +4,192 @@ use anyhow::Result;
 fn make_declarations() -> Declarations {
     let mut c = Declarations::new();
 
-    c.add(TypeDeclaration {
-        name: "",
-        docs: "",
-        config: vec![],
-        generic_params: vec![],
-        value: DeclarationValue::CodeBlock(CodeBlock::Flow(vec![
+    c.add(TypeDeclaration::new(
+        "",
+        DeclarationValue::CodeBlock(CodeBlock::flow(vec![
             r#"/*
  * @flow
  * @some stuff
  */"#,
         ])),
-    });
+    ));
 
     let skip_serializing_none =
-        StructFieldConfig::RustAttribute(r#"#[serde(skip_serializing_if = "Option::is_none")]"#);
+        StructFieldConfig::rust_attribute(r#"#[serde(skip_serializing_if = "Option::is_none")]"#);
 
-    let struct_derives = TypeDeclarationConfig::RustAttribute("#[derive(Default, Clone)]");
+    let struct_derives = TypeDeclarationConfig::rust_attribute("#[derive(Default, Clone)]");
 
-    let struct_derives_eq_ord = TypeDeclarationConfig::RustAttribute(
+    let struct_derives_eq_ord = TypeDeclarationConfig::rust_attribute(
         "#[derive(Default, Clone, Ord, PartialOrd, Eq, PartialEq)]",
     );
 
-    c.add_config(DeclarationsConfig::HackNamespace("NS"));
+    c.add_config(DeclarationsConfig::hack_namespace("NS"));
 
-    c.add(TypeDeclaration {
-        name: "",
-        docs: "",
-        config: vec![],
-        generic_params: vec![],
-        value: DeclarationValue::CodeBlock(CodeBlock::Rust(vec![
+    c.add(TypeDeclaration::new(
+        "",
+        DeclarationValue::CodeBlock(CodeBlock::rust(vec![
             "use chrono::{DateTime, Utc};",
             "use crate::types::{IndexedStr as _IndexedStr, ID as _ID};",
         ])),
-    });
-
-    c.add(TypeDeclaration {
-        name: "ID",
-        docs: "",
-        config: vec![],
-        generic_params: vec![],
-        value: DeclarationValue::TPrimitive(TPrimitive::TDifferentPerLanguage {
-            rust: Box::new(TPrimitive::THardcoded("_ID")),
+    ));
+
+    c.add(TypeDeclaration::new(
+        "ID",
+        DeclarationValue::TPrimitive(TPrimitive::TDifferentPerLanguage {
+            rust: Box::new(TPrimitive::hardcoded("_ID")),
             hack: Box::new(TPrimitive::Ti64),
             flow: Box::new(TPrimitive::Ti64),
         }),
-    });
+    ));
 
-    c.add(TypeDeclaration {
-        name: "",
-        docs: "
+    c.add(TypeDeclaration::new("", DeclarationValue::Docs).docs(
+        "
         ==========================================================================
         Simple file defining various graph data types
         ==========================================================================",
-        config: vec![],
-        generic_params: vec![],
-        value: DeclarationValue::Docs,
-    });
-
-    c.add(TypeDeclaration {
-        name: "NodeID",
-        docs: "",
-        config: vec![],
-        generic_params: vec![],
-        value: DeclarationValue::TPrimitive(TPrimitive::Ti64),
-    });
-
-    c.add(TypeDeclaration {
-        name: "NodeName",
-        docs: "",
-        config: vec![],
-        generic_params: vec![],
-        value: DeclarationValue::TPrimitive(TPrimitive::String),
-    });
-
-    let t_generic = TGeneric::TDefinition {
-        name: "T",
-        bounds: None,
-    };
-
-    let ts_generic = TGeneric::TDefinition {
-        name: "TS",
-        bounds: Some("Ord"),
-    };
-
-    let tn_generic = TGeneric::TDefinition {
-        name: "TN",
-        bounds: Some("Ord"),
-    };
-
-    let dynamic_edge = c.add(TypeDeclaration {
-        name: "DynamicEdge",
-        docs: "",
-        config: vec![struct_derives_eq_ord],
-        generic_params: vec![ts_generic.clone(), tn_generic.clone()],
-        value: DeclarationValue::TStruct(TStruct {
-            fields: vec![
-                StructField {
-                    name: "branches",
-                    docs: "",
-                    field_type: StructFieldType::TMap(TMap {
-                        key: TPrimitive::TGeneric(ts_generic.clone()),
-                        value: TMapValue::TSet(TSet::TPrimitive(TPrimitive::TGeneric(
+    ));
+
+    c.add(TypeDeclaration::new(
+        "NodeID",
+        DeclarationValue::TPrimitive(TPrimitive::Ti64),
+    ));
+
+    c.add(TypeDeclaration::new(
+        "NodeName",
+        DeclarationValue::TPrimitive(TPrimitive::String),
+    ));
+
+    let t_generic = TGeneric::param("T");
+
+    let ts_generic = TGeneric::bounded("TS", "Ord");
+
+    let tn_generic = TGeneric::bounded("TN", "Ord");
+
+    let mut dynamic_edge = c.add(
+        TypeDeclaration::new(
+            "DynamicEdge",
+            DeclarationValue::TStruct(TStruct {
+                fields: vec![
+                    StructField::new(
+                        "branches",
+                        StructFieldType::TMap(TMap {
+                            key: TPrimitive::TGeneric(ts_generic.clone()),
+                            value: TMapValue::TSet(TSet::TPrimitive(TPrimitive::TGeneric(
+                                tn_generic.clone(),
+                            ))),
+                            t: TMapType::BTree,
+                        }),
+                    ),
+                    StructField::new(
+                        "properties",
+                        StructFieldType::TOption(TOption::TMap(TMap {
+                            key: TPrimitive::TGeneric(ts_generic.clone()),
+                            value: TMapValue::TSet(TSet::TPrimitive(TPrimitive::TGeneric(
+                                ts_generic.clone(),
+                            ))),
+                            t: TMapType::BTree,
+                        })),
+                    ),
+                ],
+            }),
+        )
+        .config(struct_derives_eq_ord)
+        .generic_params(vec![ts_generic.clone(), tn_generic.clone()]),
+    );
+
+    dynamic_edge.generic_params = vec![ts_generic.clone(), tn_generic.clone()];
+
+    let mut node_edges = c.add(
+        TypeDeclaration::new(
+            "NodeEdges",
+            DeclarationValue::TStruct(TStruct {
+                fields: vec![
+                    StructField::new(
+                        "directed",
+                        StructFieldType::TSet(TSet::TPrimitive(TPrimitive::TGeneric(
                             tn_generic.clone(),
                         ))),
-                        t: TMapType::BTree,
-                    }),
-                    config: vec![],
-                },
-                StructField {
-                    name: "properties",
-                    docs: "",
-                    field_type: StructFieldType::TOption(TOption::TMap(TMap {
-                        key: TPrimitive::TGeneric(ts_generic.clone()),
-                        value: TMapValue::TSet(TSet::TPrimitive(TPrimitive::TGeneric(
-                            ts_generic.clone(),
+                    ),
+                    StructField::new(
+                        "dynamic",
+                        StructFieldType::TPrimitive(TPrimitive::TReference(dynamic_edge)),
+                    ),
+                    StructField::new(
+                        "tagged",
+                        StructFieldType::TOption(TOption::TMap(TMap {
+                            key: TPrimitive::TGeneric(ts_generic.clone()),
+                            value: TMapValue::TSet(TSet::TPrimitive(TPrimitive::TGeneric(
+                                tn_generic.clone(),
+                            ))),
+                            t: TMapType::BTree,
+                        })),
+                    ),
+                ],
+            }),
+        )
+        .config(struct_derives.clone())
+        .generic_params(vec![ts_generic.clone(), tn_generic.clone()]),
+    );
+
+    node_edges.generic_params = vec![t_generic.clone(), t_generic.clone()];
+
+    let mut node = c.add(
+        TypeDeclaration::new(
+            "GraphNode",
+            DeclarationValue::TStruct(TStruct {
+                fields: vec![
+                    StructField::new(
+                        "name",
+                        StructFieldType::TPrimitive(TPrimitive::TGeneric(t_generic.clone())),
+                    ),
+                    StructField::new(
+                        "edges",
+                        StructFieldType::TOption(TOption::TPrimitive(TPrimitive::TReference(
+                            node_edges,
                         ))),
-                        t: TMapType::BTree,
-                    })),
-                    config: vec![],
-                },
-            ],
-        }),
-    });
-
-    let node_edges = c.add(TypeDeclaration {
-        name: "NodeEdges",
-        docs: "",
-        config: vec![struct_derives],
-        generic_params: vec![ts_generic.clone(), tn_generic.clone()],
-        value: DeclarationValue::TStruct(TStruct {
-            fields: vec![
-                StructField {
-                    name: "directed",
-                    docs: "",
-                    field_type: StructFieldType::TSet(TSet::TPrimitive(TPrimitive::TGeneric(
-                        tn_generic.clone(),
-                    ))),
-                    config: vec![],
-                },
-                StructField {
-                    name: "dynamic",
-                    docs: "",
-                    field_type: StructFieldType::TPrimitive(TPrimitive::TReference(dynamic_edge)),
-                    config: vec![],
-                },
-                StructField {
-                    name: "tagged",
-                    docs: "",
-                    field_type: StructFieldType::TOption(TOption::TMap(TMap {
-                        key: TPrimitive::TGeneric(ts_generic),
-                        value: TMapValue::TSet(TSet::TPrimitive(TPrimitive::TGeneric(tn_generic))),
-                        t: TMapType::BTree,
-                    })),
-                    config: vec![],
-                },
-            ],
-        }),
-    });
-
-    let node = c.add(TypeDeclaration {
-        name: "GraphNode",
-        docs: "",
-        config: vec![struct_derives],
-        generic_params: vec![t_generic.clone()],
-        value: DeclarationValue::TStruct(TStruct {
-            fields: vec![
-                StructField {
-                    name: "name",
-                    docs: "",
-                    field_type: StructFieldType::TPrimitive(TPrimitive::TGeneric(
-                        t_generic.clone(),
-                    )),
-                    config: vec![],
-                },
-                StructField {
-                    name: "edges",
-                    docs: "",
-                    field_type: StructFieldType::TOption(TOption::TPrimitive(
-                        TPrimitive::TReference(node_edges),
-                    )),
-                    config: vec![skip_serializing_none],
-                },
-            ],
-        }),
-    });
-
-    let graph = c.add(TypeDeclaration {
-        name: "Graph",
-        docs: "",
-        config: vec![struct_derives],
-        generic_params: vec![t_generic.clone()],
-        value: DeclarationValue::TStruct(TStruct {
-            fields: vec![
-                StructField {
-                    name: "nodes",
-                    docs: "",
-                    field_type: StructFieldType::TMap(TMap {
-                        key: TPrimitive::TGeneric(t_generic.clone()),
-                        value: TMapValue::TPrimitive(TPrimitive::TReference(node)),
-                        t: TMapType::Hash,
-                    }),
-                    config: vec![],
-                },
-                StructField {
-                    name: "timestamp",
-                    docs: "",
-                    field_type: StructFieldType::TPrimitive(TPrimitive::String),
-                    config: vec![StructFieldConfig::RustOverride("DateTime<Utc>")],
-                },
-            ],
-        }),
-    });
+                    )
+                    .config(skip_serializing_none),
+                ],
+            }),
+        )
+        .config(struct_derives.clone())
+        .generic_params(vec![t_generic.clone()]),
+    );
+
+    node.generic_params = vec![t_generic.clone()];
+
+    let graph = c.add(
+        TypeDeclaration::new(
+            "Graph",
+            DeclarationValue::TStruct(TStruct {
+                fields: vec![
+                    StructField::new(
+                        "nodes",
+                        StructFieldType::TMap(TMap {
+                            key: TPrimitive::TGeneric(t_generic.clone()),
+                            value: TMapValue::TPrimitive(TPrimitive::TReference(node)),
+                            t: TMapType::Hash,
+                        }),
+                    ),
+                    StructField::new("timestamp", StructFieldType::TPrimitive(TPrimitive::String))
+                        .config(StructFieldConfig::rust_override("DateTime<Utc>")),
+                ],
+            }),
+        )
+        .config(struct_derives)
+        .generic_params(vec![t_generic.clone()]),
+    );
 
     let mut generic_graph = graph;
     generic_graph.generic_params = vec![t_generic.clone()];
 
-    c.add(TypeDeclaration {
-        name: "GraphProxyType",
-        docs: "",
-        config: vec![],
-        generic_params: vec![t_generic],
-        value: DeclarationValue::TPrimitive(generic_graph.primitive()),
-    });
+    c.add(
+        TypeDeclaration::new(
+            "GraphProxyType",
+            DeclarationValue::TPrimitive(generic_graph.primitive()),
+        )
+        .generic_params(vec![t_generic]),
+    );
 
     c
 }
@@ -267,7 +230,7 @@ pub struct DynamicEdge<TS: Ord, TN: Ord> {
 #[derive(Default, Clone)]
 pub struct NodeEdges<TS: Ord, TN: Ord> {
     pub directed: BTreeSet<TN>,
-    pub dynamic: DynamicEdge,
+    pub dynamic: DynamicEdge<TS, TN>,
     pub tagged: Option<BTreeMap<TS, BTreeSet<TN>>>,
 }
 
@@ -275,12 +238,12 @@ pub struct NodeEdges<TS: Ord, TN: Ord> {
 pub struct GraphNode<T> {
     pub name: T,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub edges: Option<NodeEdges>,
+    pub edges: Option<NodeEdges<T, T>>,
 }
 
 #[derive(Default, Clone)]
 pub struct Graph<T> {
-    pub nodes: HashMap<T, GraphNode>,
+    pub nodes: HashMap<T, GraphNode<T>>,
     pub timestamp: DateTime<Utc>,
 }
 
@@ -322,17 +285,17 @@ type NSDynamicEdge<TS, TN> = shape(
 
 type NSNodeEdges<TS, TN> = shape(
     'directed' => keyset<TN>,
-    'dynamic' => NSDynamicEdge,
+    'dynamic' => NSDynamicEdge<TS, TN>,
     ?'tagged' => dict<TS, keyset<TN>>,
 );
 
 type NSGraphNode<T> = shape(
     'name' => T,
-    ?'edges' => NSNodeEdges,
+    ?'edges' => NSNodeEdges<T, T>,
 );
 
 type NSGraph<T> = shape(
-    'nodes' => dict<T, NSGraphNode>,
+    'nodes' => dict<T, NSGraphNode<T>>,
     'timestamp' => string,
 );
 
@@ -376,17 +339,17 @@ export type DynamicEdge<TS, TN> = {
 
 export type NodeEdges<TS, TN> = {
     'directed': Array<TN>,
-    'dynamic': DynamicEdge,
+    'dynamic': DynamicEdge<TS, TN>,
     'tagged'?: {[key: TS]: Array<TN>},
 };
 
 export type GraphNode<T> = {
     'name': T,
-    'edges'?: NodeEdges,
+    'edges'?: NodeEdges<T, T>,
 };
 
 export type Graph<T> = {
-    'nodes': {[key: T]: GraphNode},
+    'nodes': {[key: T]: GraphNode<T>},
     'timestamp': string,
 };
 
@@ -0,0 +1,190 @@
+use crate::prelude::*;
+use anyhow::Result;
+
+const SCHEMA: &str = r#"
+hack_namespace "H";
+
+@hack_enum("enum_class")
+enum Color { Red, Green }
+
+@hack_enum("constants")
+enum Size { Small, Large }
+
+@hack_enum("enum")
+enum Shape { Circle, Square }
+
+struct Item {
+    color: Color,
+    size: option<Size>,
+    shape: Shape,
+    counts: map<Color, i64>,
+}
+
+@hack_enum("constants")
+enum Op {
+    Fetch(Item),
+    Paint { color: Color },
+    Clear(option<i64>),
+}
+"#;
+
+#[test]
+fn hack_test() -> Result<()> {
+    k9::snapshot!(
+        parse_schema(SCHEMA)?.codegen_hack()?,
+        r#"
+<?hh
+
+enum class HColor: string {
+    string RED = "Red";
+    string GREEN = "Green";
+}
+
+abstract final class HSize {
+    const string SMALL = "Small";
+    const string LARGE = "Large";
+}
+
+enum HShape: string as string {
+    CIRCLE = "Circle";
+    SQUARE = "Square";
+}
+
+type HItem = shape(
+    'color' => string,
+    ?'size' => string,
+    'shape' => HShape,
+    'counts' => dict<string, int>,
+);
+
+
+abstract final class HOpType {
+    const string FETCH = "Fetch";
+    const string PAINT = "Paint";
+    const string CLEAR = "Clear";
+}
+
+type HOp = shape(
+    ?'Fetch' => HItem,
+    ?'Paint' =>  shape(
+        'color' => string,
+    ),
+    ?'Clear' => ?int,
+);
+
+function HOp_getType(HOp $x): string {
+    if (Shapes::keyExists($x, 'Fetch')) {
+        return HOpType::FETCH;
+    }
+    if (Shapes::keyExists($x, 'Paint')) {
+        return HOpType::PAINT;
+    }
+    if (Shapes::keyExists($x, 'Clear')) {
+        return HOpType::CLEAR;
+    }
+    invariant_violation('No variant of HOp is set');
+}
+
+function HOp_getFetch(HOp $x): ?HItem {
+    return Shapes::idx($x, 'Fetch');
+}
+
+function HOp_getPaint(HOp $x): ?shape(
+    'color' => string,
+) {
+    return Shapes::idx($x, 'Paint');
+}
+
+function HOp_getClear(HOp $x): ?int {
+    return Shapes::idx($x, 'Clear');
+}
+
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn validators_test() -> Result<()> {
+    let source = format!("validators;\n{}", SCHEMA);
+    let hack = parse_schema(&source)?.codegen_hack()?;
+    let validators = hack
+        .split("\n\n")
+        .filter(|block| block.starts_with("function validate"))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    k9::snapshot!(
+        validators,
+        r#"
+function validateHColor(mixed $x, string $path = '$'): string {
+    HGull::oneOf(keyset['Red', 'Green'])($x, $path);
+    /* HH_FIXME[4110] checked above */
+    return $x;
+}
+
+function validateHSize(mixed $x, string $path = '$'): string {
+    HGull::oneOf(keyset['Small', 'Large'])($x, $path);
+    /* HH_FIXME[4110] checked above */
+    return $x;
+}
+
+function validateHShape(mixed $x, string $path = '$'): HShape {
+    HGull::oneOf(keyset['Circle', 'Square'])($x, $path);
+    /* HH_FIXME[4110] checked above */
+    return $x;
+}
+
+function validateHItem(mixed $x, string $path = '$'): HItem {
+    HGull::shapeOf(dict[
+        'color' => ($x, $path) ==> { validateHColor($x, $path); },
+        'size' => HGull::optionOf(($x, $path) ==> { validateHSize($x, $path); }),
+        'shape' => ($x, $path) ==> { validateHShape($x, $path); },
+        'counts' => HGull::dictOf(HGull::isInt(), ($x, $path) ==> { validateHColor($x, $path); }),
+    ])($x, $path);
+    /* HH_FIXME[4110] checked above */
+    return $x;
+}
+
+function validateHOp(mixed $x, string $path = '$'): HOp {
+    HGull::enumOf(dict[
+        'Fetch' => ($x, $path) ==> { validateHItem($x, $path); },
+        'Paint' => HGull::shapeOf(dict[
+            'color' => ($x, $path) ==> { validateHColor($x, $path); },
+        ]),
+        'Clear' => HGull::optionOf(HGull::isInt()),
+    ])($x, $path);
+    /* HH_FIXME[4110] checked above */
+    return $x;
+}
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn errors_test() {
+    let error = |source: &str| parse_schema(source).unwrap_err().to_string();
+
+    k9::snapshot!(
+        error("@hack_enum(\"set\") enum A { B, C }"),
+        "1:1: unknown Hack enum style `set`, expected `enum`, `enum_class` or `constants`"
+    );
+    k9::snapshot!(
+        error("@hack_enum(\"enum_class\") struct A { b: i64 }"),
+        "1:1: `@hack_enum` can only be used on enums, `A` isn't one"
+    );
+    k9::snapshot!(
+        error("@hack_enum enum A { B, C }"),
+        "1:1: `@hack_enum` requires a value: `@hack_enum(\"...\")`"
+    );
+
+    k9::snapshot!(
+        parse_schema("enum E { Type(i64), A(bool) }")
+            .unwrap()
+            .codegen_hack()
+            .unwrap_err()
+            .to_string(),
+        "variant `Type` of `E` clashes with the generated `E_getType()` in Hack"
+    );
+}
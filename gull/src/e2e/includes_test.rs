@@ -0,0 +1,243 @@
+use crate::prelude::*;
+use anyhow::Result;
+
+const SCHEMA: &str = r#"
+hack_namespace "I";
+
+/// Shared by every stored object
+struct Metadata {
+    created_at: i64,
+    /// Who made the last change
+    updated_by?: string,
+}
+
+struct Page<T> {
+    items: vec<T>,
+    cursor?: string,
+}
+
+/// A node of the tree
+struct Node includes Metadata {
+    id: i64,
+    children: vec<Node>,
+}
+
+struct NodePage includes Page<Node>, Metadata {
+    total: i64,
+}
+"#;
+
+#[test]
+fn rust_test() -> Result<()> {
+    k9::snapshot!(
+        parse_schema(SCHEMA)?.codegen_rust()?,
+        r#"
+
+
+/// Shared by every stored object
+pub struct Metadata {
+    pub created_at: i64,
+    /// Who made the last change
+    pub updated_by: Option<String>,
+}
+
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub cursor: Option<String>,
+}
+
+/// A node of the tree
+pub struct Node {
+    pub created_at: i64,
+    /// Who made the last change
+    pub updated_by: Option<String>,
+    pub id: i64,
+    pub children: Vec<Node>,
+}
+
+pub struct NodePage {
+    pub items: Vec<Node>,
+    pub cursor: Option<String>,
+    pub created_at: i64,
+    /// Who made the last change
+    pub updated_by: Option<String>,
+    pub total: i64,
+}
+
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn hack_test() -> Result<()> {
+    k9::snapshot!(
+        parse_schema(SCHEMA)?.codegen_hack()?,
+        r#"
+<?hh
+
+// Shared by every stored object
+type IMetadata = shape(
+    'created_at' => int,
+    // Who made the last change
+    ?'updated_by' => string,
+);
+
+type IPage<T> = shape(
+    'items' => vec<T>,
+    ?'cursor' => string,
+);
+
+// A node of the tree
+type INode = shape(
+    'created_at' => int,
+    // Who made the last change
+    ?'updated_by' => string,
+    'id' => int,
+    'children' => vec<INode>,
+);
+
+type INodePage = shape(
+    'items' => vec<INode>,
+    ?'cursor' => string,
+    'created_at' => int,
+    // Who made the last change
+    ?'updated_by' => string,
+    'total' => int,
+);
+
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn flow_test() -> Result<()> {
+    k9::snapshot!(
+        parse_schema(SCHEMA)?.codegen_flow()?,
+        r#"
+
+// Shared by every stored object
+export type Metadata = {
+    'created_at': number,
+    // Who made the last change
+    'updated_by'?: string,
+};
+
+export type Page<T> = {
+    'items': Array<T>,
+    'cursor'?: string,
+};
+
+// A node of the tree
+export type Node = {
+    ...Metadata,
+    'id': number,
+    'children': Array<Node>,
+};
+
+export type NodePage = {
+    ...Page<Node>,
+    ...Metadata,
+    'total': number,
+};
+
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn validators_test() -> Result<()> {
+    let declarations = parse_schema(&format!("validators;\n{}", SCHEMA))?;
+    let flow = declarations.codegen_flow()?;
+    let flow = flow
+        .lines()
+        .skip_while(|l| !l.starts_with("export function validateNodePage"))
+        .take_while(|l| *l != "}")
+        .collect::<Vec<_>>()
+        .join("\n");
+    k9::snapshot!(
+        flow,
+        r#"
+export function validateNodePage(x: mixed, path: string = '$'): NodePage {
+    gullStructOf({
+        'items': gullArrayOf(validateNode),
+        'cursor': gullOptionOf(gullString),
+        'created_at': gullInt,
+        'updated_by': gullOptionOf(gullString),
+        'total': gullInt,
+    })(x, path);
+    return (x: any);
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn fixture_test() -> Result<()> {
+    let declarations = parse_schema(SCHEMA)?;
+    k9::snapshot!(
+        serde_json::to_string(&declarations.fixture("Node", FixtureKind::Minimal)?)?,
+        r#"{"created_at":0,"id":0,"children":[]}"#
+    );
+    Ok(())
+}
+
+#[test]
+fn compat_test() -> Result<()> {
+    let old = parse_schema("struct A { x: i64, y?: string } struct B { a: i64 }")?;
+    let new = parse_schema("struct A includes B { x: i64 } struct B { a: i64 }")?;
+    k9::snapshot!(
+        check_compatibility(&old, &new).to_string(),
+        r#"
+[COMPATIBLE] A.y: field was removed
+[FORWARD] A.a: required field was added
+Overall: forward compatible
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn errors_test() {
+    let error = |source: &str| match parse_schema(source) {
+        Ok(declarations) => declarations.codegen_rust().unwrap_err().to_string(),
+        Err(e) => e.to_string(),
+    };
+
+    k9::snapshot!(
+        error("struct A includes option<B> {} struct B {}"),
+        "1:19: `A` can only include other structs"
+    );
+    k9::snapshot!(
+        error("struct A includes B {} enum B { C, D }"),
+        "`A` includes `B`, which isn't a declared struct"
+    );
+    k9::snapshot!(
+        error("struct A includes B { c: i64 } struct B { c: string }"),
+        "field `c` is defined more than once in `A` and the structs it includes"
+    );
+    k9::snapshot!(
+        error("struct A includes B {} struct B includes A {}"),
+        "`A` includes itself"
+    );
+    k9::snapshot!(
+        error("struct A includes B {} struct B includes B {}"),
+        "`A` includes `B`, which includes itself"
+    );
+    k9::snapshot!(error("struct A includes C {}"), "1:19: unknown type `C`");
+}
+
+#[test]
+fn deep_test() -> Result<()> {
+    // A chain of 40 structs that each include the next one
+    let schema = (0..40)
+        .map(|i| format!("struct S{} includes S{} {{ f{}: i64 }}\n", i, i + 1, i))
+        .chain(std::iter::once("struct S40 { f40: i64 }".to_string()))
+        .collect::<String>();
+    let rust = parse_schema(&schema)?.codegen_rust()?;
+    // Every struct ends up with the field of the last struct
+    k9::snapshot!(rust.matches("pub f40: i64").count(), "41");
+    Ok(())
+}
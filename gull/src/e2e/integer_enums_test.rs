@@ -0,0 +1,247 @@
+use crate::prelude::*;
+use anyhow::Result;
+
+const SCHEMA: &str = r#"
+hack_namespace "I";
+validators;
+
+/// How urgent a request is
+enum Priority {
+    Low = -1,
+    /// The default
+    Normal = 0,
+    @deprecated("Use `Normal`")
+    High = 10,
+}
+
+@hack_enum("constants")
+enum Level { Debug = 1, Info = 2 }
+
+struct Request {
+    priority: Priority,
+    level?: Level,
+    by_level: map<Level, i64>,
+}
+"#;
+
+#[test]
+fn rust_test() -> Result<()> {
+    k9::snapshot!(
+        parse_schema(SCHEMA)?.codegen_rust()?,
+        r#"
+use std::collections::BTreeMap;
+
+
+/// How urgent a request is
+#[derive(serde_repr::Serialize_repr, serde_repr::Deserialize_repr)]
+#[repr(i32)]
+pub enum Priority {
+    Low = -1,
+    /// The default
+    Normal = 0,
+    #[deprecated(note = "Use `Normal`")]
+    High = 10,
+}
+
+#[derive(serde_repr::Serialize_repr, serde_repr::Deserialize_repr)]
+#[repr(i32)]
+pub enum Level {
+    Debug = 1,
+    Info = 2,
+}
+
+pub struct Request {
+    pub priority: Priority,
+    pub level: Option<Level>,
+    pub by_level: BTreeMap<Level, i64>,
+}
+
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn rust_impls_test() -> Result<()> {
+    k9::snapshot!(
+        parse_schema("rust_impls; enum Level { Debug = 1, Info = 2 }")?.codegen_rust()?,
+        r#"
+
+
+#[derive(serde_repr::Serialize_repr, serde_repr::Deserialize_repr)]
+#[repr(i32)]
+pub enum Level {
+    Debug = 1,
+    Info = 2,
+}
+
+impl std::fmt::Display for Level {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Debug => "1",
+            Self::Info => "2",
+        })
+    }
+}
+
+impl std::str::FromStr for Level {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "1" => Ok(Self::Debug),
+            "2" => Ok(Self::Info),
+            _ => Err(format!("unknown Level `{}`", s)),
+        }
+    }
+}
+
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn hack_test() -> Result<()> {
+    // The validator helpers are covered by `validators_test`
+    let hack = parse_schema(SCHEMA)?.codegen_hack()?;
+    k9::snapshot!(
+        hack.split("type IGullCheck").next().unwrap_or_default(),
+        r#"
+<?hh
+
+// How urgent a request is
+enum IPriority: int as int {
+    LOW = -1;
+    // The default
+    NORMAL = 0;
+    // @deprecated Use `Normal`
+    HIGH = 10;
+}
+
+function validateIPriority(mixed $x, string $path = '$'): IPriority {
+    IGull::oneOf(keyset[-1, 0, 10])($x, $path);
+    /* HH_FIXME[4110] checked above */
+    return $x;
+}
+
+abstract final class ILevel {
+    const int DEBUG = 1;
+    const int INFO = 2;
+}
+
+function validateILevel(mixed $x, string $path = '$'): int {
+    IGull::oneOf(keyset[1, 2])($x, $path);
+    /* HH_FIXME[4110] checked above */
+    return $x;
+}
+
+type IRequest = shape(
+    'priority' => IPriority,
+    ?'level' => int,
+    'by_level' => dict<int, int>,
+);
+
+function validateIRequest(mixed $x, string $path = '$'): IRequest {
+    IGull::shapeOf(dict[
+        'priority' => ($x, $path) ==> { validateIPriority($x, $path); },
+        'level' => IGull::optionOf(($x, $path) ==> { validateILevel($x, $path); }),
+        'by_level' => IGull::dictOf(IGull::isInt(), ($x, $path) ==> { validateILevel($x, $path); }),
+    ])($x, $path);
+    /* HH_FIXME[4110] checked above */
+    return $x;
+}
+
+
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn flow_test() -> Result<()> {
+    let flow = parse_schema(SCHEMA)?.codegen_flow()?;
+    k9::snapshot!(
+        flow.split("type GullCheck").next().unwrap_or_default(),
+        r#"
+
+// How urgent a request is
+export type Priority =
+    | -1
+    // The default
+    | 0
+    /** @deprecated Use `Normal` */
+    | 10;
+
+export const PriorityEnum: {|+Low: -1, +Normal: 0, +High: 10|} = Object.freeze({
+    Low: -1,
+    // The default
+    Normal: 0,
+    /** @deprecated Use `Normal` */
+    High: 10,
+});
+
+export function validatePriority(x: mixed, path: string = '$'): Priority {
+    gullOneOf([-1, 0, 10])(x, path);
+    return (x: any);
+}
+
+export type Level = 1 | 2;
+
+export const LevelEnum: {|+Debug: 1, +Info: 2|} = Object.freeze({Debug: 1, Info: 2});
+
+export function validateLevel(x: mixed, path: string = '$'): Level {
+    gullOneOf([1, 2])(x, path);
+    return (x: any);
+}
+
+export type Request = {
+    'priority': Priority,
+    'level'?: Level,
+    'by_level': {'1'?: number, '2'?: number},
+};
+
+export function validateRequest(x: mixed, path: string = '$'): Request {
+    gullStructOf({
+        'priority': validatePriority,
+        'level': gullOptionOf(validateLevel),
+        'by_level': gullMapOf(gullInt, gullOneOf(["1", "2"])),
+    })(x, path);
+    return (x: any);
+}
+
+
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn errors_test() {
+    let error = |source: &str| parse_schema(source).unwrap_err().to_string();
+
+    k9::snapshot!(
+        error("enum A { B = 1, C }"),
+        "1:1: either every variant of simple enum `A` needs a discriminant or none"
+    );
+    k9::snapshot!(
+        error("enum A { B = 1.5 }"),
+        "1:14: expected integer, found `1.5`"
+    );
+    k9::snapshot!(
+        error("enum A { B = 1, C = 1 }"),
+        "1:17: discriminant 1 is used by more than one variant of `A`"
+    );
+    k9::snapshot!(
+        error("enum A { @value(\"b\") B = 1 }"),
+        "1:22: variant `B` can't have both a discriminant and a `@value`"
+    );
+    k9::snapshot!(
+        error("enum A { B = 2147483648 }"),
+        "1:14: `2147483648` doesn't fit into an i32"
+    );
+    k9::snapshot!(
+        error("enum A { B = x }"),
+        "1:14: expected integer, found `x`"
+    );
+}
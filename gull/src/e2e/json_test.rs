@@ -0,0 +1,464 @@
+use super::schema_test::SCHEMA;
+use crate::prelude::*;
+use anyhow::Result;
+
+#[test]
+fn json_test() -> Result<()> {
+    let mut declarations = Declarations::new();
+    declarations.add_config(DeclarationsConfig::hack_namespace("NS"));
+
+    let id = declarations.add(TypeDeclaration::new(
+        "ID",
+        DeclarationValue::TPrimitive(TPrimitive::Ti64),
+    ));
+
+    declarations.add(
+        TypeDeclaration::new(
+            "Node",
+            TStruct {
+                fields: vec![StructField::new(
+                    "parent",
+                    StructFieldType::TOption(TOption::TPrimitive(id.primitive())),
+                )],
+            },
+        )
+        .docs("A node in the graph")
+        .config(TypeDeclarationConfig::rust_attribute("#[derive(Clone)]")),
+    );
+
+    k9::snapshot!(
+        declarations.to_json()?,
+        r##"
+{
+  "version": 1,
+  "config": [
+    {
+      "kind": "HackNamespace",
+      "value": "NS"
+    }
+  ],
+  "declarations": [
+    {
+      "name": "ID",
+      "docs": "",
+      "value": {
+        "kind": "TPrimitive",
+        "value": {
+          "kind": "Ti64"
+        }
+      },
+      "config": [],
+      "generic_params": []
+    },
+    {
+      "name": "Node",
+      "docs": "A node in the graph",
+      "value": {
+        "kind": "TStruct",
+        "value": {
+          "fields": [
+            {
+              "name": "parent",
+              "docs": "",
+              "field_type": {
+                "kind": "TOption",
+                "value": {
+                  "kind": "TPrimitive",
+                  "value": {
+                    "kind": "TReference",
+                    "value": {
+                      "name": "ID",
+                      "generic_params": []
+                    }
+                  }
+                }
+              },
+              "config": []
+            }
+          ]
+        }
+      },
+      "config": [
+        {
+          "kind": "RustAttribute",
+          "value": "#[derive(Clone)]"
+        }
+      ],
+      "generic_params": []
+    }
+  ]
+}
+"##
+    );
+    Ok(())
+}
+
+#[test]
+fn round_trip_test() -> Result<()> {
+    let declarations = parse_schema(SCHEMA)?;
+    let json = declarations.to_json()?;
+    let loaded = Declarations::from_json(&json)?;
+
+    assert_eq!(loaded.to_json()?, json);
+    assert_eq!(loaded.codegen_rust()?, declarations.codegen_rust()?);
+    assert_eq!(loaded.codegen_hack()?, declarations.codegen_hack()?);
+    assert_eq!(loaded.codegen_flow()?, declarations.codegen_flow()?);
+    Ok(())
+}
+
+#[test]
+fn version_test() {
+    let error = |json: &str| format!("{:#}", Declarations::from_json(json).unwrap_err());
+
+    k9::snapshot!(
+        error(r#"{"version": 2, "something": "else"}"#),
+        "Unsupported declarations JSON version `2`. Expected version `1`"
+    );
+    k9::snapshot!(
+        error(r#"{"config": [], "declarations": []}"#),
+        "Declarations JSON must have a `version` field: missing field `version` at line 1 column 34"
+    );
+}
+
+// Written from `SCHEMA` when version 1 was introduced. Later IR changes must
+// keep loading it, see `DECLARATIONS_JSON_VERSION`
+const V1_JSON: &str = r##"
+{
+  "version": 1,
+  "config": [
+    {
+      "kind": "HackNamespace",
+      "value": "Schema"
+    }
+  ],
+  "declarations": [
+    {
+      "name": "",
+      "docs": "==========================================\nTypes loaded from a schema file\n==========================================",
+      "value": {
+        "kind": "Docs"
+      },
+      "config": [],
+      "generic_params": []
+    },
+    {
+      "name": "",
+      "docs": "",
+      "value": {
+        "kind": "CodeBlock",
+        "value": {
+          "kind": "Rust",
+          "value": [
+            "use crate::types::ID as _ID;"
+          ]
+        }
+      },
+      "config": [],
+      "generic_params": []
+    },
+    {
+      "name": "ID",
+      "docs": "",
+      "value": {
+        "kind": "TPrimitive",
+        "value": {
+          "kind": "TDifferentPerLanguage",
+          "value": {
+            "hack": {
+              "kind": "Ti64"
+            },
+            "rust": {
+              "kind": "THardcoded",
+              "value": "_ID"
+            },
+            "flow": {
+              "kind": "Ti64"
+            }
+          }
+        }
+      },
+      "config": [],
+      "generic_params": []
+    },
+    {
+      "name": "Frame",
+      "docs": "Frame represents a tuple of an Timestamp (RFC3339) and an ID",
+      "value": {
+        "kind": "TTuple",
+        "value": {
+          "items": [
+            {
+              "kind": "TPrimitive",
+              "value": {
+                "kind": "String"
+              }
+            },
+            {
+              "kind": "TPrimitive",
+              "value": {
+                "kind": "TReference",
+                "value": {
+                  "name": "ID",
+                  "generic_params": []
+                }
+              }
+            }
+          ]
+        }
+      },
+      "config": [
+        {
+          "kind": "RustAttribute",
+          "value": "#[derive(Copy, Clone)]"
+        }
+      ],
+      "generic_params": []
+    },
+    {
+      "name": "StorageType",
+      "docs": "",
+      "value": {
+        "kind": "TSimpleEnum",
+        "value": {
+          "variants": [
+            "Full",
+            "Delta"
+          ]
+        }
+      },
+      "config": [],
+      "generic_params": []
+    },
+    {
+      "name": "Operation",
+      "docs": "Operation is a single unit of transormation logic",
+      "value": {
+        "kind": "TEnum",
+        "value": {
+          "variants": [
+            {
+              "name": "Fetch",
+              "docs": "Fetch items by their IDs",
+              "variant_type": {
+                "kind": "TStruct",
+                "value": {
+                  "fields": [
+                    {
+                      "name": "items",
+                      "docs": "",
+                      "field_type": {
+                        "kind": "TVec",
+                        "value": {
+                          "kind": "TPrimitive",
+                          "value": {
+                            "kind": "Ti64"
+                          }
+                        }
+                      },
+                      "config": []
+                    }
+                  ]
+                }
+              }
+            },
+            {
+              "name": "Store",
+              "docs": "",
+              "variant_type": {
+                "kind": "TPrimitive",
+                "value": {
+                  "kind": "TVec",
+                  "value": {
+                    "kind": "TPrimitive",
+                    "value": {
+                      "kind": "TReference",
+                      "value": {
+                        "name": "Frame",
+                        "generic_params": []
+                      }
+                    }
+                  }
+                }
+              }
+            },
+            {
+              "name": "Drop",
+              "docs": "",
+              "variant_type": {
+                "kind": "TPrimitive",
+                "value": {
+                  "kind": "Tbool"
+                }
+              }
+            }
+          ]
+        }
+      },
+      "config": [],
+      "generic_params": []
+    },
+    {
+      "name": "GraphNode",
+      "docs": "",
+      "value": {
+        "kind": "TStruct",
+        "value": {
+          "fields": [
+            {
+              "name": "name",
+              "docs": "Node name",
+              "field_type": {
+                "kind": "TPrimitive",
+                "value": {
+                  "kind": "TGeneric",
+                  "value": {
+                    "kind": "TDefinition",
+                    "value": {
+                      "name": "T",
+                      "bounds": null
+                    }
+                  }
+                }
+              },
+              "config": []
+            },
+            {
+              "name": "tags",
+              "docs": "",
+              "field_type": {
+                "kind": "TSet",
+                "value": {
+                  "kind": "TPrimitive",
+                  "value": {
+                    "kind": "TGeneric",
+                    "value": {
+                      "kind": "TDefinition",
+                      "value": {
+                        "name": "T",
+                        "bounds": null
+                      }
+                    }
+                  }
+                }
+              },
+              "config": []
+            },
+            {
+              "name": "edges",
+              "docs": "",
+              "field_type": {
+                "kind": "TMap",
+                "value": {
+                  "key": {
+                    "kind": "String"
+                  },
+                  "value": {
+                    "kind": "TSet",
+                    "value": {
+                      "kind": "TPrimitive",
+                      "value": {
+                        "kind": "TReference",
+                        "value": {
+                          "name": "ID",
+                          "generic_params": []
+                        }
+                      }
+                    }
+                  },
+                  "t": "Hash"
+                }
+              },
+              "config": []
+            },
+            {
+              "name": "parent",
+              "docs": "",
+              "field_type": {
+                "kind": "TOption",
+                "value": {
+                  "kind": "TPrimitive",
+                  "value": {
+                    "kind": "TReference",
+                    "value": {
+                      "name": "ID",
+                      "generic_params": []
+                    }
+                  }
+                }
+              },
+              "config": [
+                {
+                  "kind": "RustAttribute",
+                  "value": "#[serde(skip_serializing_if = \"Option::is_none\")]"
+                }
+              ]
+            },
+            {
+              "name": "timestamp",
+              "docs": "",
+              "field_type": {
+                "kind": "TPrimitive",
+                "value": {
+                  "kind": "String"
+                }
+              },
+              "config": [
+                {
+                  "kind": "RustOverride",
+                  "value": "DateTime<Utc>"
+                }
+              ]
+            }
+          ]
+        }
+      },
+      "config": [],
+      "generic_params": [
+        {
+          "kind": "TDefinition",
+          "value": {
+            "name": "T",
+            "bounds": "Ord + Clone"
+          }
+        }
+      ]
+    },
+    {
+      "name": "StringNode",
+      "docs": "",
+      "value": {
+        "kind": "TPrimitive",
+        "value": {
+          "kind": "TReference",
+          "value": {
+            "name": "GraphNode",
+            "generic_params": [
+              {
+                "kind": "TReference",
+                "value": {
+                  "name": "ID",
+                  "generic_params": []
+                }
+              }
+            ]
+          }
+        }
+      },
+      "config": [],
+      "generic_params": []
+    }
+  ]
+}
+"##;
+
+#[test]
+fn v1_test() -> Result<()> {
+    let loaded = Declarations::from_json(V1_JSON)?;
+    let declarations = parse_schema(SCHEMA)?;
+
+    assert_eq!(loaded.to_json()?, declarations.to_json()?);
+    assert_eq!(loaded.codegen_rust()?, declarations.codegen_rust()?);
+    assert_eq!(loaded.codegen_hack()?, declarations.codegen_hack()?);
+    assert_eq!(loaded.codegen_flow()?, declarations.codegen_flow()?);
+    Ok(())
+}
@@ -0,0 +1,157 @@
+use crate::prelude::*;
+use anyhow::Result;
+
+const SCHEMA: &str = r##"
+hack_namespace "K";
+
+type NodeID = i64;
+
+@rust("#[derive(PartialEq, Eq, PartialOrd, Ord, Hash)]")
+enum Color { Red, Green }
+
+type Shade = Color;
+
+struct Palette<K: Ord> {
+    names: map<string, string>,
+    nodes: map<NodeID, bool>,
+    counts: hash_map<u32, i64>,
+    colors: map<Color, vec<string>>,
+    shades?: map<Shade, set<i64>>,
+    custom: map<K, i64>,
+}
+
+type Default = Palette<string>;
+"##;
+
+#[test]
+fn flow_test() -> Result<()> {
+    k9::snapshot!(
+        parse_schema(SCHEMA)?.codegen_flow()?,
+        r#"
+
+export type NodeID = number;
+
+export type Color = "Red" | "Green";
+
+export enum ColorEnum {Red, Green};
+
+export type Shade = Color;
+
+export type Palette<K> = {
+    'names': {[key: string]: string},
+    'nodes': {[key: string]: boolean},
+    'counts': {[key: string]: number},
+    'colors': {'Red'?: Array<string>, 'Green'?: Array<string>},
+    'shades'?: {'Red'?: Array<number>, 'Green'?: Array<number>},
+    'custom': {[key: K]: number},
+};
+
+export type Default = Palette<string>;
+
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn hack_test() -> Result<()> {
+    k9::snapshot!(
+        parse_schema(SCHEMA)?.codegen_hack()?,
+        r#"
+<?hh
+
+type KNodeID = int;
+
+enum KColor: string as string {
+    RED = "Red";
+    GREEN = "Green";
+}
+
+type KShade = KColor;
+
+type KPalette<K> = shape(
+    'names' => dict<arraykey, string>,
+    'nodes' => dict<arraykey, bool>,
+    'counts' => dict<arraykey, int>,
+    'colors' => dict<KColor, vec<string>>,
+    ?'shades' => dict<KShade, keyset<int>>,
+    'custom' => dict<K, int>,
+);
+
+type KDefault = KPalette<string>;
+
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn validators_test() -> Result<()> {
+    let mut declarations = parse_schema(SCHEMA)?;
+    declarations.add_config(DeclarationsConfig::Validators);
+    let map_checks = |code: String| {
+        code.lines()
+            .map(|l| l.trim().to_string())
+            .filter(|l| {
+                l.starts_with('\'') && (l.contains("gullMapOf(") || l.contains("::dictOf("))
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    k9::snapshot!(
+        map_checks(declarations.codegen_flow()?),
+        r#"
+'names': gullMapOf(gullString),
+'nodes': gullMapOf(gullBoolean),
+'counts': gullMapOf(gullInt),
+'colors': gullMapOf(gullArrayOf(gullString), gullOneOf(["Red", "Green"])),
+'shades': gullOptionOf(gullMapOf(gullArrayOf(gullInt), gullOneOf(["Red", "Green"]))),
+'custom': gullMapOf(gullInt),
+"#
+    );
+    k9::snapshot!(
+        map_checks(declarations.codegen_hack()?),
+        r#"
+'names' => KGull::dictOf(KGull::isString()),
+'nodes' => KGull::dictOf(KGull::isBool()),
+'counts' => KGull::dictOf(KGull::isInt()),
+'colors' => KGull::dictOf(KGull::vecOf(KGull::isString()), ($x, $path) ==> { validateKColor($x, $path); }),
+'shades' => KGull::optionOf(KGull::dictOf(KGull::keysetOf(KGull::isInt()), ($x, $path) ==> { validateKShade($x, $path); })),
+'custom' => KGull::dictOf(KGull::isInt()),
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn errors_test() {
+    let error = |source: &str| {
+        parse_schema(source)
+            .unwrap()
+            .codegen_flow()
+            .unwrap_err()
+            .to_string()
+    };
+
+    k9::snapshot!(
+        error("type A = map<f64, string>;"),
+        "map keys must be strings, integers or simple enums, found `f64` in `A`"
+    );
+    k9::snapshot!(
+        error("struct A { b: option<map<bool, string>> }"),
+        "map keys must be strings, integers or simple enums, found `bool` in `A`"
+    );
+    k9::snapshot!(
+        error("struct A { b: i64 }\ntype B = map<A, string>;"),
+        "map keys must be strings, integers or simple enums, found struct `A` in `B`"
+    );
+    k9::snapshot!(
+        error("type A = vec<i64>;\ntype B = A;\ntype C = map<B, string>;"),
+        "map keys must be strings, integers or simple enums, found `A` in `C`"
+    );
+    k9::snapshot!(
+        error("enum A { B(map<vec<string>, i64>), C(i64) }"),
+        "map keys must be strings, integers or simple enums, found `vec` in `A`"
+    );
+}
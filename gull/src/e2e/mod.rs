@@ -1,3 +1,26 @@
+mod api_docs_test;
 mod basic_codegen_test;
+mod compat_test;
+mod conformance;
+mod conformance_test;
+mod constraints_test;
+mod dependency_graph_test;
+mod deprecation_test;
+mod enum_helpers_test;
+mod fixtures_test;
+mod flatten_test;
+mod generics_test;
 mod graph_types_test;
+mod hack_enums_test;
+mod includes_test;
+mod integer_enums_test;
+mod json_test;
+mod map_keys_test;
+mod object_settings_test;
 mod project;
+mod rust_box_test;
+mod rust_impls_test;
+mod schema_test;
+mod simple_enum_variants_test;
+mod targets_test;
+mod validators_test;
@@ -0,0 +1,182 @@
+use crate::prelude::*;
+use anyhow::Result;
+
+const SCHEMA: &str = r#"
+hack_namespace "O";
+
+@flow_exact
+@flow_read_only
+@flow_read_only_arrays
+@hack_open_shape
+struct Node {
+    id: i64,
+    tags: set<string>,
+    children: vec<Node>,
+    weights?: map<string, f64>,
+}
+
+@flow_read_only
+type Index = map<string, vec<Node>>;
+
+@flow_exact
+@hack_open_shape
+enum Event {
+    Created(Node),
+    Moved { from: i64, to: i64 },
+}
+
+struct Plain {
+    nodes: vec<Node>,
+    event: Event,
+}
+"#;
+
+#[test]
+fn flow_test() -> Result<()> {
+    k9::snapshot!(
+        parse_schema(SCHEMA)?.codegen_flow()?,
+        r#"
+
+export type Node = $ReadOnly<{|
+    'id': number,
+    'tags': $ReadOnlyArray<string>,
+    'children': $ReadOnlyArray<Node>,
+    'weights'?: $ReadOnly<{[key: string]: number}>,
+|}>;
+
+export type Index = $ReadOnly<{[key: string]: Array<Node>}>;
+
+export type EventType = "Created" | "Moved";
+
+export type Event = {|
+    'Created'?: Node,
+    'Moved'?:  {|
+        'from': number,
+        'to': number,
+    |},
+|};
+
+export function getEventType(x: Event): EventType {
+    if (x.Created !== undefined) {
+        return 'Created';
+    }
+    if (x.Moved !== undefined) {
+        return 'Moved';
+    }
+    throw new Error('No variant of Event is set');
+}
+
+export function eventCreated(value: Node): Event {
+    return {'Created': value};
+}
+
+export function eventMoved(value: {|
+    'from': number,
+    'to': number,
+|}): Event {
+    return {'Moved': value};
+}
+
+export function matchEvent<TResult>(
+    x: Event,
+    cases: {|
+        'Created': (value: Node) => TResult,
+        'Moved': (value: {|
+            'from': number,
+            'to': number,
+        |}) => TResult,
+    |},
+): TResult {
+    if (x.Created !== undefined) {
+        return cases.Created(x.Created);
+    }
+    if (x.Moved !== undefined) {
+        return cases.Moved(x.Moved);
+    }
+    throw new Error('No variant of Event is set');
+}
+
+export type Plain = {
+    'nodes': Array<Node>,
+    'event': Event,
+};
+
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn hack_test() -> Result<()> {
+    k9::snapshot!(
+        parse_schema(SCHEMA)?.codegen_hack()?,
+        r#"
+<?hh
+
+type ONode = shape(
+    'id' => int,
+    'tags' => keyset<string>,
+    'children' => vec<ONode>,
+    ?'weights' => dict<arraykey, float>,
+    ...
+);
+
+type OIndex = dict<arraykey, vec<ONode>>;
+
+
+enum OEventType: string as string {
+    CREATED = "Created";
+    MOVED = "Moved";
+}
+
+type OEvent = shape(
+    ?'Created' => ONode,
+    ?'Moved' =>  shape(
+        'from' => int,
+        'to' => int,
+        ...
+    ),
+    ...
+);
+
+function OEvent_getType(OEvent $x): OEventType {
+    if (Shapes::keyExists($x, 'Created')) {
+        return OEventType::CREATED;
+    }
+    if (Shapes::keyExists($x, 'Moved')) {
+        return OEventType::MOVED;
+    }
+    invariant_violation('No variant of OEvent is set');
+}
+
+function OEvent_getCreated(OEvent $x): ?ONode {
+    return Shapes::idx($x, 'Created');
+}
+
+function OEvent_getMoved(OEvent $x): ?shape(
+    'from' => int,
+    'to' => int,
+    ...
+) {
+    return Shapes::idx($x, 'Moved');
+}
+
+type OPlain = shape(
+    'nodes' => vec<ONode>,
+    'event' => OEvent,
+);
+
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn errors_test() {
+    let error = |source: &str| parse_schema(source).unwrap_err().to_string();
+
+    k9::snapshot!(
+        error("@flow_exact(\"yes\") struct A { b: i64 }"),
+        "1:1: `@flow_exact` doesn't take a value"
+    );
+}
@@ -52,9 +52,11 @@ impl Project {
     //
     // Will write a file under `absolute/path/to/gull/gull/tmp/test_project_name/Cargo.toml`
     pub fn write_file(&self, path: &str, content: &str) -> Result<()> {
-        fs::create_dir_all(&self.root_dir).context("can't create project root dir")?;
         let mut absolute_path = self.root_dir.clone();
         absolute_path.push(path);
+        if let Some(dir) = absolute_path.parent() {
+            fs::create_dir_all(dir).context("can't create project dir")?;
+        }
         fs::write(&absolute_path, content)
             .with_context(|| format!("failed to write file `{}`", absolute_path.display()))?;
         Ok(())
@@ -0,0 +1,189 @@
+use crate::prelude::*;
+use anyhow::Result;
+
+const SCHEMA: &str = r#"
+enum Expr {
+    Literal(i64),
+    Not(Not),
+    And { left: Expr, right: option<Expr> },
+    Group(vec<Expr>),
+}
+
+struct Not { expr: Expr }
+
+struct LinkedList<T> {
+    value: T,
+    next: option<LinkedList<T>>,
+}
+
+struct Tree {
+    children: vec<Tree>,
+    index: map<string, Tree>,
+    list: LinkedList<Index>,
+}
+
+type Index = i64;
+
+struct Config {
+    @rust_box
+    tree: Tree,
+    @rust_box
+    parent?: Config,
+}
+"#;
+
+#[test]
+fn rust_test() -> Result<()> {
+    let declarations = parse_schema(SCHEMA)?;
+
+    k9::snapshot!(
+        declarations.codegen_rust()?,
+        r#"
+use std::collections::BTreeMap;
+
+
+pub enum Expr {
+    Literal(i64),
+    Not(Box<Not>),
+    And {
+        left: Box<Expr>,
+        right: Option<Box<Expr>>,
+    },
+    Group(Vec<Expr>),
+}
+
+pub struct Not {
+    pub expr: Box<Expr>,
+}
+
+pub struct LinkedList<T> {
+    pub value: T,
+    pub next: Option<Box<LinkedList<T>>>,
+}
+
+pub struct Tree {
+    pub children: Vec<Tree>,
+    pub index: BTreeMap<String, Tree>,
+    pub list: LinkedList<Index>,
+}
+
+pub type Index = i64;
+
+pub struct Config {
+    pub tree: Box<Tree>,
+    pub parent: Option<Box<Config>>,
+}
+
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn flow_test() -> Result<()> {
+    let declarations = parse_schema(SCHEMA)?;
+
+    k9::snapshot!(
+        declarations.codegen_flow()?,
+        r#"
+
+export type ExprType = "Literal" | "Not" | "And" | "Group";
+
+export type Expr = {
+    'Literal'?: number,
+    'Not'?: Not,
+    'And'?:  {
+        'left': Expr,
+        'right'?: Expr,
+    },
+    'Group'?: Array<Expr>,
+};
+
+export function getExprType(x: Expr): ExprType {
+    if (x.Literal !== undefined) {
+        return 'Literal';
+    }
+    if (x.Not !== undefined) {
+        return 'Not';
+    }
+    if (x.And !== undefined) {
+        return 'And';
+    }
+    if (x.Group !== undefined) {
+        return 'Group';
+    }
+    throw new Error('No variant of Expr is set');
+}
+
+export function exprLiteral(value: number): Expr {
+    return {'Literal': value};
+}
+
+export function exprNot(value: Not): Expr {
+    return {'Not': value};
+}
+
+export function exprAnd(value: {
+    'left': Expr,
+    'right'?: Expr,
+}): Expr {
+    return {'And': value};
+}
+
+export function exprGroup(value: Array<Expr>): Expr {
+    return {'Group': value};
+}
+
+export function matchExpr<TResult>(
+    x: Expr,
+    cases: {|
+        'Literal': (value: number) => TResult,
+        'Not': (value: Not) => TResult,
+        'And': (value: {
+            'left': Expr,
+            'right'?: Expr,
+        }) => TResult,
+        'Group': (value: Array<Expr>) => TResult,
+    |},
+): TResult {
+    if (x.Literal !== undefined) {
+        return cases.Literal(x.Literal);
+    }
+    if (x.Not !== undefined) {
+        return cases.Not(x.Not);
+    }
+    if (x.And !== undefined) {
+        return cases.And(x.And);
+    }
+    if (x.Group !== undefined) {
+        return cases.Group(x.Group);
+    }
+    throw new Error('No variant of Expr is set');
+}
+
+export type Not = {
+    'expr': Expr,
+};
+
+export type LinkedList<T> = {
+    'value': T,
+    'next'?: LinkedList<T>,
+};
+
+export type Tree = {
+    'children': Array<Tree>,
+    'index': {[key: string]: Tree},
+    'list': LinkedList<Index>,
+};
+
+export type Index = number;
+
+export type Config = {
+    'tree': Tree,
+    'parent'?: Config,
+};
+
+"#
+    );
+    Ok(())
+}
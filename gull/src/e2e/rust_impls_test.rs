@@ -0,0 +1,220 @@
+use super::project::Project;
+use crate::prelude::*;
+use anyhow::Result;
+
+const SCHEMA: &str = r#"
+rust_impls;
+
+enum Color { Red, Green }
+
+struct Node<T: Clone> {
+    id: i64,
+    value: T,
+    color?: Color,
+    children: vec<Node<T>>,
+}
+
+enum Expr {
+    Literal(i64),
+    Count(i64),
+    Name(string),
+    Not(Expr),
+    @deprecated("Use `Not` twice")
+    Pair { left: i64, right: i64 },
+}
+
+enum Single {
+    Only(Color),
+}
+"#;
+
+// Uses every generated impl, with warnings denied
+const USAGE: &str = r#"
+#![deny(warnings)]
+
+#[test]
+fn impls() {
+    assert_eq!(Color::Green.to_string(), "Green");
+    assert_eq!("Red".parse::<Color>(), Ok(Color::Red));
+    assert_eq!("Blue".parse::<Color>(), Err("unknown Color `Blue`".to_string()));
+
+    let node = Node::new(1, "a".to_string(), vec![]);
+    assert_eq!(node.color, None);
+
+    let expr = Expr::from("x".to_string());
+    assert!(expr.is_name());
+    assert_eq!(expr.as_name().map(String::as_str), Some("x"));
+    assert_eq!(expr.as_literal(), None);
+    let not = Expr::Not(Box::new(expr));
+    assert_eq!(not.as_not().and_then(Expr::as_name).map(String::as_str), Some("x"));
+    assert!(!Expr::Count(1).is_pair());
+
+    assert_eq!(Single::from(Color::Red).as_only(), Some(&Color::Red));
+}
+"#;
+
+#[test]
+fn rust_test() -> Result<()> {
+    k9::snapshot!(
+        parse_schema(SCHEMA)?.codegen_rust()?,
+        r#"
+
+
+pub enum Color {
+    Red,
+    Green,
+}
+
+impl std::fmt::Display for Color {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Red => "Red",
+            Self::Green => "Green",
+        })
+    }
+}
+
+impl std::str::FromStr for Color {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "Red" => Ok(Self::Red),
+            "Green" => Ok(Self::Green),
+            _ => Err(format!("unknown Color `{}`", s)),
+        }
+    }
+}
+
+pub struct Node<T: Clone> {
+    pub id: i64,
+    pub value: T,
+    pub color: Option<Color>,
+    pub children: Vec<Node<T>>,
+}
+
+impl<T: Clone> Node<T> {
+    pub fn new(id: i64, value: T, children: Vec<Node<T>>) -> Self {
+        Self {
+            id,
+            value,
+            color: None,
+            children,
+        }
+    }
+}
+
+pub enum Expr {
+    Literal(i64),
+    Count(i64),
+    Name(String),
+    Not(Box<Expr>),
+    #[deprecated(note = "Use `Not` twice")]
+    Pair {
+        left: i64,
+        right: i64,
+    },
+}
+
+#[allow(deprecated)]
+impl From<String> for Expr {
+    fn from(value: String) -> Self {
+        Self::Name(value)
+    }
+}
+
+#[allow(deprecated)]
+impl Expr {
+    pub fn is_literal(&self) -> bool {
+        matches!(self, Self::Literal(..))
+    }
+
+    pub fn as_literal(&self) -> Option<&i64> {
+        match self {
+            Self::Literal(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn is_count(&self) -> bool {
+        matches!(self, Self::Count(..))
+    }
+
+    pub fn as_count(&self) -> Option<&i64> {
+        match self {
+            Self::Count(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn is_name(&self) -> bool {
+        matches!(self, Self::Name(..))
+    }
+
+    pub fn as_name(&self) -> Option<&String> {
+        match self {
+            Self::Name(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn is_not(&self) -> bool {
+        matches!(self, Self::Not(..))
+    }
+
+    pub fn as_not(&self) -> Option<&Expr> {
+        match self {
+            Self::Not(value) => Some(&**value),
+            _ => None,
+        }
+    }
+
+    pub fn is_pair(&self) -> bool {
+        matches!(self, Self::Pair { .. })
+    }
+}
+
+pub enum Single {
+    Only(Color),
+}
+
+impl From<Color> for Single {
+    fn from(value: Color) -> Self {
+        Self::Only(value)
+    }
+}
+
+impl Single {
+    pub fn is_only(&self) -> bool {
+        matches!(self, Self::Only(..))
+    }
+
+    pub fn as_only(&self) -> Option<&Color> {
+        match self {
+            Self::Only(value) => Some(value),
+        }
+    }
+}
+
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn compile_test() -> Result<()> {
+    let mut declarations = parse_schema(SCHEMA)?;
+    for declaration in &mut declarations.declarations {
+        declaration.config.push(TypeDeclarationConfig::rust_attribute(
+            "#[derive(Debug, PartialEq)]",
+        ));
+    }
+
+    let project = Project::new("rust_impls")?;
+    project.write_default_cargo_toml()?;
+    project.write_file(
+        "lib.rs",
+        &format!("{}\n{}", USAGE, declarations.codegen_rust()?),
+    )?;
+    project.run("cargo test --offline --quiet")?.assert_success()
+}
@@ -0,0 +1,321 @@
+use crate::prelude::*;
+use anyhow::Result;
+
+pub(crate) const SCHEMA: &str = r##"
+hack_namespace "Schema";
+
+//! ==========================================
+//! Types loaded from a schema file
+//! ==========================================
+
+code rust "use crate::types::ID as _ID;";
+
+type ID = per_language(rust: raw("_ID"), hack: i64, flow: i64);
+
+/// Frame represents a tuple of an Timestamp (RFC3339) and an ID
+@rust("#[derive(Copy, Clone)]")
+type Frame = (string, ID);
+
+enum StorageType { Full, Delta }
+
+/// Operation is a single unit of transormation logic
+enum Operation {
+    /// Fetch items by their IDs
+    Fetch { items: vec<i64> },
+    Store(vec<Frame>),
+    Drop(bool),
+}
+
+struct GraphNode<T: Ord + Clone> {
+    /// Node name
+    name: T,
+    tags: set<T>,
+    edges: hash_map<string, set<ID>>,
+    @rust("#[serde(skip_serializing_if = \"Option::is_none\")]")
+    parent?: ID,
+    @rust_override("DateTime<Utc>")
+    timestamp: string,
+}
+
+type StringNode = GraphNode<ID>;
+"##;
+
+#[test]
+fn rust_test() -> Result<()> {
+    let declarations = parse_schema(SCHEMA)?;
+    k9::snapshot!(
+        declarations.codegen_rust()?,
+        r#"
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+
+
+// ==========================================
+// Types loaded from a schema file
+// ==========================================
+
+
+use crate::types::ID as _ID;
+
+pub type ID = _ID;
+
+#[derive(Copy, Clone)]
+/// Frame represents a tuple of an Timestamp (RFC3339) and an ID
+pub type Frame = (String, ID);
+
+pub enum StorageType {
+    Full,
+    Delta,
+}
+
+/// Operation is a single unit of transormation logic
+pub enum Operation {
+    /// Fetch items by their IDs
+    Fetch {
+        items: Vec<i64>,
+    },
+    Store(Vec<Frame>),
+    Drop(bool),
+}
+
+pub struct GraphNode<T: Ord + Clone> {
+    /// Node name
+    pub name: T,
+    pub tags: BTreeSet<T>,
+    pub edges: HashMap<String, BTreeSet<ID>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<ID>,
+    pub timestamp: DateTime<Utc>,
+}
+
+pub type StringNode = GraphNode<ID>;
+
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn hack_test() -> Result<()> {
+    let declarations = parse_schema(SCHEMA)?;
+    k9::snapshot!(
+        declarations.codegen_hack()?,
+        r#"
+<?hh
+
+// ==========================================
+// Types loaded from a schema file
+// ==========================================
+
+
+
+
+type SchemaID = int;
+
+// Frame represents a tuple of an Timestamp (RFC3339) and an ID
+type SchemaFrame = (string, SchemaID);
+
+enum SchemaStorageType: string as string {
+    FULL = "Full";
+    DELTA = "Delta";
+}
+
+// Operation is a single unit of transormation logic
+
+enum SchemaOperationType: string as string {
+    FETCH = "Fetch";
+    STORE = "Store";
+    DROP = "Drop";
+}
+
+type SchemaOperation = shape(
+    // Fetch items by their IDs
+    ?'Fetch' =>  shape(
+        'items' => vec<int>,
+    ),
+    ?'Store' => vec<SchemaFrame>,
+    ?'Drop' => bool,
+);
+
+function SchemaOperation_getType(SchemaOperation $x): SchemaOperationType {
+    if (Shapes::keyExists($x, 'Fetch')) {
+        return SchemaOperationType::FETCH;
+    }
+    if (Shapes::keyExists($x, 'Store')) {
+        return SchemaOperationType::STORE;
+    }
+    if (Shapes::keyExists($x, 'Drop')) {
+        return SchemaOperationType::DROP;
+    }
+    invariant_violation('No variant of SchemaOperation is set');
+}
+
+function SchemaOperation_getFetch(SchemaOperation $x): ?shape(
+    'items' => vec<int>,
+) {
+    return Shapes::idx($x, 'Fetch');
+}
+
+function SchemaOperation_getStore(SchemaOperation $x): ?vec<SchemaFrame> {
+    return Shapes::idx($x, 'Store');
+}
+
+function SchemaOperation_getDrop(SchemaOperation $x): ?bool {
+    return Shapes::idx($x, 'Drop');
+}
+
+type SchemaGraphNode<T> = shape(
+    // Node name
+    'name' => T,
+    'tags' => keyset<T>,
+    'edges' => dict<arraykey, keyset<SchemaID>>,
+    ?'parent' => SchemaID,
+    'timestamp' => string,
+);
+
+type SchemaStringNode = SchemaGraphNode<SchemaID>;
+
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn flow_test() -> Result<()> {
+    let declarations = parse_schema(SCHEMA)?;
+    k9::snapshot!(
+        declarations.codegen_flow()?,
+        r#"
+
+// ==========================================
+// Types loaded from a schema file
+// ==========================================
+
+
+
+
+export type ID = number;
+
+// Frame represents a tuple of an Timestamp (RFC3339) and an ID
+export type Frame = [string, ID];
+
+export type StorageType = "Full" | "Delta";
+
+export enum StorageTypeEnum {Full, Delta};
+
+// Operation is a single unit of transormation logic
+export type OperationType = "Fetch" | "Store" | "Drop";
+
+export type Operation = {
+    // Fetch items by their IDs
+    'Fetch'?:  {
+        'items': Array<number>,
+    },
+    'Store'?: Array<Frame>,
+    'Drop'?: boolean,
+};
+
+export function getOperationType(x: Operation): OperationType {
+    if (x.Fetch !== undefined) {
+        return 'Fetch';
+    }
+    if (x.Store !== undefined) {
+        return 'Store';
+    }
+    if (x.Drop !== undefined) {
+        return 'Drop';
+    }
+    throw new Error('No variant of Operation is set');
+}
+
+export function operationFetch(value: {
+    'items': Array<number>,
+}): Operation {
+    return {'Fetch': value};
+}
+
+export function operationStore(value: Array<Frame>): Operation {
+    return {'Store': value};
+}
+
+export function operationDrop(value: boolean): Operation {
+    return {'Drop': value};
+}
+
+export function matchOperation<TResult>(
+    x: Operation,
+    cases: {|
+        'Fetch': (value: {
+            'items': Array<number>,
+        }) => TResult,
+        'Store': (value: Array<Frame>) => TResult,
+        'Drop': (value: boolean) => TResult,
+    |},
+): TResult {
+    if (x.Fetch !== undefined) {
+        return cases.Fetch(x.Fetch);
+    }
+    if (x.Store !== undefined) {
+        return cases.Store(x.Store);
+    }
+    if (x.Drop !== undefined) {
+        return cases.Drop(x.Drop);
+    }
+    throw new Error('No variant of Operation is set');
+}
+
+export type GraphNode<T> = {
+    // Node name
+    'name': T,
+    'tags': Array<T>,
+    'edges': {[key: string]: Array<ID>},
+    'parent'?: ID,
+    'timestamp': string,
+};
+
+export type StringNode = GraphNode<ID>;
+
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn errors_test() {
+    let error = |source: &str| parse_schema(source).unwrap_err().to_string();
+
+    k9::snapshot!(
+        error("struct A {\n    b: Missing,\n}"),
+        "2:8: unknown type `Missing`"
+    );
+    k9::snapshot!(
+        error("type A = map<string>;"),
+        "1:10: `map` expects 2 generic argument(s), found 1"
+    );
+    k9::snapshot!(
+        error("type A = i64;\ntype A = string;"),
+        "2:1: type `A` is declared more than once"
+    );
+    k9::snapshot!(
+        error("enum A {\n  B,\n  C(i64),\n}"),
+        "2:3: variant `B` has no data. Enums can't mix variants with and without data"
+    );
+    k9::snapshot!(
+        error("struct A {\n  b: i64\n  c: i64,\n}"),
+        "3:3: expected `,`, found `c`"
+    );
+    k9::snapshot!(
+        error("type A = \"unterminated;"),
+        "1:10: unterminated string"
+    );
+    k9::snapshot!(
+        error("struct A {\n  @rust\n  b: i64,\n}"),
+        "2:3: `@rust` requires a value: `@rust(\"...\")`"
+    );
+    k9::snapshot!(
+        error("struct A {\n  @rust_box(\"yes\")\n  b: i64,\n}"),
+        "2:3: `@rust_box` doesn't take a value"
+    );
+    k9::snapshot!(error("union A {}"), "1:1: expected `type`, `struct`, `enum`, `code`, `hack_namespace`, `file_header`, `validators`, `rust_impls` or `emit_order`, found `union`");
+    k9::snapshot!(error("1"), "1:1: expected `type`, `struct`, `enum`, `code`, `hack_namespace`, `file_header`, `validators`, `rust_impls` or `emit_order`, found `1`");
+}
@@ -0,0 +1,211 @@
+use crate::prelude::*;
+use anyhow::Result;
+
+const SCHEMA: &str = r#"
+hack_namespace "S";
+rust_impls;
+
+/// Where a node is stored
+enum Storage {
+    /// Kept in memory
+    @value("in-memory")
+    InMemory,
+    @value("disk")
+    @deprecated("Use `InMemory`")
+    @since("2.0")
+    Disk,
+    Remote,
+}
+
+struct Node {
+    storage: Storage,
+    by_storage: map<Storage, i64>,
+}
+"#;
+
+#[test]
+fn rust_test() -> Result<()> {
+    k9::snapshot!(
+        parse_schema(SCHEMA)?.codegen_rust()?,
+        r#"
+use std::collections::BTreeMap;
+
+
+/// Where a node is stored
+pub enum Storage {
+    /// Kept in memory
+    #[serde(rename = "in-memory")]
+    InMemory,
+    #[deprecated(since = "2.0", note = "Use `InMemory`")]
+    #[serde(rename = "disk")]
+    Disk,
+    Remote,
+}
+
+#[allow(deprecated)]
+impl std::fmt::Display for Storage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::InMemory => "in-memory",
+            Self::Disk => "disk",
+            Self::Remote => "Remote",
+        })
+    }
+}
+
+#[allow(deprecated)]
+impl std::str::FromStr for Storage {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "in-memory" => Ok(Self::InMemory),
+            "disk" => Ok(Self::Disk),
+            "Remote" => Ok(Self::Remote),
+            _ => Err(format!("unknown Storage `{}`", s)),
+        }
+    }
+}
+
+pub struct Node {
+    pub storage: Storage,
+    pub by_storage: BTreeMap<Storage, i64>,
+}
+
+impl Node {
+    pub fn new(storage: Storage, by_storage: BTreeMap<Storage, i64>) -> Self {
+        Self {
+            storage,
+            by_storage,
+        }
+    }
+}
+
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn hack_test() -> Result<()> {
+    k9::snapshot!(
+        parse_schema(SCHEMA)?.codegen_hack()?,
+        r#"
+<?hh
+
+// Where a node is stored
+enum SStorage: string as string {
+    // Kept in memory
+    IN_MEMORY = "in-memory";
+    // @deprecated Use `InMemory` (since 2.0)
+    DISK = "disk";
+    REMOTE = "Remote";
+}
+
+type SNode = shape(
+    'storage' => SStorage,
+    'by_storage' => dict<SStorage, int>,
+);
+
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn flow_test() -> Result<()> {
+    k9::snapshot!(
+        parse_schema(SCHEMA)?.codegen_flow()?,
+        r#"
+
+// Where a node is stored
+export type Storage =
+    // Kept in memory
+    | "in-memory"
+    /** @deprecated Use `InMemory` (since 2.0) */
+    | "disk"
+    | "Remote";
+
+export enum StorageEnum {
+    // Kept in memory
+    InMemory = "in-memory",
+    /** @deprecated Use `InMemory` (since 2.0) */
+    Disk = "disk",
+    Remote = "Remote",
+};
+
+export type Node = {
+    'storage': Storage,
+    'by_storage': {'in-memory'?: number, 'disk'?: number, 'Remote'?: number},
+};
+
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn markdown_test() -> Result<()> {
+    let markdown = parse_schema(SCHEMA)?.codegen_markdown()?;
+    k9::snapshot!(
+        markdown
+            .lines()
+            .filter(|l| l.starts_with('|'))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        r#"
+| Value | Description |
+| --- | --- |
+| `"in-memory"` | Kept in memory |
+| `"disk"` | Deprecated: Use `InMemory` (since 2.0). |
+| `"Remote"` |  |
+| Field | Type | Description |
+| --- | --- | --- |
+| `storage` | [Storage](#storage) |  |
+| `by_storage` | map&lt;[Storage](#storage), i64&gt; |  |
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn json_test() -> Result<()> {
+    // Variants used to be plain strings
+    let json = r#"{
+        "version": 1,
+        "declarations": [{
+            "name": "Color",
+            "docs": "",
+            "value": {"kind": "TSimpleEnum", "value": {"variants": ["Red", "Green"]}},
+            "config": [],
+            "generic_params": []
+        }],
+        "config": []
+    }"#;
+    let declarations = Declarations::from_json(json)?;
+    k9::snapshot!(
+        declarations.codegen_flow()?,
+        r#"
+
+export type Color = "Red" | "Green";
+
+export enum ColorEnum {Red, Green};
+
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn errors_test() {
+    let error = |source: &str| parse_schema(source).unwrap_err().to_string();
+
+    k9::snapshot!(
+        error("enum A { @value(\"b\") B, C, @value(\"C\") D }"),
+        "1:40: value \"C\" is used by more than one variant of `A`"
+    );
+    k9::snapshot!(
+        error("enum A { @rust(\"x\") B, C }"),
+        "1:10: unknown attribute `@rust`"
+    );
+}
@@ -0,0 +1,153 @@
+use crate::prelude::*;
+use anyhow::Result;
+
+const SCHEMA: &str = r#"
+hack_namespace "T";
+
+type ID = i64;
+
+@except("hack")
+@rust_visibility("pub(crate)")
+struct Cache {
+    entries: map<ID, string>,
+}
+
+@only("rust, flow")
+@rust_visibility("private")
+struct Internal {
+    cache: Cache,
+}
+
+@only("hack, flow")
+enum Shape { Square, Circle }
+
+struct Node {
+    id: ID,
+}
+"#;
+
+#[test]
+fn rust_test() -> Result<()> {
+    k9::snapshot!(
+        parse_schema(SCHEMA)?.codegen_rust()?,
+        r#"
+use std::collections::BTreeMap;
+
+
+pub type ID = i64;
+
+pub(crate) struct Cache {
+    pub entries: BTreeMap<ID, String>,
+}
+
+struct Internal {
+    pub cache: Cache,
+}
+
+pub struct Node {
+    pub id: ID,
+}
+
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn hack_test() -> Result<()> {
+    k9::snapshot!(
+        parse_schema(SCHEMA)?.codegen_hack()?,
+        r#"
+<?hh
+
+type TID = int;
+
+enum TShape: string as string {
+    SQUARE = "Square";
+    CIRCLE = "Circle";
+}
+
+type TNode = shape(
+    'id' => TID,
+);
+
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn flow_test() -> Result<()> {
+    k9::snapshot!(
+        parse_schema(SCHEMA)?.codegen_flow()?,
+        r#"
+
+export type ID = number;
+
+export type Cache = {
+    'entries': {[key: string]: string},
+};
+
+export type Internal = {
+    'cache': Cache,
+};
+
+export type Shape = "Square" | "Circle";
+
+export enum ShapeEnum {Square, Circle};
+
+export type Node = {
+    'id': ID,
+};
+
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn errors_test() -> Result<()> {
+    let codegen_error = |source: &str, language: &str| {
+        let declarations = parse_schema(source).unwrap();
+        let result = match language {
+            "rust" => declarations.codegen_rust(),
+            "hack" => declarations.codegen_hack(),
+            _ => declarations.codegen_flow(),
+        };
+        result.unwrap_err().to_string()
+    };
+
+    k9::snapshot!(
+        codegen_error(
+            "@only(\"rust\") struct A { b: i64 }\nstruct B { a: A }",
+            "hack"
+        ),
+        "`B` references `A` that is not generated for Hack"
+    );
+    k9::snapshot!(
+        codegen_error("@except(\"flow\") type A = i64;\ntype B = vec<A>;", "flow"),
+        "`B` references `A` that is not generated for Flow"
+    );
+    k9::snapshot!(
+        codegen_error(
+            "@rust_visibility(\"pub(crate)\") struct A { b: i64 }\nstruct B { a: A }",
+            "rust"
+        ),
+        "`pub B` references less visible `pub(crate) A`"
+    );
+
+    let error = |source: &str| parse_schema(source).unwrap_err().to_string();
+    k9::snapshot!(
+        error("@only(\"rust, swift\") type A = i64;"),
+        "1:1: unknown language `swift`, expected `rust`, `hack` or `flow`"
+    );
+    k9::snapshot!(
+        error("@rust_visibility(\"pub(super)\") type A = i64;"),
+        "1:1: unknown visibility `pub(super)`, expected `pub`, `pub(crate)` or `private`"
+    );
+    k9::snapshot!(
+        error("@except type A = i64;"),
+        "1:1: `@except` requires a value: `@except(\"...\")`"
+    );
+    Ok(())
+}
@@ -0,0 +1,579 @@
+use crate::prelude::*;
+use anyhow::Result;
+
+const SCHEMA: &str = r#"
+hack_namespace "V";
+validators;
+
+type ID = i64;
+
+enum StorageType { Full, Delta }
+
+struct Node<T> {
+    id: ID,
+    value: T,
+    tags: set<string>,
+    weights: map<string, f64>,
+    parent?: ID,
+    frame: (string, option<i64>),
+}
+
+enum Operation {
+    Fetch { items: vec<ID> },
+    Store(Node<ID>),
+}
+"#;
+
+#[test]
+fn flow_test() -> Result<()> {
+    let declarations = parse_schema(SCHEMA)?;
+    k9::snapshot!(
+        without_helpers(&declarations.codegen_flow()?, "type GullCheck"),
+        r#"
+
+export type ID = number;
+
+export function validateID(x: mixed, path: string = '$'): ID {
+    gullInt(x, path);
+    return (x: any);
+}
+
+export type StorageType = "Full" | "Delta";
+
+export enum StorageTypeEnum {Full, Delta};
+
+export function validateStorageType(x: mixed, path: string = '$'): StorageType {
+    gullOneOf(["Full", "Delta"])(x, path);
+    return (x: any);
+}
+
+export type Node<T> = {
+    'id': ID,
+    'value': T,
+    'tags': Array<string>,
+    'weights': {[key: string]: number},
+    'parent'?: ID,
+    'frame': [string, ?number],
+};
+
+export function validateNode<T>(x: mixed, validateT: GullCheck, path: string = '$'): Node<T> {
+    gullStructOf({
+        'id': validateID,
+        'value': validateT,
+        'tags': gullArrayOf(gullString),
+        'weights': gullMapOf(gullNumber),
+        'parent': gullOptionOf(validateID),
+        'frame': gullTupleOf([gullString, gullOptionOf(gullInt)]),
+    })(x, path);
+    return (x: any);
+}
+
+export type OperationType = "Fetch" | "Store";
+
+export type Operation = {
+    'Fetch'?:  {
+        'items': Array<ID>,
+    },
+    'Store'?: Node<ID>,
+};
+
+export function getOperationType(x: Operation): OperationType {
+    if (x.Fetch !== undefined) {
+        return 'Fetch';
+    }
+    if (x.Store !== undefined) {
+        return 'Store';
+    }
+    throw new Error('No variant of Operation is set');
+}
+
+export function operationFetch(value: {
+    'items': Array<ID>,
+}): Operation {
+    return {'Fetch': value};
+}
+
+export function operationStore(value: Node<ID>): Operation {
+    return {'Store': value};
+}
+
+export function matchOperation<TResult>(
+    x: Operation,
+    cases: {|
+        'Fetch': (value: {
+            'items': Array<ID>,
+        }) => TResult,
+        'Store': (value: Node<ID>) => TResult,
+    |},
+): TResult {
+    if (x.Fetch !== undefined) {
+        return cases.Fetch(x.Fetch);
+    }
+    if (x.Store !== undefined) {
+        return cases.Store(x.Store);
+    }
+    throw new Error('No variant of Operation is set');
+}
+
+export function validateOperation(x: mixed, path: string = '$'): Operation {
+    gullEnumOf({
+        'Fetch': gullStructOf({
+            'items': gullArrayOf(validateID),
+        }),
+        'Store': (x, path) => validateNode(x, validateID, path),
+    })(x, path);
+    return (x: any);
+}
+
+
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn hack_test() -> Result<()> {
+    let declarations = parse_schema(SCHEMA)?;
+    k9::snapshot!(
+        without_helpers(&declarations.codegen_hack()?, "type VGullCheck"),
+        r#"
+<?hh
+
+type VID = int;
+
+function validateVID(mixed $x, string $path = '$'): VID {
+    VGull::isInt()($x, $path);
+    /* HH_FIXME[4110] checked above */
+    return $x;
+}
+
+enum VStorageType: string as string {
+    FULL = "Full";
+    DELTA = "Delta";
+}
+
+function validateVStorageType(mixed $x, string $path = '$'): VStorageType {
+    VGull::oneOf(keyset['Full', 'Delta'])($x, $path);
+    /* HH_FIXME[4110] checked above */
+    return $x;
+}
+
+type VNode<T> = shape(
+    'id' => VID,
+    'value' => T,
+    'tags' => keyset<string>,
+    'weights' => dict<arraykey, float>,
+    ?'parent' => VID,
+    'frame' => (string, ?int),
+);
+
+function validateVNode<T>(mixed $x, VGullCheck $validate_T, string $path = '$'): VNode<T> {
+    VGull::shapeOf(dict[
+        'id' => ($x, $path) ==> { validateVID($x, $path); },
+        'value' => $validate_T,
+        'tags' => VGull::keysetOf(VGull::isString()),
+        'weights' => VGull::dictOf(VGull::isFloat()),
+        'parent' => VGull::optionOf(($x, $path) ==> { validateVID($x, $path); }),
+        'frame' => VGull::tupleOf(vec[VGull::isString(), VGull::optionOf(VGull::isInt())]),
+    ])($x, $path);
+    /* HH_FIXME[4110] checked above */
+    return $x;
+}
+
+
+enum VOperationType: string as string {
+    FETCH = "Fetch";
+    STORE = "Store";
+}
+
+type VOperation = shape(
+    ?'Fetch' =>  shape(
+        'items' => vec<VID>,
+    ),
+    ?'Store' => VNode<VID>,
+);
+
+function VOperation_getType(VOperation $x): VOperationType {
+    if (Shapes::keyExists($x, 'Fetch')) {
+        return VOperationType::FETCH;
+    }
+    if (Shapes::keyExists($x, 'Store')) {
+        return VOperationType::STORE;
+    }
+    invariant_violation('No variant of VOperation is set');
+}
+
+function VOperation_getFetch(VOperation $x): ?shape(
+    'items' => vec<VID>,
+) {
+    return Shapes::idx($x, 'Fetch');
+}
+
+function VOperation_getStore(VOperation $x): ?VNode<VID> {
+    return Shapes::idx($x, 'Store');
+}
+
+function validateVOperation(mixed $x, string $path = '$'): VOperation {
+    VGull::enumOf(dict[
+        'Fetch' => VGull::shapeOf(dict[
+            'items' => VGull::vecOf(($x, $path) ==> { validateVID($x, $path); }),
+        ]),
+        'Store' => ($x, $path) ==> { validateVNode($x, ($x, $path) ==> { validateVID($x, $path); }, $path); },
+    ])($x, $path);
+    /* HH_FIXME[4110] checked above */
+    return $x;
+}
+
+
+"#
+    );
+    Ok(())
+}
+
+#[test]
+fn helpers_test() -> Result<()> {
+    let declarations = parse_schema(SCHEMA)?;
+    let flow = declarations.codegen_flow()?;
+    let hack = declarations.codegen_hack()?;
+    assert_eq!(flow.matches("type GullCheck").count(), 1);
+    assert_eq!(hack.matches("type VGullCheck").count(), 1);
+
+    k9::snapshot!(
+        helpers(&flow, "type GullCheck"),
+        r#"
+type GullCheck = (x: mixed, path: string) => mixed;
+
+function gullFail(x: mixed, path: string, expected: string): empty {
+    const actual = x === null ? 'null' : Array.isArray(x) ? 'array' : typeof x;
+    throw new Error(`${path}: expected ${expected}, got ${actual}`);
+}
+
+function gullAny(x: mixed, path: string): void {}
+
+function gullString(x: mixed, path: string): void {
+    if (typeof x !== 'string') gullFail(x, path, 'string');
+}
+
+function gullNumber(x: mixed, path: string): void {
+    if (typeof x !== 'number') gullFail(x, path, 'number');
+}
+
+function gullInt(x: mixed, path: string): void {
+    if (typeof x !== 'number' || !Number.isInteger(x)) gullFail(x, path, 'integer');
+}
+
+function gullBoolean(x: mixed, path: string): void {
+    if (typeof x !== 'boolean') gullFail(x, path, 'boolean');
+}
+
+function gullObject(x: mixed, path: string): {+[key: string]: mixed} {
+    if (typeof x !== 'object' || x === null || Array.isArray(x)) {
+        return gullFail(x, path, 'object');
+    }
+    return x;
+}
+
+function gullArray(x: mixed, path: string): $ReadOnlyArray<mixed> {
+    if (!Array.isArray(x)) return gullFail(x, path, 'array');
+    return x;
+}
+
+function gullArrayOf(item: GullCheck): GullCheck {
+    return (x, path) => gullArray(x, path).forEach((v, i) => item(v, `${path}[${i}]`));
+}
+
+function gullMapOf(value: GullCheck, key?: GullCheck): GullCheck {
+    return (x, path) => {
+        const o = gullObject(x, path);
+        Object.keys(o).forEach(k => {
+            if (key) key(k, `${path}.${k}`);
+            value(o[k], `${path}.${k}`);
+        });
+    };
+}
+
+function gullOptionOf(inner: GullCheck): GullCheck {
+    return (x, path) => (x == null ? x : inner(x, path));
+}
+
+function gullTupleOf(items: $ReadOnlyArray<GullCheck>): GullCheck {
+    return (x, path) => {
+        const a = gullArray(x, path);
+        if (a.length !== items.length) gullFail(x, path, `${items.length} items`);
+        items.forEach((item, i) => item(a[i], `${path}[${i}]`));
+    };
+}
+
+function gullStructOf(fields: {+[key: string]: GullCheck}): GullCheck {
+    return (x, path) => {
+        const o = gullObject(x, path);
+        Object.keys(fields).forEach(k => fields[k](o[k], `${path}.${k}`));
+    };
+}
+
+function gullEnumOf(variants: {+[key: string]: GullCheck}): GullCheck {
+    return (x, path) => {
+        const o = gullObject(x, path);
+        const keys = Object.keys(o);
+        if (keys.length !== 1 || variants[keys[0]] == null) {
+            return gullFail(x, path, `one of ${Object.keys(variants).join(', ')}`);
+        }
+        variants[keys[0]](o[keys[0]], `${path}.${keys[0]}`);
+    };
+}
+
+function gullOneOf(values: $ReadOnlyArray<string | number>): GullCheck {
+    return (x, path) => {
+        if (!values.includes(x)) {
+            gullFail(x, path, values.map(v => JSON.stringify(v)).join(' | '));
+        }
+    };
+}
+
+type GullConstraints = {|
+    minLength?: number,
+    maxLength?: number,
+    pattern?: string,
+    minimum?: number,
+    maximum?: number,
+|};
+
+// Strings are measured in characters, like in Rust and Hack
+function gullLength(x: mixed): number {
+    if (typeof x === 'string') return Array.from(x).length;
+    if (Array.isArray(x)) return x.length;
+    return typeof x === 'object' && x !== null ? Object.keys(x).length : 0;
+}
+
+function gullConstrained(inner: GullCheck, c: GullConstraints): GullCheck {
+    return (x, path) => {
+        inner(x, path);
+        if (x == null) return;
+        const violated = (expected: string) => {
+            throw new Error(`${path}: expected ${expected}`);
+        };
+        const length = gullLength(x);
+        if (c.minLength != null && length < c.minLength) {
+            violated(`length of at least ${c.minLength}`);
+        }
+        if (c.maxLength != null && length > c.maxLength) {
+            violated(`length of at most ${c.maxLength}`);
+        }
+        if (c.pattern != null && typeof x === 'string' && !new RegExp(c.pattern, 'u').test(x)) {
+            violated(`a match of ${c.pattern}`);
+        }
+        if (typeof x === 'number') {
+            if (c.minimum != null && x < c.minimum) violated(`at least ${c.minimum}`);
+            if (c.maximum != null && x > c.maximum) violated(`at most ${c.maximum}`);
+        }
+    };
+}
+
+"#
+    );
+    k9::snapshot!(
+        helpers(&hack, "type VGullCheck"),
+        r#"
+type VGullCheck = (function(mixed, string): void);
+
+abstract final class VGull {
+    public static function fail(mixed $x, string $path, string $expected): nothing {
+        throw new \\UnexpectedValueException(
+            $path.': expected '.$expected.', got '.\\gettype($x),
+        );
+    }
+
+    public static function any(): VGullCheck {
+        return ($x, $path) ==> {};
+    }
+
+    public static function isString(): VGullCheck {
+        return ($x, $path) ==> {
+            if (!($x is string)) {
+                self::fail($x, $path, 'string');
+            }
+        };
+    }
+
+    public static function isInt(): VGullCheck {
+        return ($x, $path) ==> {
+            if (!($x is int)) {
+                self::fail($x, $path, 'int');
+            }
+        };
+    }
+
+    public static function isFloat(): VGullCheck {
+        return ($x, $path) ==> {
+            if (!($x is num)) {
+                self::fail($x, $path, 'float');
+            }
+        };
+    }
+
+    public static function isBool(): VGullCheck {
+        return ($x, $path) ==> {
+            if (!($x is bool)) {
+                self::fail($x, $path, 'bool');
+            }
+        };
+    }
+
+    public static function vecOf(VGullCheck $item): VGullCheck {
+        return ($x, $path) ==> {
+            if (!($x is vec<_>)) {
+                self::fail($x, $path, 'vec');
+            }
+            foreach ($x as $i => $v) {
+                $item($v, $path.'['.$i.']');
+            }
+        };
+    }
+
+    public static function keysetOf(VGullCheck $item): VGullCheck {
+        return ($x, $path) ==> {
+            if (!($x is vec<_>) && !($x is keyset<_>)) {
+                self::fail($x, $path, 'keyset');
+            }
+            foreach ($x as $i => $v) {
+                $item($v, $path.'['.$i.']');
+            }
+        };
+    }
+
+    public static function dictOf(VGullCheck $value, ?VGullCheck $key = null): VGullCheck {
+        return ($x, $path) ==> {
+            if (!($x is dict<_, _>)) {
+                self::fail($x, $path, 'dict');
+            }
+            foreach ($x as $k => $v) {
+                if ($key is nonnull) {
+                    $key($k, $path.'.'.$k);
+                }
+                $value($v, $path.'.'.$k);
+            }
+        };
+    }
+
+    public static function optionOf(VGullCheck $inner): VGullCheck {
+        return ($x, $path) ==> {
+            if ($x is nonnull) {
+                $inner($x, $path);
+            }
+        };
+    }
+
+    public static function tupleOf(vec<VGullCheck> $items): VGullCheck {
+        return ($x, $path) ==> {
+            if (!($x is vec<_>) || \\count($x) !== \\count($items)) {
+                self::fail($x, $path, \\count($items).' items');
+            }
+            foreach ($items as $i => $item) {
+                $item($x[$i], $path.'['.$i.']');
+            }
+        };
+    }
+
+    public static function shapeOf(dict<string, VGullCheck> $fields): VGullCheck {
+        return ($x, $path) ==> {
+            if (!($x is dict<_, _>)) {
+                self::fail($x, $path, 'shape');
+            }
+            foreach ($fields as $k => $field) {
+                $field(idx($x, $k), $path.'.'.$k);
+            }
+        };
+    }
+
+    public static function enumOf(dict<string, VGullCheck> $variants): VGullCheck {
+        return ($x, $path) ==> {
+            if (!($x is dict<_, _>) || \\count($x) !== 1) {
+                self::fail($x, $path, 'one of '.\\implode(', ', \\array_keys($variants)));
+            }
+            foreach ($x as $k => $v) {
+                $variant = idx($variants, (string)$k);
+                if ($variant is null) {
+                    self::fail($x, $path, 'one of '.\\implode(', ', \\array_keys($variants)));
+                }
+                $variant($v, $path.'.'.$k);
+            }
+        };
+    }
+
+    public static function oneOf(keyset<arraykey> $values): VGullCheck {
+        return ($x, $path) ==> {
+            if (!($x is arraykey) || !\\array_key_exists($x, $values)) {
+                self::fail($x, $path, \\implode(' | ', $values));
+            }
+        };
+    }
+
+    public static function violated(string $path, string $expected): nothing {
+        throw new \\UnexpectedValueException($path.': expected '.$expected);
+    }
+
+    // Strings are measured in characters, like in Rust and Flow
+    public static function constrained(
+        VGullCheck $inner,
+        shape(
+            ?'min_length' => int,
+            ?'max_length' => int,
+            ?'pattern' => string,
+            ?'minimum' => num,
+            ?'maximum' => num,
+        ) $c,
+    ): VGullCheck {
+        return ($x, $path) ==> {
+            $inner($x, $path);
+            if ($x is null) {
+                return;
+            }
+            $length = $x is string
+                ? \\mb_strlen($x, 'UTF-8')
+                : ($x is Container<_> ? \\count($x) : 0);
+            $min_length = Shapes::idx($c, 'min_length');
+            if ($min_length is nonnull && $length < $min_length) {
+                self::violated($path, 'length of at least '.$min_length);
+            }
+            $max_length = Shapes::idx($c, 'max_length');
+            if ($max_length is nonnull && $length > $max_length) {
+                self::violated($path, 'length of at most '.$max_length);
+            }
+            $pattern = Shapes::idx($c, 'pattern');
+            if (
+                $pattern is nonnull &&
+                $x is string &&
+                \\preg_match('#'.\\str_replace('#', '\\\\#', $pattern).'#u', $x) !== 1
+            ) {
+                self::violated($path, 'a match of '.$pattern);
+            }
+            if ($x is num) {
+                $minimum = Shapes::idx($c, 'minimum');
+                if ($minimum is nonnull && $x < $minimum) {
+                    self::violated($path, 'at least '.$minimum);
+                }
+                $maximum = Shapes::idx($c, 'maximum');
+                if ($maximum is nonnull && $x > $maximum) {
+                    self::violated($path, 'at most '.$maximum);
+                }
+            }
+        };
+    }
+}
+
+"#
+    );
+    Ok(())
+}
+
+/// Generated code up to the validator helpers, which `helpers_test` covers
+fn without_helpers(code: &str, start: &str) -> String {
+    code.split(start).next().unwrap_or_default().to_string()
+}
+
+/// The validator helpers emitted after the generated types
+fn helpers(code: &str, start: &str) -> String {
+    code.find(start)
+        .map(|i| code[i..].to_string())
+        .unwrap_or_default()
+}
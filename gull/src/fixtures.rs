@@ -0,0 +1,394 @@
+/*!
+Example JSON values for declarations. Values are produced the same way serde
+would serialize the generated Rust types, so they can be used as golden files
+or docs for any of the target languages.
+
+```
+use gull::prelude::*;
+
+let declarations = parse_schema("struct Point { x: i32, label?: string }").unwrap();
+
+assert_eq!(
+    declarations.fixture("Point", FixtureKind::Minimal).unwrap().to_string(),
+    r#"{"x":0}"#
+);
+assert_eq!(
+    declarations.fixture("Point", FixtureKind::Maximal).unwrap().to_string(),
+    r#"{"x":2147483647,"label":"example"}"#
+);
+```
+*/
+
+use crate::definitions::*;
+use crate::generics::{bind_generics, resolve_generic, Binding};
+use anyhow::{Context, Result};
+use serde_json::{Map, Value};
+
+// Nested references deeper than this only produce minimal values (empty
+// collections, `null` options) so that recursive types terminate
+const MAX_DEPTH: usize = 4;
+
+// Types that can't produce a value without referencing themselves (e.g. a
+// required recursive field) would otherwise recurse forever
+const MAX_REQUIRED_DEPTH: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FixtureKind {
+    /// Zero values, empty collections, `null` options and no optional
+    /// fields. Enums use their first variant.
+    Minimal,
+    /// Every optional field filled, one item in every collection and the
+    /// largest values that are safe in every language. Enums use their last
+    /// variant.
+    Maximal,
+    /// Random values that are always the same for the same seed
+    Random { seed: u64 },
+}
+
+pub struct FixtureGenerator<'a> {
+    declarations: &'a Declarations,
+    kind: FixtureKind,
+    rng: Rng,
+    depth: usize,
+}
+
+impl<'a> FixtureGenerator<'a> {
+    pub fn new(declarations: &'a Declarations, kind: FixtureKind) -> Self {
+        let seed = match kind {
+            FixtureKind::Random { seed } => seed,
+            _ => 0,
+        };
+        Self {
+            declarations,
+            kind,
+            rng: Rng::new(seed),
+            depth: 0,
+        }
+    }
+
+    /// Generate a value of a declared type. Generic declarations can only be
+    /// generated through a reference that provides generic params.
+    pub fn generate(&mut self, name: &str) -> Result<Value> {
+        self.gen_reference(&TReference::new(name.to_string()), &[])
+            .with_context(|| format!("Failed to generate a value of `{}`", name))
+    }
+
+    pub fn generate_declaration(&mut self, declaration: &TypeDeclaration) -> Result<Value> {
+        let required = declaration.generic_params.iter().find_map(|g| match g {
+            TGeneric::TDefinition {
+                name,
+                default: None,
+                ..
+            } => Some(name),
+            _ => None,
+        });
+        if let Some(name) = required {
+            anyhow::bail!(
+                "Can't generate a value of `{}` without a type for its generic param `{}`",
+                declaration.name,
+                name
+            );
+        }
+        let reference = TReference::new(declaration.name.clone());
+        let generics = bind_generics(declaration, &reference, &[])?;
+        self.gen_type_declaration(declaration, &generics)
+            .with_context(|| format!("Failed to generate a value of `{}`", declaration.name))
+    }
+
+    // Past `MAX_DEPTH` everything is generated as minimal
+    fn kind(&self) -> FixtureKind {
+        if self.depth > MAX_DEPTH {
+            FixtureKind::Minimal
+        } else {
+            self.kind
+        }
+    }
+
+    // Index of the enum variant to use
+    fn pick(&mut self, len: usize) -> usize {
+        match self.kind() {
+            FixtureKind::Minimal => 0,
+            FixtureKind::Maximal => len - 1,
+            FixtureKind::Random { .. } => self.rng.below(len),
+        }
+    }
+
+    fn gen_reference(&mut self, r: &TReference, generics: &[Binding]) -> Result<Value> {
+        let declaration = self
+            .declarations
+            .declarations
+            .iter()
+            .find(|d| d.name == r.get_name())
+            .with_context(|| format!("Unknown type `{}`", r.get_name()))?;
+
+        if self.depth > MAX_REQUIRED_DEPTH {
+            anyhow::bail!(
+                "`{}` can't be generated without infinitely referencing itself",
+                r.get_name()
+            );
+        }
+
+        let bindings = bind_generics(declaration, r, generics)?;
+
+        self.depth += 1;
+        let value = self.gen_type_declaration(declaration, &bindings);
+        self.depth -= 1;
+        value
+    }
+
+    // Fields of included structs come first, like in the merged struct
+    fn gen_type_declaration(
+        &mut self,
+        declaration: &TypeDeclaration,
+        generics: &[Binding],
+    ) -> Result<Value> {
+        let mut object = Map::new();
+        for r in declaration.includes() {
+            match self.gen_reference(r, generics)? {
+                Value::Object(fields) => object.extend(fields),
+                _ => anyhow::bail!("`{}` can only include structs", declaration.name),
+            }
+        }
+        match self.gen_declaration(&declaration.value, generics)? {
+            Value::Object(fields) if !object.is_empty() => {
+                object.extend(fields);
+                Ok(Value::Object(object))
+            }
+            value => Ok(value),
+        }
+    }
+
+    fn gen_declaration(&mut self, value: &DeclarationValue, generics: &[Binding]) -> Result<Value> {
+        match value {
+            DeclarationValue::TPrimitive(p) => self.gen_primitive(p, generics),
+            DeclarationValue::TMap(m) => self.gen_map(m, generics),
+            DeclarationValue::TVec(v) => self.gen_vec(v, generics),
+            DeclarationValue::TOption(o) => self.gen_option(o, generics),
+            DeclarationValue::TTuple(t) => self.gen_tuple(t, generics),
+            DeclarationValue::TStruct(s) => self.gen_struct(s, generics),
+            DeclarationValue::TEnum(e) if e.variants.is_empty() => {
+                anyhow::bail!("Enum has no variants")
+            }
+            DeclarationValue::TSimpleEnum(e) if e.variants.is_empty() => {
+                anyhow::bail!("Enum has no variants")
+            }
+            // Enums are externally tagged, e.g. `{"Variant": {...}}`
+            DeclarationValue::TEnum(e) => {
+                let variant = &e.variants[self.pick(e.variants.len())];
+                let value = match &variant.variant_type {
+                    EnumVariantType::TStruct(s) => self.gen_struct(s, generics)?,
+                    EnumVariantType::TPrimitive(p) => self.gen_primitive(p, generics)?,
+                };
+                let mut object = Map::new();
+                object.insert(variant.name.to_string(), value);
+                Ok(Value::Object(object))
+            }
+            DeclarationValue::TSimpleEnum(e) => {
+                let variant = &e.variants[self.pick(e.variants.len())];
+                Ok(match variant.discriminant {
+                    Some(d) => Value::from(d),
+                    None => Value::String(variant.value.to_string()),
+                })
+            }
+            DeclarationValue::Docs | DeclarationValue::CodeBlock(_) => {
+                anyhow::bail!("Docs and code blocks don't have values")
+            }
+        }
+    }
+
+    fn gen_primitive(&mut self, p: &TPrimitive, generics: &[Binding]) -> Result<Value> {
+        // Stay within the range of integers that are safe in JS
+        const MAX_SAFE_INT: i64 = (1 << 53) - 1;
+
+        Ok(match p {
+            TPrimitive::String => Value::String(self.gen_string()),
+            TPrimitive::Ti64 => self.gen_int(-MAX_SAFE_INT, MAX_SAFE_INT),
+            TPrimitive::Ti32 => self.gen_int(i32::MIN as i64, i32::MAX as i64),
+            TPrimitive::Tu32 => self.gen_int(0, u32::MAX as i64),
+            TPrimitive::Tusize => self.gen_int(0, MAX_SAFE_INT),
+            // Quarters are represented exactly in every language
+            TPrimitive::Tf64 => Value::from(match self.kind() {
+                FixtureKind::Minimal => 0.0,
+                FixtureKind::Maximal => 1000.5,
+                FixtureKind::Random { .. } => self.rng.range(-4000, 4000) as f64 / 4.0,
+            }),
+            TPrimitive::Tbool => Value::Bool(match self.kind() {
+                FixtureKind::Minimal => false,
+                FixtureKind::Maximal => true,
+                FixtureKind::Random { .. } => self.rng.below(2) == 1,
+            }),
+            TPrimitive::TVec(v) => self.gen_vec(v, generics)?,
+            TPrimitive::TMap(m) => self.gen_map(m, generics)?,
+            TPrimitive::TOption(o) => self.gen_option(o, generics)?,
+            TPrimitive::TDifferentPerLanguage { rust, .. } => self.gen_primitive(rust, generics)?,
+            TPrimitive::TReference(r) => self.gen_reference(r, generics)?,
+            TPrimitive::TGeneric(g) => match resolve_generic(g, generics) {
+                TGeneric::TReference(r) => self.gen_reference(&r, generics)?,
+                TGeneric::TPrimitive(p) => self.gen_primitive(&p, generics)?,
+                TGeneric::TDefinition { name, .. } => {
+                    anyhow::bail!("Generic param `{}` is not bound to a type", name)
+                }
+            },
+            TPrimitive::THardcoded(s) => {
+                anyhow::bail!("Can't generate a value of a hardcoded type `{}`", s)
+            }
+        })
+    }
+
+    fn gen_int(&mut self, min: i64, max: i64) -> Value {
+        Value::from(match self.kind() {
+            FixtureKind::Minimal => 0,
+            FixtureKind::Maximal => max,
+            FixtureKind::Random { .. } => self.rng.range(min, max),
+        })
+    }
+
+    fn gen_string(&mut self) -> String {
+        match self.kind() {
+            FixtureKind::Minimal => String::new(),
+            FixtureKind::Maximal => "example".to_string(),
+            FixtureKind::Random { .. } => {
+                let len = self.rng.below(8);
+                (0..len)
+                    .map(|_| (b'a' + self.rng.below(26) as u8) as char)
+                    .collect()
+            }
+        }
+    }
+
+    fn gen_len(&mut self) -> usize {
+        match self.kind() {
+            FixtureKind::Minimal => 0,
+            FixtureKind::Maximal => 1,
+            FixtureKind::Random { .. } => self.rng.below(4),
+        }
+    }
+
+    fn gen_vec(&mut self, v: &TVec, generics: &[Binding]) -> Result<Value> {
+        let TVec::TPrimitive(p) = v;
+        let len = self.gen_len();
+        let items = (0..len)
+            .map(|_| self.gen_primitive(p, generics))
+            .collect::<Result<_>>()?;
+        Ok(Value::Array(items))
+    }
+
+    fn gen_set(&mut self, s: &TSet, generics: &[Binding]) -> Result<Value> {
+        let TSet::TPrimitive(p) = s;
+        let len = self.gen_len();
+        let mut items = vec![];
+        for _ in 0..len {
+            let item = self.gen_primitive(p, generics)?;
+            if !items.contains(&item) {
+                items.push(item);
+            }
+        }
+        Ok(Value::Array(items))
+    }
+
+    fn gen_map(&mut self, m: &TMap, generics: &[Binding]) -> Result<Value> {
+        let len = self.gen_len();
+        let mut object = Map::new();
+        for _ in 0..len {
+            // JSON object keys are always strings, serde writes integer keys
+            // as their decimal representation
+            let key = match self.gen_primitive(&m.key, generics)? {
+                Value::String(s) => s,
+                other => other.to_string(),
+            };
+            let value = match &m.value {
+                TMapValue::TPrimitive(p) => self.gen_primitive(p, generics)?,
+                TMapValue::TSet(s) => self.gen_set(s, generics)?,
+            };
+            object.insert(key, value);
+        }
+        Ok(Value::Object(object))
+    }
+
+    fn is_some(&mut self) -> bool {
+        match self.kind() {
+            FixtureKind::Minimal => false,
+            FixtureKind::Maximal => true,
+            FixtureKind::Random { .. } => self.rng.below(2) == 1,
+        }
+    }
+
+    fn gen_option(&mut self, o: &TOption, generics: &[Binding]) -> Result<Value> {
+        if !self.is_some() {
+            return Ok(Value::Null);
+        }
+        match o {
+            TOption::TPrimitive(p) => self.gen_primitive(p, generics),
+            TOption::TMap(m) => self.gen_map(m, generics),
+            TOption::TVec(v) => self.gen_vec(v, generics),
+            TOption::TSet(s) => self.gen_set(s, generics),
+            TOption::TTuple(t) => self.gen_tuple(t, generics),
+        }
+    }
+
+    fn gen_tuple(&mut self, t: &TTuple, generics: &[Binding]) -> Result<Value> {
+        let items = t
+            .items
+            .iter()
+            .map(|item| match item {
+                TupleItem::TPrimitive(p) => self.gen_primitive(p, generics),
+                TupleItem::TOption(o) => self.gen_option(o, generics),
+            })
+            .collect::<Result<_>>()?;
+        Ok(Value::Array(items))
+    }
+
+    fn gen_struct(&mut self, s: &TStruct, generics: &[Binding]) -> Result<Value> {
+        let mut object = Map::new();
+        for field in &s.fields {
+            let value = match &field.field_type {
+                StructFieldType::TMap(m) => self.gen_map(m, generics)?,
+                StructFieldType::TSet(s) => self.gen_set(s, generics)?,
+                StructFieldType::TPrimitive(p) => self.gen_primitive(p, generics)?,
+                StructFieldType::TTuple(t) => self.gen_tuple(t, generics)?,
+                StructFieldType::TVec(v) => self.gen_vec(v, generics)?,
+                StructFieldType::TOption(o) => match self.gen_option(o, generics)? {
+                    // optional fields can be left out altogether
+                    Value::Null if !self.is_some() => continue,
+                    value => value,
+                },
+            };
+            match value {
+                // fields of flattened structs are serialized inline
+                Value::Object(inner) if field.is_flattened() => object.extend(inner),
+                value => {
+                    object.insert(field.name.to_string(), value);
+                }
+            }
+        }
+        Ok(Value::Object(object))
+    }
+}
+
+// xorshift64*, good enough for fixtures and doesn't need any dependencies
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        // zero state would only ever produce zeros
+        Rng(seed ^ 0x9E37_79B9_7F4A_7C15)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    // [0, n)
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    // [min, max]
+    fn range(&mut self, min: i64, max: i64) -> i64 {
+        let span = (max as i128 - min as i128 + 1) as u128;
+        (min as i128 + (self.next() as u128 % span) as i128) as i64
+    }
+}
@@ -0,0 +1,131 @@
+/*!
+Flattened struct fields (`StructFieldConfig::Flatten`).
+
+A flattened field references another struct whose fields are serialized as if
+they were fields of the outer struct. Rust keeps the field and marks it with
+`#[serde(flatten)]` and Flow spreads the referenced object type. Everything
+that needs the actual JSON layout (Hack shapes, validators, fixtures) inlines
+the fields with `Flattener::fields`.
+*/
+
+use crate::definitions::*;
+use crate::generics::{bind_generics, walk_struct, Substitute};
+use crate::graph::DependencyGraph;
+use anyhow::{anyhow, bail, Result};
+use std::collections::{BTreeMap, BTreeSet};
+
+pub(crate) struct Flattener {
+    structs: BTreeMap<String, TypeDeclaration>,
+    // Structs that flatten themselves, directly or through other structs.
+    // They would be inlined forever.
+    cyclic: BTreeSet<String>,
+}
+
+impl Flattener {
+    pub(crate) fn new(declarations: &Declarations) -> Self {
+        let structs = declarations
+            .declarations
+            .iter()
+            .filter(|d| matches!(d.value, DeclarationValue::TStruct(_)))
+            .map(|d| (d.name.to_string(), d.clone()))
+            .collect::<BTreeMap<_, _>>();
+
+        let graph = DependencyGraph::from_references(structs.values().flat_map(|d| {
+            d.structs()
+                .into_iter()
+                .flat_map(|s| &s.fields)
+                .filter(|f| f.is_flattened())
+                .filter_map(move |f| match &f.field_type {
+                    StructFieldType::TPrimitive(TPrimitive::TReference(r)) => {
+                        Some((d.name.as_ref(), r.get_name()))
+                    }
+                    _ => None,
+                })
+        }));
+        let cyclic = graph
+            .cycles()
+            .into_iter()
+            .flatten()
+            .map(String::from)
+            .collect();
+
+        Flattener { structs, cyclic }
+    }
+
+    /// Fields of `s` as they appear in JSON. Flattened fields are replaced
+    /// by the fields of the struct they reference, with its generic params
+    /// bound to the ones the field uses.
+    pub(crate) fn fields(&self, s: &TStruct) -> Result<Vec<StructField>> {
+        if !s.fields.iter().any(StructField::is_flattened) {
+            return Ok(s.fields.clone());
+        }
+
+        let mut fields = vec![];
+        self.collect(s, &mut fields)?;
+
+        let mut names = BTreeSet::new();
+        for field in &fields {
+            if !names.insert(&field.name) {
+                bail!(
+                    "field `{}` is defined more than once after flattening",
+                    field.name
+                );
+            }
+        }
+        Ok(fields)
+    }
+
+    fn collect(&self, s: &TStruct, fields: &mut Vec<StructField>) -> Result<()> {
+        for field in &s.fields {
+            if !field.is_flattened() {
+                fields.push(field.clone());
+                continue;
+            }
+
+            let r = match &field.field_type {
+                StructFieldType::TPrimitive(TPrimitive::TReference(r)) => r,
+                _ => bail!(
+                    "flattened field `{}` must be a reference to a struct",
+                    field.name
+                ),
+            };
+            let declaration = self.structs.get(r.get_name()).ok_or_else(|| {
+                anyhow!(
+                    "flattened field `{}` references `{}`, which isn't a declared struct",
+                    field.name,
+                    r.get_name()
+                )
+            })?;
+            if self.cyclic.contains(r.get_name()) {
+                bail!(
+                    "flattened field `{}` references `{}`, which flattens itself",
+                    field.name,
+                    r.get_name()
+                );
+            }
+
+            let mut inner = match &declaration.value {
+                DeclarationValue::TStruct(inner) => inner.clone(),
+                _ => unreachable!("only structs are collected"),
+            };
+            let bindings = bind_generics(declaration, r, &[])?;
+            walk_struct(&mut Substitute(&bindings), &mut inner)?;
+            self.collect(&inner, fields)?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks that every flattened field references a struct and that no field
+/// name is used twice once they're inlined
+pub(crate) fn validate(declarations: &Declarations) -> Result<()> {
+    let flattener = Flattener::new(declarations);
+    for declaration in &declarations.declarations {
+        for s in declaration.structs() {
+            flattener
+                .fields(s)
+                .map_err(|e| anyhow!("{} in `{}`", e, declaration.name))?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,416 @@
+/*!
+Validation and instantiation of generic params.
+
+Every backend gets declarations with fully applied generic references:
+params that a reference omits are filled in with their defaults, because
+Hack doesn't support defaults at all. Rust and Flow still emit defaults in
+definitions so handwritten code can rely on them.
+*/
+
+use crate::definitions::*;
+use anyhow::{bail, Result};
+use std::collections::{BTreeMap, BTreeSet};
+
+// Defaults that reference the type they're defined on would expand forever
+const MAX_DEFAULT_DEPTH: usize = 32;
+
+/// Validate generics of all declarations and fill in omitted generic params
+/// of references with their defaults.
+pub(crate) fn instantiate(declarations: &Declarations) -> Result<Declarations> {
+    let mut params = BTreeMap::new();
+    for declaration in &declarations.declarations {
+        validate_params(declaration)?;
+        params.insert(
+            declaration.name.to_string(),
+            declaration.generic_params.clone(),
+        );
+    }
+
+    let mut result = declarations.clone();
+    for declaration in &mut result.declarations {
+        let mut defaults = Instantiate {
+            params: &params,
+            declaration: declaration.name.to_string(),
+            scope: BTreeSet::new(),
+            depth: 0,
+        };
+        // Defaults can't use other params, so they're checked with an empty scope
+        for param in &mut declaration.generic_params {
+            if let TGeneric::TDefinition {
+                default: Some(default),
+                ..
+            } = param
+            {
+                walk_primitive(&mut defaults, default)?;
+            }
+        }
+
+        defaults.scope = declaration
+            .generic_params
+            .iter()
+            .filter_map(param_name)
+            .map(String::from)
+            .collect();
+        walk_declaration(&mut defaults, &mut declaration.value)?;
+    }
+
+    Ok(result)
+}
+
+fn validate_params(declaration: &TypeDeclaration) -> Result<()> {
+    let name = &declaration.name;
+    if let DeclarationValue::TSimpleEnum(e) = &declaration.value {
+        if !declaration.generic_params.is_empty() {
+            bail!("simple enum `{}` can't have generic params", name);
+        }
+        validate_discriminants(name, e)?;
+    }
+
+    let mut names = BTreeSet::new();
+    let mut after_default = None;
+    for param in &declaration.generic_params {
+        let (param, default) = match param {
+            TGeneric::TDefinition { name, default, .. } => (name, default),
+            _ => bail!(
+                "generic params of `{}` must be generic param definitions",
+                name
+            ),
+        };
+        if !names.insert(param) {
+            bail!(
+                "generic param `{}` of `{}` is defined more than once",
+                param,
+                name
+            );
+        }
+        match (default, after_default) {
+            (Some(_), _) => after_default = Some(param),
+            (None, Some(previous)) => bail!(
+                "generic param `{}` of `{}` needs a default because it goes after `{}` that has one",
+                param,
+                name,
+                previous
+            ),
+            (None, None) => {}
+        }
+    }
+    Ok(())
+}
+
+fn validate_discriminants(name: &str, e: &TSimpleEnum) -> Result<()> {
+    let with_discriminant = e.variants.iter().filter(|v| v.discriminant.is_some());
+    if with_discriminant.clone().count() == 0 {
+        return Ok(());
+    }
+    if !e.is_integer() {
+        bail!(
+            "either every variant of simple enum `{}` needs a discriminant or none",
+            name
+        );
+    }
+
+    let mut discriminants = BTreeSet::new();
+    for variant in with_discriminant {
+        if !discriminants.insert(variant.discriminant) {
+            bail!(
+                "discriminant {} is used by more than one variant of `{}`",
+                variant.wire_value(),
+                name
+            );
+        }
+    }
+    Ok(())
+}
+
+fn param_name(g: &TGeneric) -> Option<&str> {
+    match g {
+        TGeneric::TDefinition { name, .. } => Some(name),
+        _ => None,
+    }
+}
+
+fn param_default(g: &TGeneric) -> Option<&TPrimitive> {
+    match g {
+        TGeneric::TDefinition {
+            default: Some(default),
+            ..
+        } => Some(default),
+        _ => None,
+    }
+}
+
+struct Instantiate<'a> {
+    params: &'a BTreeMap<String, Vec<TGeneric>>,
+    // declaration that is being instantiated, for error messages
+    declaration: String,
+    // generic params that can be used
+    scope: BTreeSet<String>,
+    depth: usize,
+}
+
+impl<'a> GenericsVisitor for Instantiate<'a> {
+    fn reference(&mut self, r: &mut TReference) -> Result<()> {
+        // Declarations added separately (e.g. derived ones) can't be checked
+        let params = match self.params.get(r.get_name()) {
+            Some(params) => params,
+            None => return walk_reference(self, r),
+        };
+
+        let required = params
+            .iter()
+            .take_while(|p| param_default(p).is_none())
+            .count();
+        let found = r.generic_params.len();
+        if found < required || found > params.len() {
+            let expected = if required == params.len() {
+                required.to_string()
+            } else {
+                format!("{} to {}", required, params.len())
+            };
+            bail!(
+                "`{}` expects {} generic argument(s), found {} in `{}`",
+                r.get_name(),
+                expected,
+                found,
+                self.declaration
+            );
+        }
+
+        if found < params.len() {
+            if self.depth > MAX_DEFAULT_DEPTH {
+                bail!(
+                    "defaults of generic params of `{}` reference it infinitely",
+                    r.get_name()
+                );
+            }
+            for param in &params[found..] {
+                if let Some(default) = param_default(param) {
+                    r.generic_params.push(TGeneric::from(default.clone()));
+                }
+            }
+            // Defaults are instantiated within the scope of the reference
+            let scope = std::mem::take(&mut self.scope);
+            self.depth += 1;
+            for g in &mut r.generic_params[found..] {
+                walk_generic(self, g)?;
+            }
+            self.depth -= 1;
+            self.scope = scope;
+        }
+
+        for g in &mut r.generic_params[..found] {
+            walk_generic(self, g)?;
+        }
+        Ok(())
+    }
+
+    fn param(&mut self, g: &mut TGeneric) -> Result<()> {
+        if let Some(name) = param_name(g) {
+            if !self.scope.contains(name) {
+                bail!("unknown generic param `{}` in `{}`", name, self.declaration);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Visits generic param uses, references and maps within a type. Every
+/// reference is visited before its generic params and every map before its
+/// key and value.
+pub(crate) trait GenericsVisitor {
+    fn reference(&mut self, r: &mut TReference) -> Result<()> {
+        walk_reference(self, r)
+    }
+
+    fn map(&mut self, m: &mut TMap) -> Result<()> {
+        walk_map(self, m)
+    }
+
+    /// `TGeneric::TDefinition` used as a type
+    fn param(&mut self, g: &mut TGeneric) -> Result<()>;
+}
+
+pub(crate) fn walk_declaration<V: GenericsVisitor + ?Sized>(
+    v: &mut V,
+    value: &mut DeclarationValue,
+) -> Result<()> {
+    match value {
+        DeclarationValue::TPrimitive(p) => walk_primitive(v, p),
+        DeclarationValue::TMap(m) => v.map(m),
+        DeclarationValue::TVec(vec) => {
+            let TVec::TPrimitive(p) = vec.as_mut();
+            walk_primitive(v, p)
+        }
+        DeclarationValue::TOption(o) => walk_option(v, o),
+        DeclarationValue::TTuple(t) => walk_tuple(v, t),
+        DeclarationValue::TStruct(s) => walk_struct(v, s),
+        DeclarationValue::TEnum(e) => {
+            for variant in &mut e.variants {
+                match &mut variant.variant_type {
+                    EnumVariantType::TStruct(s) => walk_struct(v, s)?,
+                    EnumVariantType::TPrimitive(p) => walk_primitive(v, p)?,
+                }
+            }
+            Ok(())
+        }
+        DeclarationValue::TSimpleEnum(_)
+        | DeclarationValue::Docs
+        | DeclarationValue::CodeBlock(_) => Ok(()),
+    }
+}
+
+pub(crate) fn walk_struct<V: GenericsVisitor + ?Sized>(v: &mut V, s: &mut TStruct) -> Result<()> {
+    for field in &mut s.fields {
+        match &mut field.field_type {
+            StructFieldType::TMap(m) => v.map(m)?,
+            StructFieldType::TSet(TSet::TPrimitive(p)) => walk_primitive(v, p)?,
+            StructFieldType::TOption(o) => walk_option(v, o)?,
+            StructFieldType::TPrimitive(p) => walk_primitive(v, p)?,
+            StructFieldType::TTuple(t) => walk_tuple(v, t)?,
+            StructFieldType::TVec(TVec::TPrimitive(p)) => walk_primitive(v, p)?,
+        }
+    }
+    Ok(())
+}
+
+// Generic param name bound to the type it was instantiated with
+pub(crate) struct Binding {
+    pub(crate) name: String,
+    pub(crate) value: TGeneric,
+}
+
+// Bind generic params of a declaration to the params of the reference to it
+pub(crate) fn bind_generics(
+    declaration: &TypeDeclaration,
+    r: &TReference,
+    generics: &[Binding],
+) -> Result<Vec<Binding>> {
+    let mut bindings = vec![];
+    for (i, param) in declaration.generic_params.iter().enumerate() {
+        if let TGeneric::TDefinition { name, default, .. } = param {
+            let value = match (r.generic_params.get(i), default) {
+                (Some(value), _) => resolve_generic(value, generics),
+                // Defaults never reference other params
+                (None, Some(default)) => TGeneric::from(default.as_ref().clone()),
+                (None, None) => anyhow::bail!(
+                    "Generic param `{}` of `{}` must be provided",
+                    name,
+                    r.get_name()
+                ),
+            };
+            bindings.push(Binding {
+                name: name.to_string(),
+                value,
+            });
+        }
+    }
+    Ok(bindings)
+}
+
+pub(crate) fn resolve_generic(g: &TGeneric, generics: &[Binding]) -> TGeneric {
+    match g {
+        TGeneric::TDefinition { name, .. } => generics
+            .iter()
+            .find(|b| b.name == *name)
+            .map_or_else(|| g.clone(), |b| b.value.clone()),
+        TGeneric::TReference(r) => {
+            let mut r = r.clone();
+            r.generic_params = r
+                .generic_params
+                .iter()
+                .map(|g| resolve_generic(g, generics))
+                .collect();
+            TGeneric::TReference(r)
+        }
+        TGeneric::TPrimitive(p) => {
+            let mut p = p.as_ref().clone();
+            walk_primitive(&mut Substitute(generics), &mut p)
+                .expect("substituting generic params never fails");
+            TGeneric::from(p)
+        }
+    }
+}
+
+// Replaces generic params with the types they're bound to
+pub(crate) struct Substitute<'a>(pub(crate) &'a [Binding]);
+
+impl<'a> GenericsVisitor for Substitute<'a> {
+    fn param(&mut self, g: &mut TGeneric) -> Result<()> {
+        *g = resolve_generic(g, self.0);
+        Ok(())
+    }
+}
+
+pub(crate) fn walk_primitive<V: GenericsVisitor + ?Sized>(
+    v: &mut V,
+    p: &mut TPrimitive,
+) -> Result<()> {
+    match p {
+        TPrimitive::TGeneric(g) => walk_generic(v, g),
+        TPrimitive::TReference(r) => v.reference(r),
+        TPrimitive::TDifferentPerLanguage { hack, rust, flow } => {
+            walk_primitive(v, hack)?;
+            walk_primitive(v, rust)?;
+            walk_primitive(v, flow)
+        }
+        TPrimitive::TVec(vec) => {
+            let TVec::TPrimitive(p) = vec.as_mut();
+            walk_primitive(v, p)
+        }
+        TPrimitive::TMap(m) => v.map(m),
+        TPrimitive::TOption(o) => walk_option(v, o),
+        TPrimitive::String
+        | TPrimitive::Ti64
+        | TPrimitive::Tf64
+        | TPrimitive::Ti32
+        | TPrimitive::Tu32
+        | TPrimitive::Tusize
+        | TPrimitive::Tbool
+        | TPrimitive::THardcoded(_) => Ok(()),
+    }
+}
+
+pub(crate) fn walk_generic<V: GenericsVisitor + ?Sized>(v: &mut V, g: &mut TGeneric) -> Result<()> {
+    match g {
+        TGeneric::TDefinition { .. } => v.param(g),
+        TGeneric::TReference(r) => v.reference(r),
+        TGeneric::TPrimitive(p) => walk_primitive(v, p),
+    }
+}
+
+pub(crate) fn walk_reference<V: GenericsVisitor + ?Sized>(
+    v: &mut V,
+    r: &mut TReference,
+) -> Result<()> {
+    for g in &mut r.generic_params {
+        walk_generic(v, g)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn walk_map<V: GenericsVisitor + ?Sized>(v: &mut V, m: &mut TMap) -> Result<()> {
+    walk_primitive(v, &mut m.key)?;
+    match &mut m.value {
+        TMapValue::TPrimitive(p) | TMapValue::TSet(TSet::TPrimitive(p)) => walk_primitive(v, p),
+    }
+}
+
+fn walk_option<V: GenericsVisitor + ?Sized>(v: &mut V, o: &mut TOption) -> Result<()> {
+    match o {
+        TOption::TPrimitive(p)
+        | TOption::TSet(TSet::TPrimitive(p))
+        | TOption::TVec(TVec::TPrimitive(p)) => walk_primitive(v, p),
+        TOption::TMap(m) => v.map(m),
+        TOption::TTuple(t) => walk_tuple(v, t),
+    }
+}
+
+fn walk_tuple<V: GenericsVisitor + ?Sized>(v: &mut V, t: &mut TTuple) -> Result<()> {
+    for item in &mut t.items {
+        match item {
+            TupleItem::TPrimitive(p) => walk_primitive(v, p)?,
+            TupleItem::TOption(o) => walk_option(v, o)?,
+        }
+    }
+    Ok(())
+}
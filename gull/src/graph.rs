@@ -0,0 +1,508 @@
+/*!
+Dependency graph of declarations built from their `TReference`s.
+
+An edge `A -> B` means that `A` references `B`. References are either
+**direct** (`B` is stored by value inside of `A`, including `option<B>`) or
+**indirect** (`B` is stored in a heap allocated collection, e.g. `vec<B>`).
+A cycle of direct references is a type of infinite size, which Rust can only
+represent with `Box` indirection. Rust codegen boxes every direct reference
+between types of the same cycle.
+
+```
+use gull::prelude::*;
+
+let declarations = parse_schema("
+    struct Tree { children: vec<Tree>, parent: option<Node> }
+    struct Node { tree: Tree }
+    type ID = i64;
+").unwrap();
+let graph = DependencyGraph::new(&declarations);
+
+assert_eq!(graph.topological_order(), vec!["ID", "Node", "Tree"]);
+assert_eq!(graph.boxed_cycles(), vec![vec!["Node", "Tree"]]);
+```
+*/
+
+use crate::definitions::declarations::DeclarationsConfig;
+use crate::definitions::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Order in which codegen emits declarations. Docs and code blocks always
+/// keep their insertion order and go before all types when types are
+/// reordered.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum EmitOrder {
+    /// Same order declarations were added in
+    Insertion,
+    /// Dependencies before the types that use them. Independent types and
+    /// types within a cycle are sorted alphabetically, so the order doesn't
+    /// depend on insertion order.
+    Topological,
+    Alphabetical,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EdgeKind {
+    /// Referenced type is stored by value
+    Direct,
+    /// Referenced type is stored in a collection
+    Indirect,
+}
+
+#[derive(Debug)]
+pub struct DependencyGraph {
+    // Names of type declarations in insertion order
+    nodes: Vec<String>,
+    // from -> to -> kind. If a type is referenced both ways, it's direct.
+    edges: BTreeMap<String, BTreeMap<String, EdgeKind>>,
+}
+
+impl DependencyGraph {
+    pub fn new(declarations: &Declarations) -> Self {
+        let types = declarations
+            .declarations
+            .iter()
+            .filter(|d| is_type(d))
+            .collect::<Vec<_>>();
+
+        // Generic params used by value, e.g. `T` in `struct Node<T> { value: T }`.
+        // Generic args only make a direct reference in those positions.
+        let mut direct_params = BTreeMap::new();
+        for declaration in &types {
+            let mut walker = Walker::new(&direct_params);
+            walker.declaration(&declaration.value);
+            walker.includes(declaration);
+            let direct = declaration
+                .generic_params
+                .iter()
+                .map(|g| match g {
+                    TGeneric::TDefinition { name, .. } => walker.params.contains(name.as_ref()),
+                    TGeneric::TReference(_) | TGeneric::TPrimitive(_) => true,
+                })
+                .collect::<Vec<_>>();
+            direct_params.insert(declaration.name.to_string(), direct);
+        }
+
+        let nodes = types.iter().map(|d| d.name.to_string()).collect::<Vec<_>>();
+        let mut edges = BTreeMap::new();
+        for declaration in &types {
+            let mut walker = Walker::new(&direct_params);
+            walker.declaration(&declaration.value);
+            walker.includes(declaration);
+            // References to types declared elsewhere are not part of the graph
+            walker.edges.retain(|to, _| nodes.contains(to));
+            edges.insert(declaration.name.to_string(), walker.edges);
+        }
+
+        Self { nodes, edges }
+    }
+
+    /// Graph of only the given `(from, to)` references, all of them direct.
+    /// Used to find cycles of a single kind of reference, e.g. structs that
+    /// include each other.
+    pub(crate) fn from_references<'a>(
+        references: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) -> Self {
+        let mut nodes = vec![];
+        let mut edges: BTreeMap<String, BTreeMap<String, EdgeKind>> = BTreeMap::new();
+        for (from, to) in references {
+            for name in &[from, to] {
+                if !nodes.iter().any(|n| n == name) {
+                    nodes.push(name.to_string());
+                }
+            }
+            edges
+                .entry(from.to_string())
+                .or_default()
+                .insert(to.to_string(), EdgeKind::Direct);
+        }
+        Self { nodes, edges }
+    }
+
+    /// Types referenced by the given type
+    pub fn dependencies(&self, name: &str) -> Vec<(&str, EdgeKind)> {
+        self.edges
+            .get(name)
+            .map(|edges| {
+                edges
+                    .iter()
+                    .map(|(to, kind)| (to.as_str(), *kind))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn alphabetical_order(&self) -> Vec<&str> {
+        let mut names = self.nodes.iter().map(|n| n.as_str()).collect::<Vec<_>>();
+        names.sort_unstable();
+        names
+    }
+
+    /// Every type comes after the types it references, except for references
+    /// within a cycle
+    pub fn topological_order(&self) -> Vec<&str> {
+        let components = self.components(|_| true);
+
+        let mut component_of = BTreeMap::new();
+        for (i, component) in components.iter().enumerate() {
+            for name in component {
+                component_of.insert(*name, i);
+            }
+        }
+
+        // Components each component depends on
+        let mut pending = components
+            .iter()
+            .enumerate()
+            .map(|(i, component)| {
+                component
+                    .iter()
+                    .flat_map(|name| self.dependencies(name))
+                    .map(|(to, _)| component_of[to])
+                    .filter(|c| *c != i)
+                    .collect::<BTreeSet<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        // Ready components ordered by their first name
+        let mut ready = BTreeSet::new();
+        for (i, deps) in pending.iter().enumerate() {
+            if deps.is_empty() {
+                ready.insert((components[i][0], i));
+            }
+        }
+
+        let mut order = vec![];
+        while let Some(next) = ready.iter().next().copied() {
+            ready.remove(&next);
+            let (_, done) = next;
+            order.extend(components[done].iter().copied());
+            for (i, deps) in pending.iter_mut().enumerate() {
+                if deps.remove(&done) && deps.is_empty() {
+                    ready.insert((components[i][0], i));
+                }
+            }
+        }
+        order
+    }
+
+    /// Groups of types that reference each other, including types that
+    /// reference themselves
+    pub fn cycles(&self) -> Vec<Vec<&str>> {
+        self.cycles_of(|_| true)
+    }
+
+    /// Cycles of direct references. These types have infinite size unless at
+    /// least one of the references in the cycle is boxed in Rust.
+    pub fn boxed_cycles(&self) -> Vec<Vec<&str>> {
+        self.cycles_of(|kind| kind == EdgeKind::Direct)
+    }
+
+    /// Graphviz DOT representation. Indirect references are dashed and types
+    /// that need `Box` are red.
+    pub fn to_dot(&self) -> String {
+        let boxed = self
+            .boxed_cycles()
+            .into_iter()
+            .flatten()
+            .collect::<BTreeSet<_>>();
+
+        let mut dot = String::from("digraph gull {\n");
+        for name in self.alphabetical_order() {
+            if boxed.contains(name) {
+                dot.push_str(&format!("    \"{}\" [color=red];\n", name));
+            } else {
+                dot.push_str(&format!("    \"{}\";\n", name));
+            }
+        }
+        for (from, edges) in &self.edges {
+            for (to, kind) in edges {
+                let style = match kind {
+                    EdgeKind::Direct => "",
+                    EdgeKind::Indirect => " [style=dashed]",
+                };
+                dot.push_str(&format!("    \"{}\" -> \"{}\"{};\n", from, to, style));
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    fn cycles_of(&self, follow: impl Fn(EdgeKind) -> bool + Copy) -> Vec<Vec<&str>> {
+        let mut cycles = self
+            .components(follow)
+            .into_iter()
+            .filter(|c| {
+                c.len() > 1
+                    || self
+                        .dependencies(c[0])
+                        .iter()
+                        .any(|(to, kind)| *to == c[0] && follow(*kind))
+            })
+            .collect::<Vec<_>>();
+        cycles.sort();
+        cycles
+    }
+
+    // Strongly connected components (Tarjan), every component is sorted
+    fn components(&self, follow: impl Fn(EdgeKind) -> bool + Copy) -> Vec<Vec<&str>> {
+        struct State<'a> {
+            index: BTreeMap<&'a str, usize>,
+            low: BTreeMap<&'a str, usize>,
+            stack: Vec<&'a str>,
+            on_stack: BTreeSet<&'a str>,
+            components: Vec<Vec<&'a str>>,
+        }
+
+        fn visit<'a>(
+            graph: &'a DependencyGraph,
+            name: &'a str,
+            follow: impl Fn(EdgeKind) -> bool + Copy,
+            state: &mut State<'a>,
+        ) {
+            let index = state.index.len();
+            state.index.insert(name, index);
+            state.low.insert(name, index);
+            state.stack.push(name);
+            state.on_stack.insert(name);
+
+            for (to, kind) in graph.dependencies(name) {
+                if !follow(kind) {
+                    continue;
+                }
+                if !state.index.contains_key(to) {
+                    visit(graph, to, follow, state);
+                    let low = state.low[name].min(state.low[to]);
+                    state.low.insert(name, low);
+                } else if state.on_stack.contains(to) {
+                    let low = state.low[name].min(state.index[to]);
+                    state.low.insert(name, low);
+                }
+            }
+
+            if state.low[name] == state.index[name] {
+                let mut component = vec![];
+                while let Some(member) = state.stack.pop() {
+                    state.on_stack.remove(member);
+                    component.push(member);
+                    if member == name {
+                        break;
+                    }
+                }
+                component.sort_unstable();
+                state.components.push(component);
+            }
+        }
+
+        let mut state = State {
+            index: BTreeMap::new(),
+            low: BTreeMap::new(),
+            stack: vec![],
+            on_stack: BTreeSet::new(),
+            components: vec![],
+        };
+        for name in &self.nodes {
+            if !state.index.contains_key(name.as_str()) {
+                visit(self, name, follow, &mut state);
+            }
+        }
+        state.components
+    }
+}
+
+/// Declarations in the order set by `DeclarationsConfig::EmitOrder`
+pub(crate) fn emit_order(declarations: &Declarations) -> Vec<&TypeDeclaration> {
+    let order = declarations
+        .config
+        .iter()
+        .rev()
+        .find_map(|c| match c {
+            DeclarationsConfig::EmitOrder(order) => Some(*order),
+            _ => None,
+        })
+        .unwrap_or(EmitOrder::Insertion);
+
+    let graph = DependencyGraph::new(declarations);
+    let names = match order {
+        EmitOrder::Insertion => return declarations.declarations.iter().collect(),
+        EmitOrder::Topological => graph.topological_order(),
+        EmitOrder::Alphabetical => graph.alphabetical_order(),
+    };
+
+    let mut result = declarations
+        .declarations
+        .iter()
+        .filter(|d| !is_type(d))
+        .collect::<Vec<_>>();
+    for name in names {
+        result.extend(declarations.declarations.iter().find(|d| d.name == name));
+    }
+    result
+}
+
+fn is_type(declaration: &TypeDeclaration) -> bool {
+    !matches!(
+        declaration.value,
+        DeclarationValue::Docs | DeclarationValue::CodeBlock(_)
+    )
+}
+
+// Collects references of a single declaration
+struct Walker<'a> {
+    direct_params: &'a BTreeMap<String, Vec<bool>>,
+    edges: BTreeMap<String, EdgeKind>,
+    // Generic params used by value
+    params: BTreeSet<String>,
+}
+
+impl<'a> Walker<'a> {
+    fn new(direct_params: &'a BTreeMap<String, Vec<bool>>) -> Self {
+        Self {
+            direct_params,
+            edges: BTreeMap::new(),
+            params: BTreeSet::new(),
+        }
+    }
+
+    fn kind(direct: bool) -> EdgeKind {
+        if direct {
+            EdgeKind::Direct
+        } else {
+            EdgeKind::Indirect
+        }
+    }
+
+    fn declaration(&mut self, value: &DeclarationValue) {
+        match value {
+            DeclarationValue::TPrimitive(p) => self.primitive(p, true),
+            DeclarationValue::TMap(m) => self.map(m),
+            DeclarationValue::TVec(v) => self.vec(v),
+            DeclarationValue::TOption(o) => self.option(o, true),
+            DeclarationValue::TTuple(t) => self.tuple(t, true),
+            DeclarationValue::TStruct(s) => self.fields(s),
+            DeclarationValue::TEnum(e) => {
+                for variant in &e.variants {
+                    match &variant.variant_type {
+                        EnumVariantType::TStruct(s) => self.fields(s),
+                        EnumVariantType::TPrimitive(p) => self.primitive(p, true),
+                    }
+                }
+            }
+            DeclarationValue::TSimpleEnum(_)
+            | DeclarationValue::Docs
+            | DeclarationValue::CodeBlock(_) => {}
+        }
+    }
+
+    fn fields(&mut self, s: &TStruct) {
+        for field in &s.fields {
+            match &field.field_type {
+                StructFieldType::TMap(m) => self.map(m),
+                StructFieldType::TSet(s) => self.set(s),
+                StructFieldType::TOption(o) => self.option(o, true),
+                StructFieldType::TPrimitive(p) => self.primitive(p, true),
+                StructFieldType::TTuple(t) => self.tuple(t, true),
+                StructFieldType::TVec(v) => self.vec(v),
+            }
+        }
+    }
+
+    fn primitive(&mut self, p: &TPrimitive, direct: bool) {
+        match p {
+            TPrimitive::TReference(r) => self.reference(r, direct),
+            TPrimitive::TGeneric(g) => self.generic(g, direct),
+            TPrimitive::TDifferentPerLanguage { hack, rust, flow } => {
+                self.primitive(hack, direct);
+                self.primitive(rust, direct);
+                self.primitive(flow, direct);
+            }
+            TPrimitive::TVec(v) => self.vec(v),
+            TPrimitive::TMap(m) => self.map(m),
+            TPrimitive::TOption(o) => self.option(o, direct),
+            TPrimitive::String
+            | TPrimitive::Ti64
+            | TPrimitive::Tf64
+            | TPrimitive::Ti32
+            | TPrimitive::Tu32
+            | TPrimitive::Tusize
+            | TPrimitive::Tbool
+            | TPrimitive::THardcoded(_) => {}
+        }
+    }
+
+    // Included structs are part of the including struct, like its fields
+    fn includes(&mut self, declaration: &TypeDeclaration) {
+        for r in declaration.includes() {
+            self.reference(r, true);
+        }
+    }
+
+    fn reference(&mut self, r: &TReference, direct: bool) {
+        let kind = self
+            .edges
+            .entry(r.get_name().to_string())
+            .or_insert(EdgeKind::Indirect);
+        *kind = (*kind).min(Self::kind(direct));
+
+        for (i, g) in r.generic_params.iter().enumerate() {
+            let param_direct = self
+                .direct_params
+                .get(r.get_name())
+                .and_then(|params| params.get(i))
+                .copied()
+                .unwrap_or(true);
+            self.generic(g, direct && param_direct);
+        }
+    }
+
+    fn generic(&mut self, g: &TGeneric, direct: bool) {
+        match g {
+            TGeneric::TDefinition { name, .. } => {
+                if direct {
+                    self.params.insert(name.to_string());
+                }
+            }
+            TGeneric::TReference(r) => self.reference(r, direct),
+            TGeneric::TPrimitive(p) => self.primitive(p, direct),
+        }
+    }
+
+    // Everything inside of collections is heap allocated
+    fn vec(&mut self, v: &TVec) {
+        let TVec::TPrimitive(p) = v;
+        self.primitive(p, false);
+    }
+
+    fn set(&mut self, s: &TSet) {
+        let TSet::TPrimitive(p) = s;
+        self.primitive(p, false);
+    }
+
+    fn map(&mut self, m: &TMap) {
+        self.primitive(&m.key, false);
+        match &m.value {
+            TMapValue::TPrimitive(p) => self.primitive(p, false),
+            TMapValue::TSet(s) => self.set(s),
+        }
+    }
+
+    fn option(&mut self, o: &TOption, direct: bool) {
+        match o {
+            TOption::TPrimitive(p) => self.primitive(p, direct),
+            TOption::TMap(m) => self.map(m),
+            TOption::TVec(v) => self.vec(v),
+            TOption::TSet(s) => self.set(s),
+            TOption::TTuple(t) => self.tuple(t, direct),
+        }
+    }
+
+    fn tuple(&mut self, t: &TTuple, direct: bool) {
+        for item in &t.items {
+            match item {
+                TupleItem::TPrimitive(p) => self.primitive(p, direct),
+                TupleItem::TOption(o) => self.option(o, direct),
+            }
+        }
+    }
+}
@@ -0,0 +1,112 @@
+/*!
+Structs that include other structs (`TypeDeclarationConfig::Include`).
+
+The fields of every included struct are merged into the including struct
+before codegen, in the order the structs are included and ahead of its own
+fields, so backends only ever see plain structs. Flow spreads the included
+types instead of repeating their fields (see `FlowCodegen`).
+*/
+
+use crate::definitions::*;
+use crate::generics::{bind_generics, walk_struct, Substitute};
+use crate::graph::DependencyGraph;
+use anyhow::{anyhow, bail, Result};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Declarations with the fields of included structs merged into the structs
+/// that include them. Fails on includes of anything but a struct, on cycles
+/// and on field names that are defined more than once.
+pub(crate) fn merge(declarations: &Declarations) -> Result<Declarations> {
+    if declarations
+        .declarations
+        .iter()
+        .all(|d| d.includes().is_empty())
+    {
+        return Ok(declarations.clone());
+    }
+
+    let by_name: BTreeMap<&str, &TypeDeclaration> = declarations
+        .declarations
+        .iter()
+        .map(|d| (d.name.as_ref(), d))
+        .collect();
+
+    // Structs that include themselves, directly or through other structs,
+    // would be merged forever
+    let graph = DependencyGraph::from_references(declarations.declarations.iter().flat_map(|d| {
+        d.includes()
+            .into_iter()
+            .map(move |r| (d.name.as_ref(), r.get_name()))
+    }));
+    let cyclic: BTreeSet<&str> = graph.cycles().into_iter().flatten().collect();
+
+    let mut result = declarations.clone();
+    for declaration in &mut result.declarations {
+        if declaration.includes().is_empty() {
+            continue;
+        }
+        if cyclic.contains(declaration.name.as_ref()) {
+            bail!("`{}` includes itself", declaration.name);
+        }
+        let fields = merged_fields(&by_name, &cyclic, declaration)?;
+        if let DeclarationValue::TStruct(s) = &mut declaration.value {
+            s.fields = fields;
+        }
+    }
+    Ok(result)
+}
+
+fn merged_fields(
+    by_name: &BTreeMap<&str, &TypeDeclaration>,
+    cyclic: &BTreeSet<&str>,
+    declaration: &TypeDeclaration,
+) -> Result<Vec<StructField>> {
+    let own = match &declaration.value {
+        DeclarationValue::TStruct(s) => s,
+        _ => bail!(
+            "only structs can include other structs, `{}` isn't one",
+            declaration.name
+        ),
+    };
+
+    let mut fields = vec![];
+    for r in declaration.includes() {
+        let included = by_name
+            .get(r.get_name())
+            .filter(|d| matches!(d.value, DeclarationValue::TStruct(_)))
+            .ok_or_else(|| {
+                anyhow!(
+                    "`{}` includes `{}`, which isn't a declared struct",
+                    declaration.name,
+                    r.get_name()
+                )
+            })?;
+        if cyclic.contains(r.get_name()) {
+            bail!(
+                "`{}` includes `{}`, which includes itself",
+                declaration.name,
+                r.get_name()
+            );
+        }
+
+        let mut inner = TStruct {
+            fields: merged_fields(by_name, cyclic, included)?,
+        };
+        let bindings = bind_generics(included, r, &[])?;
+        walk_struct(&mut Substitute(&bindings), &mut inner)?;
+        fields.extend(inner.fields);
+    }
+    fields.extend(own.fields.iter().cloned());
+
+    let mut names = BTreeSet::new();
+    for field in &fields {
+        if !names.insert(&field.name) {
+            bail!(
+                "field `{}` is defined more than once in `{}` and the structs it includes",
+                field.name,
+                declaration.name
+            );
+        }
+    }
+    Ok(fields)
+}
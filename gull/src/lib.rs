@@ -42,18 +42,19 @@ use k9::snapshot;
 
 let mut declarations = Declarations::new();
 
-declarations.add(TypeDeclaration {
-    name: "Frame",
-    docs: "Frame represents a tuple of an Timestamp (RFC3339) and an ID",
-    config: vec![TypeDeclarationConfig::RustAttribute("#[derive(Copy, Clone)]")],
-    generic_params: vec![],
-    value: DeclarationValue::TTuple(TTuple {
-        items: vec![
-            TupleItem::TPrimitive(TPrimitive::String),
-            TupleItem::TPrimitive(TPrimitive::Ti64),
-        ],
-    }),
-});
+declarations.add(
+    TypeDeclaration::new(
+        "Frame",
+        DeclarationValue::TTuple(TTuple {
+            items: vec![
+                TupleItem::TPrimitive(TPrimitive::String),
+                TupleItem::TPrimitive(TPrimitive::Ti64),
+            ],
+        }),
+    )
+    .docs("Frame represents a tuple of an Timestamp (RFC3339) and an ID")
+    .config(TypeDeclarationConfig::rust_attribute("#[derive(Copy, Clone)]")),
+);
 
 snapshot!(
         declarations.codegen_rust().unwrap().trim(),
@@ -85,6 +86,36 @@ export type Frame = [string, number];
 
 for more examples see `gull/e2e/basic_codegen_test.rs`
 
+With the `derive` feature enabled, declarations can also be derived from
+existing rust types. Field types, docs, generics and serde renames are
+carried over.
+
+```ignore
+#[derive(serde::Serialize, serde::Deserialize, gull::Gull)]
+struct Frame(String, i64);
+
+let mut declarations = Declarations::new();
+declarations.add_derived::<Frame>();
+```
+
+`Declarations` can also be serialized into a versioned JSON document with
+`Declarations::to_json` and loaded back with `Declarations::from_json`. This
+allows snapshotting the schema, diffing it between commits or consuming it from
+tools that don't link gull.
+
+`Declarations::fixture` produces example JSON values of any declared type
+(`FixtureKind::Minimal`, `FixtureKind::Maximal` or a seeded
+`FixtureKind::Random`), serialized the same way the generated Rust types would
+be. They are deterministic and can be checked in as golden files.
+
+`DependencyGraph` orders declarations by their references, finds reference
+cycles and exports the graph as DOT. Codegen can emit types in dependency or
+alphabetical order with `DeclarationsConfig::EmitOrder`.
+
+`Declarations::codegen_markdown` and `Declarations::codegen_html` render an API
+reference with the docs, signature in every language and an example JSON value
+of every type.
+
 These types can be safely passed across the boundaries when de\serialized to
 and from JSON.
 
@@ -106,21 +137,39 @@ const json: Frame = getFrame("/tmp/my_thing.json");
  */
 
 mod codegen;
+mod configs;
+mod constraints;
 mod definitions;
-
+mod flatten;
+mod generics;
+mod includes;
+mod map_keys;
+mod targets;
+
+pub mod compat;
+pub mod fixtures;
 pub mod generator;
+pub mod graph;
+pub mod schema;
 #[cfg(feature = "sign_source")]
 pub mod sign_source;
 
 pub use generator::write_generated;
-// pub use macros::EnumSerialization;
+#[cfg(feature = "derive")]
+pub use gull_derive::Gull;
 
 pub mod prelude {
     pub use crate::codegen::*;
+    pub use crate::compat::{check_compatibility, CompatReport, Compatibility};
     pub use crate::definitions::builders::*;
     pub use crate::definitions::declarations::*;
     pub use crate::definitions::*;
+    pub use crate::fixtures::{FixtureGenerator, FixtureKind};
     pub use crate::generator::*;
+    pub use crate::graph::{DependencyGraph, EdgeKind, EmitOrder};
+    pub use crate::schema::{load_schema, parse_schema, SchemaError};
+    #[cfg(feature = "derive")]
+    pub use gull_derive::Gull;
 }
 
 #[cfg(test)]
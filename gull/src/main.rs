@@ -0,0 +1,377 @@
+use anyhow::{Context, Result};
+use colored::Colorize;
+use gull::prelude::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const USAGE: &str = "
+Generate types from a gull schema file.
+
+USAGE:
+    gull <SCHEMA> --target <rust|hack|flow|json|markdown|html|dot> --out <PATH> [--formatter <CMD>] [--target ...] [--check]
+    gull <SCHEMA> --compat-with <PATH> [--compat-level <LEVEL>]
+
+FLAGS:
+    --target <TARGET>   Language to generate. Can be repeated to generate multiple targets in one run.
+                        `json` writes the versioned JSON representation of the declarations,
+                        `markdown` and `html` write an API reference, `dot` writes the
+                        dependency graph of types
+    --out <PATH>        File to write the previous --target into
+    --formatter <CMD>   Command to format the previous --target's output with, e.g. \"rustfmt --edition 2018\".
+                        The path of the generated file is appended to the command
+    --check             Don't write anything, exit with code 1 if any generated file is out of date
+    --compat-with <PATH>
+                        Compare the schema with a previous version of it (a schema file or a `json`
+                        target output) and exit with code 1 if the changes are not compatible
+    --compat-level <LEVEL>
+                        Compatibility required by --compat-with. One of `backward` (new code can
+                        read old data, default), `forward` (old code can read new data), `full`
+    -h, --help          Print this message
+
+EXIT CODES:
+    0   Success
+    1   --check found out of date files or --compat-with found incompatible changes
+    2   Invalid arguments or failed to generate
+";
+
+const EXIT_OUT_OF_DATE: i32 = 1;
+const EXIT_ERROR: i32 = 2;
+
+fn main() {
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return;
+        }
+        Err(e) => {
+            eprintln!("{} {:#}\n{}", "[ERROR]".red(), e, USAGE);
+            std::process::exit(EXIT_ERROR);
+        }
+    };
+
+    match run(&args) {
+        Ok(true) => {}
+        Ok(false) => std::process::exit(EXIT_OUT_OF_DATE),
+        Err(e) => {
+            eprintln!("{} {:#}", "[ERROR]".red(), e);
+            std::process::exit(EXIT_ERROR);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Language {
+    Rust,
+    Hack,
+    Flow,
+    Json,
+    Markdown,
+    Html,
+    Dot,
+}
+
+#[derive(Debug, PartialEq)]
+struct Target {
+    language: Language,
+    out: PathBuf,
+    formatter: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Args {
+    schema: PathBuf,
+    targets: Vec<Target>,
+    check: bool,
+    compat_with: Option<PathBuf>,
+    compat_level: Compatibility,
+}
+
+impl Args {
+    // Returns `None` if help was requested
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Option<Self>> {
+        let mut schema = None;
+        let mut check = false;
+        let mut compat_with = None;
+        let mut compat_level = Compatibility::Backward;
+        // --out and --formatter apply to the last --target
+        let mut targets: Vec<(Language, Option<PathBuf>, Option<String>)> = vec![];
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = |flag: &str| {
+                args.next()
+                    .with_context(|| format!("`{}` requires a value", flag))
+            };
+
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "--check" => check = true,
+                "--compat-with" => compat_with = Some(PathBuf::from(value("--compat-with")?)),
+                "--compat-level" => {
+                    compat_level = match value("--compat-level")?.as_str() {
+                        "backward" => Compatibility::Backward,
+                        "forward" => Compatibility::Forward,
+                        "full" => Compatibility::Full,
+                        other => anyhow::bail!(
+                            "Unknown compatibility level `{}`. Expected one of `backward`, `forward`, `full`",
+                            other
+                        ),
+                    }
+                }
+                "--target" => {
+                    let language = match value("--target")?.as_str() {
+                        "rust" => Language::Rust,
+                        "hack" => Language::Hack,
+                        "flow" => Language::Flow,
+                        "json" => Language::Json,
+                        "markdown" => Language::Markdown,
+                        "html" => Language::Html,
+                        "dot" => Language::Dot,
+                        other => anyhow::bail!(
+                            "Unknown target `{}`. Expected one of `rust`, `hack`, `flow`, `json`, `markdown`, `html`, `dot`",
+                            other
+                        ),
+                    };
+                    targets.push((language, None, None));
+                }
+                "--out" => {
+                    let out = value("--out")?;
+                    match targets.last_mut() {
+                        Some((_, path @ None, _)) => *path = Some(PathBuf::from(out)),
+                        Some(_) => anyhow::bail!("`--out` is passed twice for the same `--target`"),
+                        None => anyhow::bail!("`--out` must follow a `--target`"),
+                    }
+                }
+                "--formatter" => {
+                    let formatter = value("--formatter")?;
+                    match targets.last_mut() {
+                        Some((_, _, f @ None)) => *f = Some(formatter),
+                        Some(_) => {
+                            anyhow::bail!("`--formatter` is passed twice for the same `--target`")
+                        }
+                        None => anyhow::bail!("`--formatter` must follow a `--target`"),
+                    }
+                }
+                flag if flag.starts_with('-') => anyhow::bail!("Unknown flag `{}`", flag),
+                path => {
+                    if schema.is_some() {
+                        anyhow::bail!("Only one schema file can be passed");
+                    }
+                    schema = Some(PathBuf::from(path));
+                }
+            }
+        }
+
+        let schema = schema.context("Schema file path is required")?;
+
+        if targets.is_empty() && compat_with.is_none() {
+            anyhow::bail!("At least one `--target` or `--compat-with` is required");
+        }
+
+        let targets = targets
+            .into_iter()
+            .map(|(language, out, formatter)| {
+                Ok(Target {
+                    language,
+                    out: out.with_context(|| format!("`--out` is missing for {:?}", language))?,
+                    formatter,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Some(Self {
+            schema,
+            targets,
+            check,
+            compat_with,
+            compat_level,
+        }))
+    }
+}
+
+// Returns false if `--check` found out of date files or `--compat-with` found
+// incompatible changes
+fn run(args: &Args) -> Result<bool> {
+    let declarations = load_schema(&args.schema)?;
+    let mut up_to_date = true;
+
+    if let Some(path) = &args.compat_with {
+        let previous = load_declarations(path)?;
+        let report = check_compatibility(&previous, &declarations);
+        println!("{}", report);
+
+        if !report.satisfies(args.compat_level) {
+            println!(
+                "{} changes are not {}",
+                "[INCOMPATIBLE]".red(),
+                args.compat_level
+            );
+            up_to_date = false;
+        }
+    }
+
+    for target in &args.targets {
+        let source = match target.language {
+            Language::Rust => declarations.codegen_rust()?,
+            Language::Hack => declarations.codegen_hack()?,
+            Language::Flow => declarations.codegen_flow()?,
+            Language::Json => declarations.to_json()?,
+            Language::Markdown => declarations.codegen_markdown()?,
+            Language::Html => declarations.codegen_html()?,
+            Language::Dot => DependencyGraph::new(&declarations).to_dot(),
+        };
+
+        if args.check {
+            if !check(target, &source)? {
+                up_to_date = false;
+            }
+        } else {
+            let formatter = target
+                .formatter
+                .as_ref()
+                .map(|f| move || format_file(f, &target.out));
+            write_generated(&target.out, &source, formatter)?;
+        }
+    }
+
+    Ok(up_to_date)
+}
+
+// Previous versions can be either a schema or a `--target json` snapshot
+fn load_declarations(path: &Path) -> Result<Declarations> {
+    if path.extension().is_some_and(|e| e == "json") {
+        let json = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read `{}`", path.display()))?;
+        Declarations::from_json(&json)
+            .with_context(|| format!("Failed to load `{}`", path.display()))
+    } else {
+        load_schema(path)
+    }
+}
+
+fn check(target: &Target, source: &str) -> Result<bool> {
+    let expected = match &target.formatter {
+        Some(formatter) => {
+            // Format in the same directory so that formatter config files apply
+            let file_name = target
+                .out
+                .file_name()
+                .with_context(|| format!("`{}` is not a file path", target.out.display()))?;
+            let mut tmp = target.out.clone();
+            tmp.set_file_name(format!(".gull_check_{}", file_name.to_string_lossy()));
+
+            fs::write(&tmp, source)
+                .with_context(|| format!("Failed to write `{}`", tmp.display()))?;
+            let formatted =
+                format_file(formatter, &tmp).and_then(|_| Ok(fs::read_to_string(&tmp)?));
+            fs::remove_file(&tmp).ok();
+            formatted?
+        }
+        None => source.to_string(),
+    };
+
+    let actual = fs::read_to_string(&target.out).unwrap_or_default();
+
+    match colored_diff(&actual, &expected) {
+        None => {
+            println!(
+                "{} {}",
+                "[UP TO DATE]".green(),
+                target.out.display().to_string().cyan()
+            );
+            Ok(true)
+        }
+        Some(diff) => {
+            println!(
+                "{} {}",
+                "[OUT OF DATE]".red(),
+                target.out.display().to_string().cyan()
+            );
+            println!("{}", diff);
+            Ok(false)
+        }
+    }
+}
+
+fn format_file(formatter: &str, path: &Path) -> Result<()> {
+    let mut split = formatter.split_whitespace();
+    let program = split.next().context("`--formatter` is empty")?;
+
+    let output = Command::new(program)
+        .args(split)
+        .arg(path)
+        .output()
+        .with_context(|| format!("Failed to run formatter `{}`", formatter))?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "Failed to run formatter on the file `{}`\nSTDOUT:\n{}\nSTDERR:\n{}\n",
+            path.display(),
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr),
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Option<Args>> {
+        Args::parse(args.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn parse_multiple_targets() -> Result<()> {
+        let args = parse("types.gull --target rust --out a.rs --formatter rustfmt --target flow --out b.js --check")?;
+
+        assert_eq!(
+            args,
+            Some(Args {
+                schema: PathBuf::from("types.gull"),
+                targets: vec![
+                    Target {
+                        language: Language::Rust,
+                        out: PathBuf::from("a.rs"),
+                        formatter: Some("rustfmt".to_string()),
+                    },
+                    Target {
+                        language: Language::Flow,
+                        out: PathBuf::from("b.js"),
+                        formatter: None,
+                    },
+                ],
+                check: true,
+                compat_with: None,
+                compat_level: Compatibility::Backward,
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = |args: &str| parse(args).unwrap_err().to_string();
+
+        assert_eq!(
+            error("--target rust --out a.rs"),
+            "Schema file path is required"
+        );
+        assert_eq!(
+            error("a.gull"),
+            "At least one `--target` or `--compat-with` is required"
+        );
+        assert_eq!(
+            error("a.gull --out a.rs"),
+            "`--out` must follow a `--target`"
+        );
+        assert_eq!(error("a.gull --target rust"), "`--out` is missing for Rust");
+        assert_eq!(
+            error("a.gull --target php --out a.php"),
+            "Unknown target `php`. Expected one of `rust`, `hack`, `flow`, `json`, `markdown`, `html`, `dot`"
+        );
+    }
+}
@@ -0,0 +1,138 @@
+/*!
+Map keys as they appear in JSON.
+
+JSON object keys are always strings. serde writes integer keys as their
+decimal representation, so Flow sees them as `string`. Hack turns numeric
+string keys into ints when decoding, so both integer and string keys are
+`arraykey` there. Simple enum keys
+are limited to their variants. Anything else (floats, bools, structs,
+collections) can't be serialized as an object key and is rejected before
+codegen.
+*/
+
+use crate::definitions::*;
+use crate::generics::{walk_declaration, walk_map, GenericsVisitor};
+use anyhow::{bail, Result};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+// Aliases that resolve to themselves would be followed forever
+const MAX_ALIAS_DEPTH: usize = 32;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum MapKey {
+    /// Integers, written as strings in JSON
+    Number,
+    /// Strings, which can look like integers
+    String,
+    /// Values of a simple enum
+    Enum(Vec<Cow<'static, str>>),
+    /// Types that can't be looked into (hardcoded types, generic params, per
+    /// language types), used as they are
+    Other,
+    /// Can't be a JSON object key, with a description of the type
+    Invalid(String),
+}
+
+/// Looks through references and type aliases to find out what a map key
+/// serializes to
+pub(crate) struct MapKeys {
+    declarations: BTreeMap<String, DeclarationValue>,
+}
+
+impl MapKeys {
+    pub(crate) fn new(declarations: &Declarations) -> Self {
+        MapKeys {
+            declarations: declarations
+                .declarations
+                .iter()
+                .map(|d| (d.name.to_string(), d.value.clone()))
+                .collect(),
+        }
+    }
+
+    pub(crate) fn kind(&self, key: &TPrimitive) -> MapKey {
+        self.kind_at_depth(key, 0)
+    }
+
+    fn kind_at_depth(&self, key: &TPrimitive, depth: usize) -> MapKey {
+        match key {
+            TPrimitive::Ti64 | TPrimitive::Ti32 | TPrimitive::Tu32 | TPrimitive::Tusize => {
+                MapKey::Number
+            }
+            TPrimitive::String => MapKey::String,
+            TPrimitive::THardcoded(_) | TPrimitive::TDifferentPerLanguage { .. } => MapKey::Other,
+            TPrimitive::Tf64 => MapKey::Invalid("`f64`".into()),
+            TPrimitive::Tbool => MapKey::Invalid("`bool`".into()),
+            TPrimitive::TVec(_) => MapKey::Invalid("`vec`".into()),
+            TPrimitive::TMap(_) => MapKey::Invalid("`map`".into()),
+            TPrimitive::TOption(_) => MapKey::Invalid("`option`".into()),
+            TPrimitive::TGeneric(TGeneric::TDefinition { .. }) => MapKey::Other,
+            TPrimitive::TGeneric(TGeneric::TPrimitive(p)) => self.kind_at_depth(p, depth),
+            TPrimitive::TGeneric(TGeneric::TReference(r)) | TPrimitive::TReference(r) => {
+                self.reference_kind(r, depth)
+            }
+        }
+    }
+
+    fn reference_kind(&self, r: &TReference, depth: usize) -> MapKey {
+        let name = r.get_name();
+        // Declarations added separately (e.g. in another file) can't be checked
+        let value = match self.declarations.get(name) {
+            Some(value) => value,
+            None => return MapKey::Other,
+        };
+
+        match value {
+            DeclarationValue::TPrimitive(p) if depth < MAX_ALIAS_DEPTH => {
+                self.kind_at_depth(p, depth + 1)
+            }
+            DeclarationValue::TPrimitive(_) => MapKey::Other,
+            DeclarationValue::TSimpleEnum(e) => MapKey::Enum(e.values()),
+            DeclarationValue::TStruct(_) => MapKey::Invalid(format!("struct `{}`", name)),
+            DeclarationValue::TEnum(_) => MapKey::Invalid(format!("enum `{}`", name)),
+            DeclarationValue::TMap(_)
+            | DeclarationValue::TVec(_)
+            | DeclarationValue::TOption(_)
+            | DeclarationValue::TTuple(_) => MapKey::Invalid(format!("`{}`", name)),
+            DeclarationValue::Docs | DeclarationValue::CodeBlock(_) => MapKey::Other,
+        }
+    }
+}
+
+/// Fails if any map has a key that can't be a JSON object key
+pub(crate) fn validate(declarations: &Declarations) -> Result<()> {
+    let mut validator = Validator {
+        keys: MapKeys::new(declarations),
+        declaration: String::new(),
+    };
+
+    for declaration in &declarations.declarations {
+        validator.declaration = declaration.name.to_string();
+        walk_declaration(&mut validator, &mut declaration.value.clone())?;
+    }
+    Ok(())
+}
+
+struct Validator {
+    keys: MapKeys,
+    // declaration that is being validated, for error messages
+    declaration: String,
+}
+
+impl GenericsVisitor for Validator {
+    fn map(&mut self, m: &mut TMap) -> Result<()> {
+        if let MapKey::Invalid(key) = self.keys.kind(&m.key) {
+            bail!(
+                "map keys must be strings, integers or simple enums, found {} in `{}`",
+                key,
+                self.declaration
+            );
+        }
+        walk_map(self, m)
+    }
+
+    fn param(&mut self, _: &mut TGeneric) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,215 @@
+use super::SchemaError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenKind {
+    Ident(String),
+    Str(String),
+    // Number literal as written, e.g. `-12` or `0.5`
+    Number(String),
+    Punct(char),
+    // `/// text` comment that documents the next item
+    Doc(String),
+    // `//! text` comment that becomes an independent docs block
+    InnerDoc(String),
+    Eof,
+}
+
+#[derive(Debug, Clone)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub position: Position,
+}
+
+const PUNCTUATION: &[char] = &[
+    '{', '}', '(', ')', '<', '>', ',', ';', ':', '=', '?', '@', '+',
+];
+
+pub fn tokenize(source: &str) -> Result<Vec<Token>, SchemaError> {
+    let mut lexer = Lexer {
+        chars: source.chars().collect(),
+        offset: 0,
+        position: Position { line: 1, column: 1 },
+    };
+
+    let mut tokens = vec![];
+    loop {
+        let token = lexer.next_token()?;
+        let is_eof = token.kind == TokenKind::Eof;
+        tokens.push(token);
+        if is_eof {
+            return Ok(tokens);
+        }
+    }
+}
+
+struct Lexer {
+    chars: Vec<char>,
+    offset: usize,
+    position: Position,
+}
+
+impl Lexer {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.offset).copied()
+    }
+
+    fn peek_nth(&self, n: usize) -> Option<char> {
+        self.chars.get(self.offset + n).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.offset += 1;
+        if c == '\n' {
+            self.position.line += 1;
+            self.position.column = 1;
+        } else {
+            self.position.column += 1;
+        }
+        Some(c)
+    }
+
+    fn rest_of_line(&mut self) -> String {
+        let mut line = String::new();
+        while let Some(c) = self.peek() {
+            if c == '\n' {
+                break;
+            }
+            line.push(c);
+            self.bump();
+        }
+        line
+    }
+
+    fn next_token(&mut self) -> Result<Token, SchemaError> {
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() {
+                self.bump();
+                continue;
+            }
+
+            if c == '/' && self.peek_nth(1) == Some('/') {
+                let position = self.position;
+                self.bump();
+                self.bump();
+
+                let kind = match self.peek() {
+                    Some('/') if self.peek_nth(1) != Some('/') => {
+                        self.bump();
+                        Some(TokenKind::Doc(strip_one_space(self.rest_of_line())))
+                    }
+                    Some('!') => {
+                        self.bump();
+                        Some(TokenKind::InnerDoc(strip_one_space(self.rest_of_line())))
+                    }
+                    _ => {
+                        self.rest_of_line();
+                        None
+                    }
+                };
+
+                if let Some(kind) = kind {
+                    return Ok(Token { kind, position });
+                }
+                continue;
+            }
+
+            break;
+        }
+
+        let position = self.position;
+
+        let c = match self.peek() {
+            Some(c) => c,
+            None => {
+                return Ok(Token {
+                    kind: TokenKind::Eof,
+                    position,
+                })
+            }
+        };
+
+        let kind = if c == '"' {
+            self.bump();
+            TokenKind::Str(self.string(position)?)
+        } else if c.is_alphabetic() || c == '_' {
+            let mut ident = String::new();
+            while let Some(c) = self.peek() {
+                if c.is_alphanumeric() || c == '_' {
+                    ident.push(c);
+                    self.bump();
+                } else {
+                    break;
+                }
+            }
+            TokenKind::Ident(ident)
+        } else if c.is_ascii_digit()
+            || (c == '-' && self.peek_nth(1).is_some_and(|c| c.is_ascii_digit()))
+        {
+            let mut number = String::new();
+            number.extend(self.bump());
+            while let Some(c) = self.peek().filter(char::is_ascii_digit) {
+                number.push(c);
+                self.bump();
+            }
+            if self.peek() == Some('.') && self.peek_nth(1).is_some_and(|c| c.is_ascii_digit()) {
+                number.extend(self.bump());
+                while let Some(c) = self.peek().filter(char::is_ascii_digit) {
+                    number.push(c);
+                    self.bump();
+                }
+            }
+            TokenKind::Number(number)
+        } else if PUNCTUATION.contains(&c) {
+            self.bump();
+            TokenKind::Punct(c)
+        } else {
+            return Err(SchemaError::new(
+                position,
+                format!("unexpected character `{}`", c),
+            ));
+        };
+
+        Ok(Token { kind, position })
+    }
+
+    fn string(&mut self, start: Position) -> Result<String, SchemaError> {
+        let mut s = String::new();
+        loop {
+            match self.bump() {
+                None => return Err(SchemaError::new(start, "unterminated string")),
+                Some('"') => return Ok(s),
+                Some('\\') => {
+                    let position = self.position;
+                    match self.bump() {
+                        Some('n') => s.push('\n'),
+                        Some('t') => s.push('\t'),
+                        Some('"') => s.push('"'),
+                        Some('\\') => s.push('\\'),
+                        Some(c) => {
+                            return Err(SchemaError::new(
+                                position,
+                                format!("unknown escape sequence `\\{}`", c),
+                            ))
+                        }
+                        None => return Err(SchemaError::new(start, "unterminated string")),
+                    }
+                }
+                Some(c) => s.push(c),
+            }
+        }
+    }
+}
+
+fn strip_one_space(line: String) -> String {
+    match line.strip_prefix(' ') {
+        Some(stripped) => stripped.to_string(),
+        None => line,
+    }
+}
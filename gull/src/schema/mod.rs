@@ -0,0 +1,157 @@
+/*!
+A declarative text format for gull declarations, so that types can be
+defined without writing rust builder code.
+
+```text
+hack_namespace "Graph";
+
+//! ==============================
+//! Independent documentation block
+//! ==============================
+
+type NodeID = i64;
+
+/// A single node in the graph
+@rust("#[derive(serde::Serialize, serde::Deserialize)]")
+struct GraphNode<T: Ord> {
+    /// Node identifier
+    id: NodeID,
+    tags: set<T>,
+    weights: map<string, f64>,
+    @rust("#[serde(skip_serializing_if = \"Option::is_none\")]")
+    parent?: NodeID,
+}
+
+enum StorageType { Full, Delta }
+
+enum Operation {
+    Fetch { items: vec<i64> },
+    Drop(bool),
+}
+
+type ID = per_language(rust: raw("crate::types::ID"), hack: i64, flow: i64);
+code rust "use crate::types::ID;";
+```
+
+Adding `validators;` generates a runtime validator for every type in Hack and
+Flow. `emit_order topological;` (or `alphabetical`) emits types in dependency
+order instead of the order they're declared in. `rust_impls;` generates
+`From`, `is_variant()` and `as_variant()` for enums with data, `Display` and
+`FromStr` for simple enums and `new()` for structs in Rust.
+
+Generic params can have Rust bounds (`T: Ord + Clone`), Hack and Flow bounds
+(`@hack("arraykey") @flow("string | number") K`) and a default
+(`V = string`). Generic arguments can be any type except tuples, e.g.
+`Index<string, vec<Node>>`.
+
+Rust codegen boxes references that make a type recursive by value (e.g.
+`struct Not { expr: Expr }` inside of `enum Expr`). `@rust_box` boxes a field
+that isn't recursive.
+
+`@flatten` on a field that references a struct inlines the struct's fields
+into the JSON object (`#[serde(flatten)]`). Flow spreads the referenced type,
+Hack inlines its fields into the shape. Field names that clash after
+flattening are an error.
+
+`struct Node includes Metadata, Page<T> { ... }` merges the fields of the
+included structs into `Node`, ahead of its own fields. Rust and Hack get the
+merged fields, Flow spreads the included types. Including anything but a
+struct, including a struct in itself or defining a field twice is an error.
+
+Fields can be constrained with `@min_length(1)`, `@max_length(64)` and
+`@non_empty` (strings, vecs, sets and maps), `@pattern("^[a-z]+$")` (strings)
+and `@min(0)` / `@max(100.5)` (numbers). Hack and Flow validators check
+them and, with `rust_impls;`, Rust structs get a `validate()` method (patterns
+need the `regex` crate).
+
+`@only("rust, flow")` and `@except("hack")` generate a type only for some of
+the languages, and `@rust_visibility("pub(crate)")` (or `private`) restricts
+the visibility of a Rust type. Codegen fails if a type references one that
+isn't generated for the same language or, in Rust, is less visible.
+
+Types, fields and variants of enums with data can be marked with
+`@deprecated("message")` and an optional `@since("1.2.0")`. Rust gets
+`#[deprecated]`, Hack `<<__Deprecated>>` on types (and a comment on fields and
+variants, which can't have attributes) and Flow a `@deprecated` JSDoc.
+
+`@flow_exact` (`{| |}` objects), `@flow_read_only` (`$ReadOnly<>` objects),
+`@flow_read_only_arrays` (`$ReadOnlyArray<>` for vecs and sets) and
+`@hack_open_shape` (`shape(..., ...)`) change how a single type is generated to
+match the lint rules of a codebase.
+
+`@hack_enum("enum_class")` or `@hack_enum("constants")` generates a Hack
+`enum class` or an `abstract final class` with string constants instead of a
+plain `enum` for a simple enum or for the variant type of an enum with data.
+Their values are typed as `string`. Every enum with data also gets
+`Name_getType()` and a `Name_getVariant()` accessor per variant in Hack, and
+`getNameType()`, a `nameVariant()` constructor per variant and an exhaustive
+`matchName(x, {Variant: ...})` in Flow.
+
+Builtin types are `string`, `i64`, `f64`, `i32`, `u32`, `usize`, `bool`,
+`vec<T>`, `set<T>`, `option<T>`, `map<K, V>` (BTreeMap in rust),
+`hash_map<K, V>` and tuples `(A, B)`. Enums where no variant has data become
+simple (string) enums. Their variants serialize to their name unless they have
+a `@value("...")` (`#[serde(rename)]` in Rust), and can have docs and
+`@deprecated`. Giving every variant a discriminant (`enum Level { Low = 1,
+High = 2 }`) makes an integer enum instead: `#[repr(i32)]` with `serde_repr`
+derives in Rust, `enum Level: int` in Hack and a union of number literals with
+a frozen `LevelEnum` object in Flow.
+
+Map keys can be strings, integers or simple enums, since they have to be JSON
+object keys. Integer keys are `string` in Flow, integer and string keys are
+`arraykey` in Hack (which turns numeric keys into ints) and simple enum keys
+become records with an optional field per variant in Flow.
+*/
+
+mod lexer;
+mod parser;
+mod resolve;
+
+use crate::definitions::Declarations;
+use anyhow::{Context, Result};
+use std::fmt;
+use std::path::Path;
+
+pub use lexer::Position;
+
+/// Parse a schema source into `Declarations`
+pub fn parse_schema(source: &str) -> Result<Declarations, SchemaError> {
+    let tokens = lexer::tokenize(source)?;
+    let items = parser::Parser::new(tokens).parse_file()?;
+    resolve::resolve(items)
+}
+
+/// Read and parse a schema file. Errors are reported as `path:line:column: message`
+pub fn load_schema(path: &Path) -> Result<Declarations> {
+    let source = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read schema file `{}`", path.display()))?;
+
+    parse_schema(&source).map_err(|e| anyhow::anyhow!("{}:{}", path.display(), e))
+}
+
+#[derive(Debug, Clone)]
+pub struct SchemaError {
+    pub position: Position,
+    pub message: String,
+}
+
+impl SchemaError {
+    pub(crate) fn new(position: Position, message: impl Into<String>) -> Self {
+        Self {
+            position,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {}",
+            self.position.line, self.position.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for SchemaError {}
@@ -0,0 +1,565 @@
+use super::lexer::{Position, Token, TokenKind};
+use super::SchemaError;
+
+// Untyped syntax tree of a schema file. It is resolved into gull definitions
+// in `resolve.rs` once all declaration names are known.
+
+#[derive(Debug)]
+pub struct Item {
+    pub docs: String,
+    pub attrs: Vec<Attr>,
+    pub kind: ItemKind,
+    pub position: Position,
+}
+
+#[derive(Debug)]
+pub enum ItemKind {
+    HackNamespace(String),
+    FileHeader(String),
+    Validators,
+    RustImpls,
+    EmitOrder(String),
+    Code {
+        language: String,
+        code: String,
+    },
+    Docs,
+    Type {
+        name: String,
+        generics: Vec<GenericDef>,
+        ty: Ty,
+    },
+    Struct {
+        name: String,
+        generics: Vec<GenericDef>,
+        // `struct A includes B, C<T> { ... }`
+        includes: Vec<Ty>,
+        fields: Vec<Field>,
+    },
+    Enum {
+        name: String,
+        generics: Vec<GenericDef>,
+        variants: Vec<Variant>,
+    },
+}
+
+#[derive(Debug)]
+pub struct Attr {
+    pub name: String,
+    pub value: Option<String>,
+    pub position: Position,
+}
+
+#[derive(Debug)]
+pub struct GenericDef {
+    pub attrs: Vec<Attr>,
+    pub name: String,
+    pub bounds: Option<String>,
+    pub default: Option<Ty>,
+    pub position: Position,
+}
+
+#[derive(Debug)]
+pub struct Field {
+    pub docs: String,
+    pub attrs: Vec<Attr>,
+    pub name: String,
+    pub optional: bool,
+    pub ty: Ty,
+    pub position: Position,
+}
+
+#[derive(Debug)]
+pub struct Variant {
+    pub docs: String,
+    pub attrs: Vec<Attr>,
+    pub name: String,
+    pub payload: Option<VariantPayload>,
+    // `Name = 1`
+    pub discriminant: Option<i32>,
+    pub position: Position,
+}
+
+#[derive(Debug)]
+pub enum VariantPayload {
+    Primitive(Ty),
+    Struct(Vec<Field>),
+}
+
+#[derive(Debug)]
+pub struct Ty {
+    pub kind: TyKind,
+    pub position: Position,
+}
+
+#[derive(Debug)]
+pub enum TyKind {
+    // Builtins (`i64`, `vec<T>`, ...), generic params and references to other
+    // declarations. Which one it is gets figured out during resolution.
+    Named {
+        name: String,
+        args: Vec<Ty>,
+    },
+    Tuple(Vec<Ty>),
+    Raw(String),
+    PerLanguage {
+        rust: Box<Ty>,
+        hack: Box<Ty>,
+        flow: Box<Ty>,
+    },
+}
+
+// Keywords that can start an item, for error messages
+const ITEM_KEYWORDS: &str = "`type`, `struct`, `enum`, `code`, `hack_namespace`, `file_header`, `validators`, `rust_impls` or `emit_order`";
+
+pub struct Parser {
+    tokens: Vec<Token>,
+    offset: usize,
+}
+
+impl Parser {
+    pub fn new(tokens: Vec<Token>) -> Self {
+        Self { tokens, offset: 0 }
+    }
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.offset]
+    }
+
+    fn bump(&mut self) -> Token {
+        let token = self.tokens[self.offset].clone();
+        if token.kind != TokenKind::Eof {
+            self.offset += 1;
+        }
+        token
+    }
+
+    fn is_punct(&self, c: char) -> bool {
+        self.peek().kind == TokenKind::Punct(c)
+    }
+
+    fn eat_punct(&mut self, c: char) -> bool {
+        if self.is_punct(c) {
+            self.bump();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> SchemaError {
+        let token = self.peek();
+        let found = match &token.kind {
+            TokenKind::Ident(i) => format!("`{}`", i),
+            TokenKind::Str(s) => format!("string {:?}", s),
+            TokenKind::Number(n) => format!("`{}`", n),
+            TokenKind::Punct(c) => format!("`{}`", c),
+            TokenKind::Doc(_) => "doc comment".to_string(),
+            TokenKind::InnerDoc(_) => "`//!` doc comment".to_string(),
+            TokenKind::Eof => "end of file".to_string(),
+        };
+        SchemaError::new(
+            token.position,
+            format!("expected {}, found {}", expected, found),
+        )
+    }
+
+    fn expect_punct(&mut self, c: char) -> Result<(), SchemaError> {
+        if self.eat_punct(c) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("`{}`", c)))
+        }
+    }
+
+    fn expect_ident(&mut self) -> Result<(String, Position), SchemaError> {
+        if let TokenKind::Ident(i) = &self.peek().kind {
+            let i = i.clone();
+            let position = self.bump().position;
+            Ok((i, position))
+        } else {
+            Err(self.unexpected("identifier"))
+        }
+    }
+
+    fn expect_str(&mut self) -> Result<String, SchemaError> {
+        if let TokenKind::Str(s) = &self.peek().kind {
+            let s = s.clone();
+            self.bump();
+            Ok(s)
+        } else {
+            Err(self.unexpected("string"))
+        }
+    }
+
+    fn expect_i32(&mut self) -> Result<i32, SchemaError> {
+        match &self.peek().kind {
+            TokenKind::Number(i) if !i.contains('.') => {
+                let i = i.clone();
+                let position = self.bump().position;
+                i.parse().map_err(|_| {
+                    SchemaError::new(position, format!("`{}` doesn't fit into an i32", i))
+                })
+            }
+            _ => Err(self.unexpected("integer")),
+        }
+    }
+
+    fn docs(&mut self) -> String {
+        let mut lines = vec![];
+        while let TokenKind::Doc(line) = &self.peek().kind {
+            lines.push(line.clone());
+            self.bump();
+        }
+        lines.join("\n")
+    }
+
+    // @name("value"), @name(1) or a flag without a value: @name
+    fn attrs(&mut self) -> Result<Vec<Attr>, SchemaError> {
+        let mut attrs = vec![];
+        while self.is_punct('@') {
+            let position = self.bump().position;
+            let (name, _) = self.expect_ident()?;
+            let mut value = None;
+            if self.is_punct('(') {
+                self.bump();
+                value = Some(match &self.peek().kind {
+                    TokenKind::Number(n) => {
+                        let n = n.clone();
+                        self.bump();
+                        n
+                    }
+                    _ => self.expect_str()?,
+                });
+                self.expect_punct(')')?;
+            }
+            attrs.push(Attr {
+                name,
+                value,
+                position,
+            });
+        }
+        Ok(attrs)
+    }
+
+    pub fn parse_file(&mut self) -> Result<Vec<Item>, SchemaError> {
+        let mut items = vec![];
+
+        loop {
+            if let TokenKind::InnerDoc(_) = &self.peek().kind {
+                let position = self.peek().position;
+                let mut lines = vec![];
+                while let TokenKind::InnerDoc(line) = &self.peek().kind {
+                    lines.push(line.clone());
+                    self.bump();
+                }
+                items.push(Item {
+                    docs: lines.join("\n"),
+                    attrs: vec![],
+                    kind: ItemKind::Docs,
+                    position,
+                });
+                continue;
+            }
+
+            if self.peek().kind == TokenKind::Eof {
+                return Ok(items);
+            }
+
+            items.push(self.item()?);
+        }
+    }
+
+    fn item(&mut self) -> Result<Item, SchemaError> {
+        let docs = self.docs();
+        let attrs = self.attrs()?;
+        let docs = if docs.is_empty() { self.docs() } else { docs };
+
+        let (keyword, position) = match &self.peek().kind {
+            TokenKind::Ident(_) => self.expect_ident()?,
+            _ => return Err(self.unexpected(ITEM_KEYWORDS)),
+        };
+
+        let kind = match keyword.as_str() {
+            "hack_namespace" => {
+                let namespace = self.expect_str()?;
+                self.expect_punct(';')?;
+                ItemKind::HackNamespace(namespace)
+            }
+            "file_header" => {
+                let header = self.expect_str()?;
+                self.expect_punct(';')?;
+                ItemKind::FileHeader(header)
+            }
+            "validators" => {
+                self.expect_punct(';')?;
+                ItemKind::Validators
+            }
+            "rust_impls" => {
+                self.expect_punct(';')?;
+                ItemKind::RustImpls
+            }
+            "emit_order" => {
+                let (order, _) = self.expect_ident()?;
+                self.expect_punct(';')?;
+                ItemKind::EmitOrder(order)
+            }
+            "code" => {
+                let (language, _) = self.expect_ident()?;
+                let code = self.expect_str()?;
+                self.expect_punct(';')?;
+                ItemKind::Code { language, code }
+            }
+            "type" => {
+                let (name, _) = self.expect_ident()?;
+                let generics = self.generic_defs()?;
+                self.expect_punct('=')?;
+                let ty = self.ty()?;
+                self.expect_punct(';')?;
+                ItemKind::Type { name, generics, ty }
+            }
+            "struct" => {
+                let (name, _) = self.expect_ident()?;
+                let generics = self.generic_defs()?;
+                let mut includes = vec![];
+                if self.peek().kind == TokenKind::Ident("includes".to_string()) {
+                    self.bump();
+                    includes.push(self.ty()?);
+                    while self.eat_punct(',') {
+                        includes.push(self.ty()?);
+                    }
+                }
+                let fields = self.fields()?;
+                ItemKind::Struct {
+                    name,
+                    generics,
+                    includes,
+                    fields,
+                }
+            }
+            "enum" => {
+                let (name, _) = self.expect_ident()?;
+                let generics = self.generic_defs()?;
+                let variants = self.variants()?;
+                ItemKind::Enum {
+                    name,
+                    generics,
+                    variants,
+                }
+            }
+            other => {
+                return Err(SchemaError::new(
+                    position,
+                    format!("expected {}, found `{}`", ITEM_KEYWORDS, other),
+                ))
+            }
+        };
+
+        Ok(Item {
+            docs,
+            attrs,
+            kind,
+            position,
+        })
+    }
+
+    // <T, TS: Ord + Clone, @hack("arraykey") K = string>
+    fn generic_defs(&mut self) -> Result<Vec<GenericDef>, SchemaError> {
+        let mut generics = vec![];
+        if !self.eat_punct('<') {
+            return Ok(generics);
+        }
+
+        while !self.eat_punct('>') {
+            let attrs = self.attrs()?;
+            let (name, position) = self.expect_ident()?;
+            let mut bounds = None;
+            if self.eat_punct(':') {
+                let mut b = vec![self.expect_ident()?.0];
+                while self.eat_punct('+') {
+                    b.push(self.expect_ident()?.0);
+                }
+                bounds = Some(b.join(" + "));
+            }
+            let default = if self.eat_punct('=') {
+                Some(self.ty()?)
+            } else {
+                None
+            };
+            generics.push(GenericDef {
+                attrs,
+                name,
+                bounds,
+                default,
+                position,
+            });
+
+            if !self.is_punct('>') {
+                self.expect_punct(',')?;
+            }
+        }
+
+        Ok(generics)
+    }
+
+    // { name: type, other?: type }
+    fn fields(&mut self) -> Result<Vec<Field>, SchemaError> {
+        self.expect_punct('{')?;
+        let mut fields = vec![];
+
+        loop {
+            let docs = self.docs();
+            let attrs = self.attrs()?;
+            let docs = if docs.is_empty() { self.docs() } else { docs };
+
+            if self.eat_punct('}') {
+                return Ok(fields);
+            }
+
+            let (name, position) = self.expect_ident()?;
+            let optional = self.eat_punct('?');
+            self.expect_punct(':')?;
+            let ty = self.ty()?;
+
+            fields.push(Field {
+                docs,
+                attrs,
+                name,
+                optional,
+                ty,
+                position,
+            });
+
+            if !self.is_punct('}') {
+                self.expect_punct(',')?;
+            }
+        }
+    }
+
+    // { Unit, Primitive(type), Struct { field: type } }
+    fn variants(&mut self) -> Result<Vec<Variant>, SchemaError> {
+        self.expect_punct('{')?;
+        let mut variants = vec![];
+
+        loop {
+            let docs = self.docs();
+            let attrs = self.attrs()?;
+            let docs = if docs.is_empty() { self.docs() } else { docs };
+
+            if self.eat_punct('}') {
+                return Ok(variants);
+            }
+
+            let (name, position) = self.expect_ident()?;
+            let payload = if self.eat_punct('(') {
+                let ty = self.ty()?;
+                self.expect_punct(')')?;
+                Some(VariantPayload::Primitive(ty))
+            } else if self.is_punct('{') {
+                Some(VariantPayload::Struct(self.fields()?))
+            } else {
+                None
+            };
+            let discriminant = if payload.is_none() && self.eat_punct('=') {
+                Some(self.expect_i32()?)
+            } else {
+                None
+            };
+
+            variants.push(Variant {
+                docs,
+                attrs,
+                name,
+                payload,
+                discriminant,
+                position,
+            });
+
+            if !self.is_punct('}') {
+                self.expect_punct(',')?;
+            }
+        }
+    }
+
+    fn ty(&mut self) -> Result<Ty, SchemaError> {
+        let position = self.peek().position;
+
+        if self.eat_punct('(') {
+            let mut items = vec![];
+            while !self.eat_punct(')') {
+                items.push(self.ty()?);
+                if !self.is_punct(')') {
+                    self.expect_punct(',')?;
+                }
+            }
+            if items.is_empty() {
+                return Err(SchemaError::new(position, "empty tuples are not supported"));
+            }
+            return Ok(Ty {
+                kind: TyKind::Tuple(items),
+                position,
+            });
+        }
+
+        let (name, position) = self.expect_ident()?;
+
+        let kind = match name.as_str() {
+            // raw("crate::types::ID")
+            "raw" if self.is_punct('(') => {
+                self.expect_punct('(')?;
+                let raw = self.expect_str()?;
+                self.expect_punct(')')?;
+                TyKind::Raw(raw)
+            }
+            // per_language(rust: T, hack: T, flow: T)
+            "per_language" if self.is_punct('(') => {
+                self.expect_punct('(')?;
+                let (mut rust, mut hack, mut flow) = (None, None, None);
+                while !self.eat_punct(')') {
+                    let (language, language_position) = self.expect_ident()?;
+                    self.expect_punct(':')?;
+                    let ty = Box::new(self.ty()?);
+                    match language.as_str() {
+                        "rust" => rust = Some(ty),
+                        "hack" => hack = Some(ty),
+                        "flow" => flow = Some(ty),
+                        other => {
+                            return Err(SchemaError::new(
+                                language_position,
+                                format!("unknown language `{}`", other),
+                            ))
+                        }
+                    }
+                    if !self.is_punct(')') {
+                        self.expect_punct(',')?;
+                    }
+                }
+                let missing = |language: &str| {
+                    SchemaError::new(
+                        position,
+                        format!("`per_language` is missing a type for `{}`", language),
+                    )
+                };
+                TyKind::PerLanguage {
+                    rust: rust.ok_or_else(|| missing("rust"))?,
+                    hack: hack.ok_or_else(|| missing("hack"))?,
+                    flow: flow.ok_or_else(|| missing("flow"))?,
+                }
+            }
+            _ => {
+                let mut args = vec![];
+                if self.eat_punct('<') {
+                    while !self.eat_punct('>') {
+                        args.push(self.ty()?);
+                        if !self.is_punct('>') {
+                            self.expect_punct(',')?;
+                        }
+                    }
+                }
+                TyKind::Named { name, args }
+            }
+        };
+
+        Ok(Ty { kind, position })
+    }
+}
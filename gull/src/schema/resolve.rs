@@ -0,0 +1,797 @@
+use super::parser::{Attr, Field, GenericDef, Item, ItemKind, Ty, TyKind, VariantPayload};
+use super::SchemaError;
+use crate::generics::{walk_primitive, GenericsVisitor};
+use crate::prelude::*;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
+
+pub fn resolve(items: Vec<Item>) -> Result<Declarations, SchemaError> {
+    // name -> number of required and all generic params
+    let mut declared = BTreeMap::new();
+    for item in &items {
+        let (name, generics) = match &item.kind {
+            ItemKind::Type { name, generics, .. }
+            | ItemKind::Struct { name, generics, .. }
+            | ItemKind::Enum { name, generics, .. } => (name, generics),
+            _ => continue,
+        };
+        let required = generics.iter().filter(|g| g.default.is_none()).count();
+        if declared
+            .insert(name.clone(), (required, generics.len()))
+            .is_some()
+        {
+            return Err(SchemaError::new(
+                item.position,
+                format!("type `{}` is declared more than once", name),
+            ));
+        }
+    }
+
+    let mut declarations = Declarations::new();
+
+    for item in items {
+        let mut scope = Scope {
+            declared: &declared,
+            generics: BTreeSet::new(),
+        };
+
+        let mut includes = vec![];
+        let (name, generic_params, value) = match item.kind {
+            ItemKind::HackNamespace(namespace) => {
+                declarations.add_config(DeclarationsConfig::HackNamespace(namespace.into()));
+                continue;
+            }
+            ItemKind::FileHeader(header) => {
+                declarations.add_config(DeclarationsConfig::FileHeader(header.into()));
+                continue;
+            }
+            ItemKind::Validators => {
+                declarations.add_config(DeclarationsConfig::Validators);
+                continue;
+            }
+            ItemKind::RustImpls => {
+                declarations.add_config(DeclarationsConfig::RustImpls);
+                continue;
+            }
+            ItemKind::EmitOrder(order) => {
+                let order = match order.as_str() {
+                    "insertion" => EmitOrder::Insertion,
+                    "topological" => EmitOrder::Topological,
+                    "alphabetical" => EmitOrder::Alphabetical,
+                    other => {
+                        return Err(SchemaError::new(
+                            item.position,
+                            format!(
+                                "unknown emit order `{}`, expected `insertion`, `topological` or `alphabetical`",
+                                other
+                            ),
+                        ))
+                    }
+                };
+                declarations.add_config(DeclarationsConfig::EmitOrder(order));
+                continue;
+            }
+            ItemKind::Code { language, code } => {
+                let lines = code.lines().map(|l| l.to_string().into()).collect();
+                let block = match language.as_str() {
+                    "rust" => CodeBlock::Rust(lines),
+                    "hack" => CodeBlock::Hack(lines),
+                    "flow" => CodeBlock::Flow(lines),
+                    other => {
+                        return Err(SchemaError::new(
+                            item.position,
+                            format!("unknown code block language `{}`", other),
+                        ))
+                    }
+                };
+                (String::new(), vec![], DeclarationValue::CodeBlock(block))
+            }
+            ItemKind::Docs => (String::new(), vec![], DeclarationValue::Docs),
+            ItemKind::Type { name, generics, ty } => {
+                let generic_params = scope.define_generics(generics)?;
+                let value = scope.declaration_value(&ty)?;
+                (name, generic_params, value)
+            }
+            ItemKind::Struct {
+                name,
+                generics,
+                includes: included,
+                fields,
+            } => {
+                let generic_params = scope.define_generics(generics)?;
+                for ty in &included {
+                    match scope.primitive(ty)? {
+                        TPrimitive::TReference(r) => {
+                            includes.push(TypeDeclarationConfig::Include(r))
+                        }
+                        _ => {
+                            return Err(SchemaError::new(
+                                ty.position,
+                                format!("`{}` can only include other structs", name),
+                            ))
+                        }
+                    }
+                }
+                let value = DeclarationValue::TStruct(scope.gen_struct(fields)?);
+                (name, generic_params, value)
+            }
+            ItemKind::Enum {
+                name,
+                generics,
+                variants,
+            } => {
+                let generic_params = scope.define_generics(generics)?;
+
+                let unit_variants = variants.iter().filter(|v| v.payload.is_none()).count();
+                let value = if unit_variants == variants.len() {
+                    if !generic_params.is_empty() {
+                        return Err(SchemaError::new(
+                            item.position,
+                            format!("simple enum `{}` can't have generic params", name),
+                        ));
+                    }
+                    let mut simple_variants: Vec<SimpleEnumVariant> = vec![];
+                    for variant in variants {
+                        let mut simple_variant = SimpleEnumVariant::new(variant.name);
+                        simple_variant.docs = variant.docs.into();
+                        simple_variant.discriminant = variant.discriminant;
+                        check_repeated(&variant.attrs)?;
+                        if let Some(d) = deprecation(&variant.attrs)? {
+                            simple_variant.config.push(EnumVariantConfig::Deprecated(d));
+                        }
+                        for attr in &variant.attrs {
+                            match attr.name.as_str() {
+                                "deprecated" | "since" => {}
+                                "value" => simple_variant.value = attr_value(attr)?,
+                                _ => return Err(unknown_attr(attr)),
+                            }
+                        }
+                        if simple_variant.discriminant.is_some()
+                            && variant.attrs.iter().any(|a| a.name == "value")
+                        {
+                            return Err(SchemaError::new(
+                                variant.position,
+                                format!(
+                                    "variant `{}` can't have both a discriminant and a `@value`",
+                                    simple_variant.name
+                                ),
+                            ));
+                        }
+                        if simple_variants
+                            .iter()
+                            .any(|v| v.wire_value() == simple_variant.wire_value())
+                        {
+                            let value = match simple_variant.discriminant {
+                                Some(d) => format!("discriminant {}", d),
+                                None => format!("value {:?}", simple_variant.value),
+                            };
+                            return Err(SchemaError::new(
+                                variant.position,
+                                format!("{} is used by more than one variant of `{}`", value, name),
+                            ));
+                        }
+                        simple_variants.push(simple_variant);
+                    }
+                    let with_discriminant = simple_variants
+                        .iter()
+                        .filter(|v| v.discriminant.is_some())
+                        .count();
+                    if with_discriminant != 0 && with_discriminant != simple_variants.len() {
+                        return Err(SchemaError::new(
+                            item.position,
+                            format!(
+                                "either every variant of simple enum `{}` needs a discriminant or none",
+                                name
+                            ),
+                        ));
+                    }
+                    DeclarationValue::TSimpleEnum(TSimpleEnum {
+                        variants: simple_variants,
+                    })
+                } else {
+                    let mut enum_variants = vec![];
+                    for variant in variants {
+                        let variant_type = match variant.payload {
+                            Some(VariantPayload::Primitive(ty)) => {
+                                EnumVariantType::TPrimitive(scope.primitive(&ty)?)
+                            }
+                            Some(VariantPayload::Struct(fields)) => {
+                                EnumVariantType::TStruct(scope.gen_struct(fields)?)
+                            }
+                            None => {
+                                return Err(SchemaError::new(
+                                    variant.position,
+                                    format!(
+                                        "variant `{}` has no data. Enums can't mix variants with and without data",
+                                        variant.name
+                                    ),
+                                ))
+                            }
+                        };
+                        let mut config = vec![];
+                        check_repeated(&variant.attrs)?;
+                        if let Some(d) = deprecation(&variant.attrs)? {
+                            config.push(EnumVariantConfig::Deprecated(d));
+                        }
+                        for attr in &variant.attrs {
+                            match attr.name.as_str() {
+                                "deprecated" | "since" => {}
+                                _ => return Err(unknown_attr(attr)),
+                            }
+                        }
+
+                        enum_variants.push(EnumVariant {
+                            name: variant.name.into(),
+                            docs: variant.docs.into(),
+                            variant_type,
+                            config,
+                        });
+                    }
+                    DeclarationValue::TEnum(TEnum {
+                        variants: enum_variants,
+                    })
+                };
+                (name, generic_params, value)
+            }
+        };
+
+        let mut config = includes;
+        check_repeated(&item.attrs)?;
+        if let Some(d) = deprecation(&item.attrs)? {
+            config.push(TypeDeclarationConfig::Deprecated(d));
+        }
+        for attr in item.attrs {
+            match attr.name.as_str() {
+                "deprecated" | "since" => {}
+                "rust" => config.push(TypeDeclarationConfig::RustAttribute(attr_value(&attr)?)),
+                "only" => config.push(TypeDeclarationConfig::Targets(TargetFilter::Only(
+                    target_languages(&attr)?,
+                ))),
+                "except" => config.push(TypeDeclarationConfig::Targets(TargetFilter::Except(
+                    target_languages(&attr)?,
+                ))),
+                "rust_visibility" => {
+                    let visibility = match attr_value(&attr)?.as_ref() {
+                        "pub" => RustVisibility::Pub,
+                        "pub(crate)" => RustVisibility::PubCrate,
+                        "private" => RustVisibility::Private,
+                        other => {
+                            return Err(SchemaError::new(
+                                attr.position,
+                                format!(
+                                    "unknown visibility `{}`, expected `pub`, `pub(crate)` or `private`",
+                                    other
+                                ),
+                            ))
+                        }
+                    };
+                    config.push(TypeDeclarationConfig::RustVisibility(visibility))
+                }
+                "flow_exact" => config.push(flag(&attr, TypeDeclarationConfig::FlowExactObjects)?),
+                "flow_read_only" => {
+                    config.push(flag(&attr, TypeDeclarationConfig::FlowReadOnlyObjects)?)
+                }
+                "flow_read_only_arrays" => {
+                    config.push(flag(&attr, TypeDeclarationConfig::FlowReadOnlyArrays)?)
+                }
+                "hack_open_shape" => {
+                    config.push(flag(&attr, TypeDeclarationConfig::HackOpenShapes)?)
+                }
+                "hack_enum" => {
+                    let style = match attr_value(&attr)?.as_ref() {
+                        "enum" => HackEnumStyle::Enum,
+                        "enum_class" => HackEnumStyle::EnumClass,
+                        "constants" => HackEnumStyle::ClassConstants,
+                        other => {
+                            return Err(SchemaError::new(
+                                attr.position,
+                                format!(
+                                    "unknown Hack enum style `{}`, expected `enum`, `enum_class` or `constants`",
+                                    other
+                                ),
+                            ))
+                        }
+                    };
+                    match value {
+                        DeclarationValue::TEnum(_) | DeclarationValue::TSimpleEnum(_) => {}
+                        _ => {
+                            return Err(SchemaError::new(
+                                attr.position,
+                                format!(
+                                    "`@hack_enum` can only be used on enums, `{}` isn't one",
+                                    name
+                                ),
+                            ))
+                        }
+                    }
+                    config.push(TypeDeclarationConfig::HackEnumStyle(style))
+                }
+                _ => return Err(unknown_attr(&attr)),
+            }
+        }
+
+        declarations.add(TypeDeclaration {
+            name: name.into(),
+            docs: item.docs.into(),
+            value,
+            config,
+            generic_params,
+        });
+    }
+
+    Ok(declarations)
+}
+
+fn attr_value(attr: &Attr) -> Result<Cow<'static, str>, SchemaError> {
+    match &attr.value {
+        Some(value) => Ok(value.clone().into()),
+        None => Err(SchemaError::new(
+            attr.position,
+            format!(
+                "`@{}` requires a value: `@{}(\"...\")`",
+                attr.name, attr.name
+            ),
+        )),
+    }
+}
+
+// `@min_length(1)`, or the number quoted as in `@min_length("1")`
+fn attr_number<T: std::str::FromStr>(attr: &Attr) -> Result<T, SchemaError> {
+    let value = attr_value(attr)?;
+    value.trim().parse().map_err(|_| {
+        SchemaError::new(
+            attr.position,
+            format!("`@{}` expects a number, found `{}`", attr.name, value),
+        )
+    })
+}
+
+// Attribute without a value, e.g. `@rust_box`
+fn flag<T>(attr: &Attr, config: T) -> Result<T, SchemaError> {
+    match attr.value {
+        None => Ok(config),
+        Some(_) => Err(SchemaError::new(
+            attr.position,
+            format!("`@{}` doesn't take a value", attr.name),
+        )),
+    }
+}
+
+// `@deprecated("message")` with an optional `@since("version")`
+fn deprecation(attrs: &[Attr]) -> Result<Option<Deprecation>, SchemaError> {
+    let mut deprecation = None;
+    let mut since = None;
+    for attr in attrs {
+        match attr.name.as_str() {
+            "deprecated" => deprecation = Some(Deprecation::new(attr_value(attr)?)),
+            "since" => since = Some((attr, attr_value(attr)?)),
+            _ => {}
+        }
+    }
+
+    match (deprecation, since) {
+        (Some(d), Some((_, version))) => Ok(Some(d.since(version))),
+        (None, Some((attr, _))) => Err(SchemaError::new(
+            attr.position,
+            "`@since` can only be used together with `@deprecated`",
+        )),
+        (d, None) => Ok(d),
+    }
+}
+
+// Whether a type uses any generic params
+struct UsesParams(bool);
+
+impl GenericsVisitor for UsesParams {
+    fn param(&mut self, _: &mut TGeneric) -> anyhow::Result<()> {
+        self.0 = true;
+        Ok(())
+    }
+}
+
+// Comma separated list of languages, e.g. `@only("rust, flow")`
+fn target_languages(attr: &Attr) -> Result<Vec<TargetLanguage>, SchemaError> {
+    attr_value(attr)?
+        .split(',')
+        .map(|language| match language.trim() {
+            "rust" => Ok(TargetLanguage::Rust),
+            "hack" => Ok(TargetLanguage::Hack),
+            "flow" => Ok(TargetLanguage::Flow),
+            other => Err(SchemaError::new(
+                attr.position,
+                format!(
+                    "unknown language `{}`, expected `rust`, `hack` or `flow`",
+                    other
+                ),
+            )),
+        })
+        .collect()
+}
+
+// Every attribute but `@rust` can only be used once
+fn check_repeated(attrs: &[Attr]) -> Result<(), SchemaError> {
+    for (i, attr) in attrs.iter().enumerate() {
+        if attr.name != "rust" && attrs[..i].iter().any(|a| a.name == attr.name) {
+            return Err(SchemaError::new(
+                attr.position,
+                format!("`@{}` is used more than once", attr.name),
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn unknown_attr(attr: &Attr) -> SchemaError {
+    SchemaError::new(attr.position, format!("unknown attribute `@{}`", attr.name))
+}
+
+struct Scope<'a> {
+    declared: &'a BTreeMap<String, (usize, usize)>,
+    generics: BTreeSet<String>,
+}
+
+impl<'a> Scope<'a> {
+    fn define_generics(&mut self, generics: Vec<GenericDef>) -> Result<Vec<TGeneric>, SchemaError> {
+        let mut result = vec![];
+        let mut after_default = None;
+
+        for g in generics {
+            let mut hack_bounds = None;
+            let mut flow_bounds = None;
+            for attr in &g.attrs {
+                match attr.name.as_str() {
+                    "hack" => hack_bounds = Some(attr_value(attr)?),
+                    "flow" => flow_bounds = Some(attr_value(attr)?),
+                    _ => return Err(unknown_attr(attr)),
+                }
+            }
+
+            let default = match &g.default {
+                Some(ty) => {
+                    let mut default = self.primitive(ty)?;
+                    let mut uses_params = UsesParams(false);
+                    walk_primitive(&mut uses_params, &mut default)
+                        .expect("finding generic params never fails");
+                    if uses_params.0 {
+                        return Err(SchemaError::new(
+                            ty.position,
+                            format!(
+                                "default of generic param `{}` can't use other generic params",
+                                g.name
+                            ),
+                        ));
+                    }
+                    after_default = Some(g.name.clone());
+                    Some(Box::new(default))
+                }
+                None => {
+                    if let Some(previous) = &after_default {
+                        return Err(SchemaError::new(
+                            g.position,
+                            format!(
+                                "generic param `{}` needs a default because it goes after `{}` that has one",
+                                g.name, previous
+                            ),
+                        ));
+                    }
+                    None
+                }
+            };
+
+            if !self.generics.insert(g.name.clone()) {
+                return Err(SchemaError::new(
+                    g.position,
+                    format!("generic param `{}` is defined more than once", g.name),
+                ));
+            }
+            result.push(TGeneric::TDefinition {
+                name: g.name.into(),
+                bounds: g.bounds.map(Into::into),
+                hack_bounds,
+                flow_bounds,
+                default,
+            });
+        }
+
+        Ok(result)
+    }
+
+    fn gen_struct(&self, fields: Vec<Field>) -> Result<TStruct, SchemaError> {
+        let mut result = vec![];
+
+        for field in fields {
+            let mut field_type = self.field_type(&field.ty)?;
+            if field.optional {
+                field_type = match field_type {
+                    StructFieldType::TMap(m) => StructFieldType::TOption(TOption::TMap(m)),
+                    StructFieldType::TSet(s) => StructFieldType::TOption(TOption::TSet(s)),
+                    StructFieldType::TPrimitive(p) => {
+                        StructFieldType::TOption(TOption::TPrimitive(p))
+                    }
+                    StructFieldType::TTuple(t) => StructFieldType::TOption(TOption::TTuple(t)),
+                    StructFieldType::TVec(v) => StructFieldType::TOption(TOption::TVec(v)),
+                    StructFieldType::TOption(_) => {
+                        return Err(SchemaError::new(
+                            field.position,
+                            format!("field `{}` is already optional", field.name),
+                        ))
+                    }
+                };
+            }
+
+            let mut config = vec![];
+            check_repeated(&field.attrs)?;
+            if let Some(d) = deprecation(&field.attrs)? {
+                config.push(StructFieldConfig::Deprecated(d));
+            }
+            for attr in field.attrs {
+                match attr.name.as_str() {
+                    "deprecated" | "since" => {}
+                    "rust" => config.push(StructFieldConfig::RustAttribute(attr_value(&attr)?)),
+                    "rust_override" => {
+                        config.push(StructFieldConfig::RustOverride(attr_value(&attr)?))
+                    }
+                    "rust_box" => config.push(flag(&attr, StructFieldConfig::RustBox)?),
+                    "flatten" => config.push(flag(&attr, StructFieldConfig::Flatten)?),
+                    "min_length" => config.push(StructFieldConfig::Constraint(
+                        FieldConstraint::MinLength(attr_number(&attr)?),
+                    )),
+                    "max_length" => config.push(StructFieldConfig::Constraint(
+                        FieldConstraint::MaxLength(attr_number(&attr)?),
+                    )),
+                    "non_empty" => config.push(StructFieldConfig::Constraint(flag(
+                        &attr,
+                        FieldConstraint::MinLength(1),
+                    )?)),
+                    "pattern" => config.push(StructFieldConfig::Constraint(
+                        FieldConstraint::Pattern(attr_value(&attr)?),
+                    )),
+                    "min" => config.push(StructFieldConfig::Constraint(FieldConstraint::Minimum(
+                        attr_number(&attr)?,
+                    ))),
+                    "max" => config.push(StructFieldConfig::Constraint(FieldConstraint::Maximum(
+                        attr_number(&attr)?,
+                    ))),
+                    _ => return Err(unknown_attr(&attr)),
+                }
+            }
+
+            result.push(StructField {
+                name: field.name.into(),
+                docs: field.docs.into(),
+                field_type,
+                config,
+            });
+        }
+
+        Ok(TStruct { fields: result })
+    }
+
+    fn declaration_value(&self, ty: &Ty) -> Result<DeclarationValue, SchemaError> {
+        Ok(match self.builtin(ty)? {
+            Some(("vec", [inner])) => {
+                DeclarationValue::TVec(Box::new(TVec::TPrimitive(self.primitive(inner)?)))
+            }
+            Some(("option", [inner])) => DeclarationValue::TOption(Box::new(self.option(inner)?)),
+            Some(("map", [k, v])) => {
+                DeclarationValue::TMap(Box::new(self.map(k, v, TMapType::BTree)?))
+            }
+            Some(("hash_map", [k, v])) => {
+                DeclarationValue::TMap(Box::new(self.map(k, v, TMapType::Hash)?))
+            }
+            Some(("set", _)) => {
+                return Err(SchemaError::new(
+                    ty.position,
+                    "sets can only be used as field, option or map value types",
+                ))
+            }
+            _ => match &ty.kind {
+                TyKind::Tuple(items) => DeclarationValue::TTuple(self.tuple(items)?),
+                _ => DeclarationValue::TPrimitive(self.primitive(ty)?),
+            },
+        })
+    }
+
+    fn field_type(&self, ty: &Ty) -> Result<StructFieldType, SchemaError> {
+        Ok(match self.builtin(ty)? {
+            Some(("vec", [inner])) => {
+                StructFieldType::TVec(TVec::TPrimitive(self.primitive(inner)?))
+            }
+            Some(("set", [inner])) => {
+                StructFieldType::TSet(TSet::TPrimitive(self.primitive(inner)?))
+            }
+            Some(("option", [inner])) => StructFieldType::TOption(self.option(inner)?),
+            Some(("map", [k, v])) => StructFieldType::TMap(self.map(k, v, TMapType::BTree)?),
+            Some(("hash_map", [k, v])) => StructFieldType::TMap(self.map(k, v, TMapType::Hash)?),
+            _ => match &ty.kind {
+                TyKind::Tuple(items) => StructFieldType::TTuple(self.tuple(items)?),
+                _ => StructFieldType::TPrimitive(self.primitive(ty)?),
+            },
+        })
+    }
+
+    fn option(&self, ty: &Ty) -> Result<TOption, SchemaError> {
+        Ok(match self.builtin(ty)? {
+            Some(("vec", [inner])) => TOption::TVec(TVec::TPrimitive(self.primitive(inner)?)),
+            Some(("set", [inner])) => TOption::TSet(TSet::TPrimitive(self.primitive(inner)?)),
+            Some(("map", [k, v])) => TOption::TMap(self.map(k, v, TMapType::BTree)?),
+            Some(("hash_map", [k, v])) => TOption::TMap(self.map(k, v, TMapType::Hash)?),
+            _ => match &ty.kind {
+                TyKind::Tuple(items) => TOption::TTuple(self.tuple(items)?),
+                _ => TOption::TPrimitive(self.primitive(ty)?),
+            },
+        })
+    }
+
+    fn map(&self, key: &Ty, value: &Ty, t: TMapType) -> Result<TMap, SchemaError> {
+        let value = match self.builtin(value)? {
+            Some(("set", [inner])) => TMapValue::TSet(TSet::TPrimitive(self.primitive(inner)?)),
+            _ => TMapValue::TPrimitive(self.primitive(value)?),
+        };
+
+        Ok(TMap {
+            key: self.primitive(key)?,
+            value,
+            t,
+        })
+    }
+
+    fn tuple(&self, items: &[Ty]) -> Result<TTuple, SchemaError> {
+        let mut result = vec![];
+        for item in items {
+            result.push(match self.builtin(item)? {
+                Some(("option", [inner])) => TupleItem::TOption(self.option(inner)?),
+                _ => TupleItem::TPrimitive(self.primitive(item)?),
+            });
+        }
+        Ok(TTuple { items: result })
+    }
+
+    fn primitive(&self, ty: &Ty) -> Result<TPrimitive, SchemaError> {
+        match self.builtin(ty)? {
+            Some(("vec", [inner])) => {
+                return Ok(TPrimitive::TVec(Box::new(TVec::TPrimitive(
+                    self.primitive(inner)?,
+                ))))
+            }
+            Some(("option", [inner])) => {
+                return Ok(TPrimitive::TOption(Box::new(self.option(inner)?)))
+            }
+            Some(("map", [k, v])) => {
+                return Ok(TPrimitive::TMap(Box::new(self.map(
+                    k,
+                    v,
+                    TMapType::BTree,
+                )?)))
+            }
+            Some(("hash_map", [k, v])) => {
+                return Ok(TPrimitive::TMap(Box::new(self.map(
+                    k,
+                    v,
+                    TMapType::Hash,
+                )?)))
+            }
+            Some(("set", _)) => {
+                return Err(SchemaError::new(
+                    ty.position,
+                    "sets can only be used as field, option or map value types",
+                ))
+            }
+            Some((name, _)) => {
+                return Ok(match name {
+                    "string" => TPrimitive::String,
+                    "i64" => TPrimitive::Ti64,
+                    "f64" => TPrimitive::Tf64,
+                    "i32" => TPrimitive::Ti32,
+                    "u32" => TPrimitive::Tu32,
+                    "usize" => TPrimitive::Tusize,
+                    "bool" => TPrimitive::Tbool,
+                    _ => unreachable!("all builtins are handled"),
+                })
+            }
+            None => {}
+        }
+
+        match &ty.kind {
+            TyKind::Raw(raw) => Ok(TPrimitive::THardcoded(raw.clone().into())),
+            TyKind::PerLanguage { rust, hack, flow } => Ok(TPrimitive::TDifferentPerLanguage {
+                rust: Box::new(self.primitive(rust)?),
+                hack: Box::new(self.primitive(hack)?),
+                flow: Box::new(self.primitive(flow)?),
+            }),
+            TyKind::Tuple(_) => Err(SchemaError::new(
+                ty.position,
+                "tuples can only be used as declaration, field or option types",
+            )),
+            TyKind::Named { .. } => self.generic(ty).map(|g| match g {
+                TGeneric::TReference(r) => TPrimitive::TReference(r),
+                g => TPrimitive::TGeneric(g),
+            }),
+        }
+    }
+
+    fn generic(&self, ty: &Ty) -> Result<TGeneric, SchemaError> {
+        let (name, args) = match &ty.kind {
+            TyKind::Named { name, args } if self.builtin(ty)?.is_none() => (name, args),
+            // builtins, raw and per language types
+            _ => return self.primitive(ty).map(TGeneric::from),
+        };
+
+        if self.generics.contains(name) {
+            if !args.is_empty() {
+                return Err(SchemaError::new(
+                    ty.position,
+                    format!("generic param `{}` can't have generic arguments", name),
+                ));
+            }
+            return Ok(TGeneric::param(name.clone()));
+        }
+
+        let (required, total) = match self.declared.get(name) {
+            Some(arity) => *arity,
+            None => {
+                return Err(SchemaError::new(
+                    ty.position,
+                    format!("unknown type `{}`", name),
+                ))
+            }
+        };
+        if args.len() < required || args.len() > total {
+            let expected = if required == total {
+                required.to_string()
+            } else {
+                format!("{} to {}", required, total)
+            };
+            return Err(SchemaError::new(
+                ty.position,
+                format!(
+                    "`{}` expects {} generic argument(s), found {}",
+                    name,
+                    expected,
+                    args.len()
+                ),
+            ));
+        }
+
+        let mut reference = TReference::new(name.clone());
+        for arg in args {
+            reference.generic_params.push(self.generic(arg)?);
+        }
+        Ok(TGeneric::TReference(reference))
+    }
+
+    // Returns the name and arguments if `ty` is one of the builtin types,
+    // making sure it has the right number of arguments.
+    fn builtin<'t>(&self, ty: &'t Ty) -> Result<Option<(&'t str, &'t [Ty])>, SchemaError> {
+        let (name, args) = match &ty.kind {
+            TyKind::Named { name, args } => (name.as_str(), args.as_slice()),
+            _ => return Ok(None),
+        };
+
+        // declarations and generic params can shadow builtins
+        if self.generics.contains(name) || self.declared.contains_key(name) {
+            return Ok(None);
+        }
+
+        let arity = match name {
+            "string" | "i64" | "f64" | "i32" | "u32" | "usize" | "bool" => 0,
+            "vec" | "set" | "option" => 1,
+            "map" | "hash_map" => 2,
+            _ => return Ok(None),
+        };
+
+        if args.len() != arity {
+            return Err(SchemaError::new(
+                ty.position,
+                format!(
+                    "`{}` expects {} generic argument(s), found {}",
+                    name,
+                    arity,
+                    args.len()
+                ),
+            ));
+        }
+
+        Ok(Some((name, args)))
+    }
+}
@@ -0,0 +1,56 @@
+/*!
+Per language filtering of declarations (see `TypeDeclarationConfig::Targets`)
+and validation of references between them.
+*/
+
+use crate::definitions::*;
+use crate::graph::DependencyGraph;
+use anyhow::{bail, Result};
+
+/// Declarations that are generated for the given language. Fails if any of
+/// them references a declaration that is filtered out, or, in Rust, a less
+/// visible one.
+pub(crate) fn for_target(
+    declarations: &Declarations,
+    language: TargetLanguage,
+) -> Result<Declarations> {
+    let graph = DependencyGraph::new(declarations);
+
+    for declaration in &declarations.declarations {
+        if !declaration.is_generated_for(language) {
+            continue;
+        }
+
+        for (name, _) in graph.dependencies(&declaration.name) {
+            let dependency = match declarations.declarations.iter().find(|d| d.name == name) {
+                Some(dependency) => dependency,
+                None => continue,
+            };
+
+            if !dependency.is_generated_for(language) {
+                bail!(
+                    "`{}` references `{}` that is not generated for {:?}",
+                    declaration.name,
+                    dependency.name,
+                    language
+                );
+            }
+
+            if language == TargetLanguage::Rust
+                && dependency.rust_visibility() < declaration.rust_visibility()
+            {
+                bail!(
+                    "`{}{}` references less visible `{}{}`",
+                    declaration.rust_visibility().prefix(),
+                    declaration.name,
+                    dependency.rust_visibility().prefix(),
+                    dependency.name
+                );
+            }
+        }
+    }
+
+    let mut result = declarations.clone();
+    result.declarations.retain(|d| d.is_generated_for(language));
+    Ok(result)
+}
@@ -1,8 +1,938 @@
-#[proc_macro_derive(EnumSerialization)]
-pub fn my_derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let input = proc_macro2::TokenStream::from(input);
+//! `#[derive(Gull)]` builds a `gull` `TypeDeclaration` out of an existing
+//! rust struct or enum, so that rust types can be the source of truth for the
+//! types generated into other languages.
+//!
+//! Field types, doc comments, `#[deprecated]`, generic params and the serde
+//! attributes that change the JSON shape (`rename`, `rename_all`, `skip`,
+//! `flatten`) are carried over, as are the discriminants of enums with an
+//! integer `#[repr]`, which are expected to use `serde_repr`.
+//! Anything that can't be represented in gull (e.g. internally tagged enums or
+//! unit variants mixed with data variants) is reported as a compile error.
 
-    // let output: proc_macro2::TokenStream = { /* transform input */ };
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{
+    parse_macro_input, Attribute, Data, DataEnum, DeriveInput, Expr, ExprLit, ExprUnary, Fields,
+    FieldsNamed, GenericArgument, Generics, Lit, Meta, MetaNameValue, NestedMeta, PathArguments,
+    Type, UnOp,
+};
 
-    proc_macro::TokenStream::from(input)
+#[proc_macro_derive(Gull, attributes(serde))]
+pub fn derive_gull(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand(&input)
+        .unwrap_or_else(|e| e.to_compile_error())
+        .into()
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let serde = SerdeAttrs::from_attrs(&input.attrs)?;
+
+    if serde.tag.is_some() || serde.untagged {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "gull only supports externally tagged enums (serde default representation)",
+        ));
+    }
+
+    let ident = &input.ident;
+    let name = serde.rename.clone().unwrap_or_else(|| ident.to_string());
+    let docs = docs(&input.attrs);
+    let config = deprecation(&input.attrs)?
+        .map(|d| quote!(::gull::prelude::TypeDeclarationConfig::Deprecated(#d)))
+        .into_iter();
+    let ctx = Context {
+        generics: generic_names(&input.generics),
+    };
+    let generic_params = generic_param_definitions(&input.generics, &ctx)?;
+
+    let value = match &input.data {
+        Data::Struct(s) => match &s.fields {
+            Fields::Named(fields) => {
+                let s = ctx.gen_struct(fields, serde.rename_all)?;
+                quote!(::gull::prelude::DeclarationValue::TStruct(#s))
+            }
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                // newtype structs serialize as their inner value
+                let inner = &fields.unnamed[0].ty;
+                ctx.gen_declaration_value(inner)?
+            }
+            Fields::Unnamed(fields) => {
+                let items = fields
+                    .unnamed
+                    .iter()
+                    .map(|f| ctx.gen_tuple_item(&f.ty))
+                    .collect::<syn::Result<Vec<_>>>()?;
+                quote!(::gull::prelude::DeclarationValue::TTuple(
+                    ::gull::prelude::TTuple {
+                        items: vec![#(#items),*]
+                    }
+                ))
+            }
+            Fields::Unit => {
+                return Err(syn::Error::new_spanned(
+                    ident,
+                    "unit structs can't be represented in gull",
+                ))
+            }
+        },
+        Data::Enum(e) => ctx.gen_enum(e, serde.rename_all, has_integer_repr(&input.attrs))?,
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                ident,
+                "unions can't be represented in gull",
+            ))
+        }
+    };
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        // the impl names the type, which warns if the type itself is deprecated
+        #[allow(deprecated)]
+        impl #impl_generics ::gull::prelude::Gull for #ident #ty_generics #where_clause {
+            fn type_declaration() -> ::gull::prelude::TypeDeclaration {
+                ::gull::prelude::TypeDeclaration {
+                    name: #name.into(),
+                    docs: #docs.into(),
+                    config: vec![#(#config),*],
+                    generic_params: vec![#(#generic_params),*],
+                    value: #value,
+                }
+            }
+        }
+    })
+}
+
+struct Context {
+    // names of generic type params defined on the derived item
+    generics: Vec<String>,
+}
+
+impl Context {
+    fn gen_struct(
+        &self,
+        fields: &FieldsNamed,
+        rename_all: Option<RenameRule>,
+    ) -> syn::Result<TokenStream> {
+        let mut result = vec![];
+
+        for field in &fields.named {
+            let serde = SerdeAttrs::from_attrs(&field.attrs)?;
+            if serde.skip {
+                continue;
+            }
+
+            let ident = field
+                .ident
+                .as_ref()
+                .expect("named fields always have idents");
+            let name = serde.rename.unwrap_or_else(|| {
+                let name = ident.to_string();
+                let name = name.trim_start_matches("r#");
+                rename_all.map_or(name.to_string(), |r| r.apply_to_field(name))
+            });
+            let docs = docs(&field.attrs);
+            let field_type = self.gen_field_type(&field.ty)?;
+            let mut config = deprecation(&field.attrs)?
+                .map(|d| quote!(::gull::prelude::StructFieldConfig::Deprecated(#d)))
+                .into_iter()
+                .collect::<Vec<_>>();
+            if serde.flatten {
+                config.push(quote!(::gull::prelude::StructFieldConfig::Flatten));
+            }
+
+            result.push(quote! {
+                ::gull::prelude::StructField {
+                    name: #name.into(),
+                    docs: #docs.into(),
+                    field_type: #field_type,
+                    config: vec![#(#config),*],
+                }
+            });
+        }
+
+        Ok(quote!(::gull::prelude::TStruct {
+            fields: vec![#(#result),*]
+        }))
+    }
+
+    fn gen_enum(
+        &self,
+        e: &DataEnum,
+        rename_all: Option<RenameRule>,
+        serde_repr: bool,
+    ) -> syn::Result<TokenStream> {
+        let variant_name = |v: &syn::Variant, serde: &SerdeAttrs| {
+            serde.rename.clone().unwrap_or_else(|| {
+                let name = v.ident.to_string();
+                rename_all.map_or(name.clone(), |r| r.apply_to_variant(&name))
+            })
+        };
+
+        let all_unit = e.variants.iter().all(|v| matches!(v.fields, Fields::Unit));
+
+        if all_unit {
+            let mut variants = vec![];
+            // serde_repr enums serialize to the discriminant, which
+            // continues from the previous variant if it isn't explicit
+            let mut next_discriminant = 0;
+            for v in &e.variants {
+                let discriminant = match &v.discriminant {
+                    Some((_, expr)) => discriminant_value(expr)?,
+                    None => next_discriminant,
+                };
+                next_discriminant = discriminant.wrapping_add(1);
+                let discriminant = if serde_repr {
+                    quote!(Some(#discriminant))
+                } else {
+                    quote!(None)
+                };
+
+                let serde = SerdeAttrs::from_attrs(&v.attrs)?;
+                if serde.skip {
+                    continue;
+                }
+
+                let name = v.ident.to_string();
+                let value = variant_name(v, &serde);
+                let docs = docs(&v.attrs);
+                let config = deprecation(&v.attrs)?
+                    .map(|d| quote!(::gull::prelude::EnumVariantConfig::Deprecated(#d)))
+                    .into_iter();
+                variants.push(quote! {
+                    ::gull::prelude::SimpleEnumVariant {
+                        name: #name.into(),
+                        value: #value.into(),
+                        docs: #docs.into(),
+                        config: vec![#(#config),*],
+                        discriminant: #discriminant,
+                    }
+                });
+            }
+
+            return Ok(quote! {
+                ::gull::prelude::DeclarationValue::TSimpleEnum(
+                    ::gull::prelude::TSimpleEnum { variants: vec![#(#variants),*] }
+                )
+            });
+        }
+
+        let mut variants = vec![];
+
+        for v in &e.variants {
+            let serde = SerdeAttrs::from_attrs(&v.attrs)?;
+            if serde.skip {
+                continue;
+            }
+
+            let name = variant_name(v, &serde);
+            let docs = docs(&v.attrs);
+            let config = deprecation(&v.attrs)?
+                .map(|d| quote!(::gull::prelude::EnumVariantConfig::Deprecated(#d)))
+                .into_iter();
+
+            let variant_type = match &v.fields {
+                Fields::Named(fields) => {
+                    let s = self.gen_struct(fields, serde.rename_all)?;
+                    quote!(::gull::prelude::EnumVariantType::TStruct(#s))
+                }
+                Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                    let p = self.gen_primitive(&fields.unnamed[0].ty)?;
+                    quote!(::gull::prelude::EnumVariantType::TPrimitive(#p))
+                }
+                Fields::Unnamed(_) => {
+                    return Err(syn::Error::new_spanned(
+                        v,
+                        "tuple variants with more than one field can't be represented in gull",
+                    ))
+                }
+                Fields::Unit => {
+                    return Err(syn::Error::new_spanned(
+                        v,
+                        "unit variants can't be mixed with data variants. \
+                         They serialize as plain strings which hack and flow can't \
+                         discriminate from the object shaped variants. \
+                         Use a variant with a bool payload instead",
+                    ))
+                }
+            };
+
+            variants.push(quote! {
+                ::gull::prelude::EnumVariant {
+                    name: #name.into(),
+                    docs: #docs.into(),
+                    variant_type: #variant_type,
+                    config: vec![#(#config),*],
+                }
+            });
+        }
+
+        Ok(quote! {
+            ::gull::prelude::DeclarationValue::TEnum(
+                ::gull::prelude::TEnum { variants: vec![#(#variants),*] }
+            )
+        })
+    }
+
+    // Newtype structs become type aliases of whatever they wrap
+    fn gen_declaration_value(&self, ty: &Type) -> syn::Result<TokenStream> {
+        Ok(match container(ty) {
+            Some(Container::Option(inner)) => {
+                let o = self.gen_option(inner)?;
+                quote!(::gull::prelude::DeclarationValue::TOption(Box::new(#o)))
+            }
+            Some(Container::Vec(inner)) => {
+                let p = self.gen_primitive(inner)?;
+                quote!(::gull::prelude::DeclarationValue::TVec(Box::new(
+                    ::gull::prelude::TVec::TPrimitive(#p)
+                )))
+            }
+            Some(Container::Map(k, v, t)) => {
+                let m = self.gen_map(k, v, t)?;
+                quote!(::gull::prelude::DeclarationValue::TMap(Box::new(#m)))
+            }
+            Some(Container::Tuple(items)) => {
+                let t = self.gen_tuple(&items)?;
+                quote!(::gull::prelude::DeclarationValue::TTuple(#t))
+            }
+            Some(Container::Set(_)) => {
+                return Err(syn::Error::new_spanned(
+                    ty,
+                    "newtype structs over sets can't be represented in gull",
+                ))
+            }
+            None => {
+                let p = self.gen_primitive(ty)?;
+                quote!(::gull::prelude::DeclarationValue::TPrimitive(#p))
+            }
+        })
+    }
+
+    fn gen_field_type(&self, ty: &Type) -> syn::Result<TokenStream> {
+        Ok(match container(ty) {
+            Some(Container::Option(inner)) => {
+                let o = self.gen_option(inner)?;
+                quote!(::gull::prelude::StructFieldType::TOption(#o))
+            }
+            Some(Container::Vec(inner)) => {
+                let p = self.gen_primitive(inner)?;
+                quote!(::gull::prelude::StructFieldType::TVec(::gull::prelude::TVec::TPrimitive(#p)))
+            }
+            Some(Container::Set(inner)) => {
+                let p = self.gen_primitive(inner)?;
+                quote!(::gull::prelude::StructFieldType::TSet(::gull::prelude::TSet::TPrimitive(#p)))
+            }
+            Some(Container::Map(k, v, t)) => {
+                let m = self.gen_map(k, v, t)?;
+                quote!(::gull::prelude::StructFieldType::TMap(#m))
+            }
+            Some(Container::Tuple(items)) => {
+                let t = self.gen_tuple(&items)?;
+                quote!(::gull::prelude::StructFieldType::TTuple(#t))
+            }
+            None => {
+                let p = self.gen_primitive(ty)?;
+                quote!(::gull::prelude::StructFieldType::TPrimitive(#p))
+            }
+        })
+    }
+
+    fn gen_option(&self, ty: &Type) -> syn::Result<TokenStream> {
+        Ok(match container(ty) {
+            Some(Container::Vec(inner)) => {
+                let p = self.gen_primitive(inner)?;
+                quote!(::gull::prelude::TOption::TVec(::gull::prelude::TVec::TPrimitive(#p)))
+            }
+            Some(Container::Set(inner)) => {
+                let p = self.gen_primitive(inner)?;
+                quote!(::gull::prelude::TOption::TSet(::gull::prelude::TSet::TPrimitive(#p)))
+            }
+            Some(Container::Map(k, v, t)) => {
+                let m = self.gen_map(k, v, t)?;
+                quote!(::gull::prelude::TOption::TMap(#m))
+            }
+            Some(Container::Tuple(items)) => {
+                let t = self.gen_tuple(&items)?;
+                quote!(::gull::prelude::TOption::TTuple(#t))
+            }
+            Some(Container::Option(_)) | None => {
+                let p = self.gen_primitive(ty)?;
+                quote!(::gull::prelude::TOption::TPrimitive(#p))
+            }
+        })
+    }
+
+    fn gen_map(&self, key: &Type, value: &Type, t: MapType) -> syn::Result<TokenStream> {
+        let key = self.gen_primitive(key)?;
+        let value = match container(value) {
+            Some(Container::Set(inner)) => {
+                let p = self.gen_primitive(inner)?;
+                quote!(::gull::prelude::TMapValue::TSet(::gull::prelude::TSet::TPrimitive(#p)))
+            }
+            _ => {
+                let p = self.gen_primitive(value)?;
+                quote!(::gull::prelude::TMapValue::TPrimitive(#p))
+            }
+        };
+        let t = match t {
+            MapType::Hash => quote!(::gull::prelude::TMapType::Hash),
+            MapType::BTree => quote!(::gull::prelude::TMapType::BTree),
+        };
+
+        Ok(quote!(::gull::prelude::TMap { key: #key, value: #value, t: #t }))
+    }
+
+    fn gen_tuple(&self, items: &[&Type]) -> syn::Result<TokenStream> {
+        let items = items
+            .iter()
+            .map(|ty| self.gen_tuple_item(ty))
+            .collect::<syn::Result<Vec<_>>>()?;
+        Ok(quote!(::gull::prelude::TTuple {
+            items: vec![#(#items),*]
+        }))
+    }
+
+    fn gen_tuple_item(&self, ty: &Type) -> syn::Result<TokenStream> {
+        Ok(match container(ty) {
+            Some(Container::Option(inner)) => {
+                let o = self.gen_option(inner)?;
+                quote!(::gull::prelude::TupleItem::TOption(#o))
+            }
+            _ => {
+                let p = self.gen_primitive(ty)?;
+                quote!(::gull::prelude::TupleItem::TPrimitive(#p))
+            }
+        })
+    }
+
+    fn gen_primitive(&self, ty: &Type) -> syn::Result<TokenStream> {
+        match container(ty) {
+            Some(Container::Option(inner)) => {
+                let o = self.gen_option(inner)?;
+                return Ok(quote!(::gull::prelude::TPrimitive::TOption(Box::new(#o))));
+            }
+            Some(Container::Vec(inner)) => {
+                let p = self.gen_primitive(inner)?;
+                return Ok(quote!(::gull::prelude::TPrimitive::TVec(Box::new(
+                    ::gull::prelude::TVec::TPrimitive(#p)
+                ))));
+            }
+            Some(Container::Map(k, v, t)) => {
+                let m = self.gen_map(k, v, t)?;
+                return Ok(quote!(::gull::prelude::TPrimitive::TMap(Box::new(#m))));
+            }
+            Some(Container::Set(_)) | Some(Container::Tuple(_)) => {
+                return Err(syn::Error::new_spanned(
+                    ty,
+                    "sets and tuples can only be used directly as a field, option or map value type",
+                ));
+            }
+            None => {}
+        }
+
+        let path = match ty {
+            Type::Reference(r) => return self.gen_primitive(&r.elem),
+            Type::Group(g) => return self.gen_primitive(&g.elem),
+            Type::Paren(p) => return self.gen_primitive(&p.elem),
+            Type::Path(p) if p.qself.is_none() => &p.path,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    ty,
+                    "this type can't be represented in gull",
+                ))
+            }
+        };
+
+        let segment = path.segments.last().expect("paths are never empty");
+        let ident = segment.ident.to_string();
+
+        if path.segments.len() == 1 && self.generics.contains(&ident) {
+            return Ok(quote! {
+                ::gull::prelude::TPrimitive::TGeneric(::gull::prelude::TGeneric::param(#ident))
+            });
+        }
+
+        let primitive = match ident.as_str() {
+            "String" | "str" | "char" => quote!(String),
+            "i64" => quote!(Ti64),
+            "f64" | "f32" => quote!(Tf64),
+            "i32" | "i16" | "i8" | "u16" | "u8" => quote!(Ti32),
+            "u32" => quote!(Tu32),
+            "usize" => quote!(Tusize),
+            "bool" => quote!(Tbool),
+            // Don't fit into a Hack `int` or can't be represented exactly by a JS number
+            "u64" | "i128" | "u128" | "isize" => {
+                return Err(syn::Error::new_spanned(
+                    ty,
+                    format!("`{}` has no gull primitive type, use `i64` instead", ident),
+                ));
+            }
+            "Box" | "Rc" | "Arc" => {
+                // smart pointers are transparent for serde
+                return self.gen_primitive(first_type_arg(ty, segment)?);
+            }
+            _ => {
+                let reference = self.gen_reference(segment)?;
+                return Ok(quote!(::gull::prelude::TPrimitive::TReference(#reference)));
+            }
+        };
+
+        Ok(quote!(::gull::prelude::TPrimitive::#primitive))
+    }
+
+    fn gen_reference(&self, segment: &syn::PathSegment) -> syn::Result<TokenStream> {
+        let name = segment.ident.to_string();
+        let mut generic_params = vec![];
+
+        if let PathArguments::AngleBracketed(args) = &segment.arguments {
+            for arg in &args.args {
+                match arg {
+                    GenericArgument::Type(ty) => generic_params.push(self.gen_generic(ty)?),
+                    GenericArgument::Lifetime(_) => {}
+                    _ => {
+                        return Err(syn::Error::new_spanned(
+                            arg,
+                            "only type arguments are supported in gull references",
+                        ))
+                    }
+                }
+            }
+        }
+
+        Ok(quote! {{
+            let mut reference = ::gull::prelude::TReference::new(#name);
+            reference.generic_params = vec![#(#generic_params),*];
+            reference
+        }})
+    }
+
+    fn gen_generic(&self, ty: &Type) -> syn::Result<TokenStream> {
+        let p = self.gen_primitive(ty)?;
+        Ok(quote!(::gull::prelude::TGeneric::from(#p)))
+    }
+}
+
+#[derive(Clone, Copy)]
+enum MapType {
+    Hash,
+    BTree,
+}
+
+enum Container<'a> {
+    Option(&'a Type),
+    Vec(&'a Type),
+    Set(&'a Type),
+    Map(&'a Type, &'a Type, MapType),
+    Tuple(Vec<&'a Type>),
+}
+
+// Recognize the std collection types that have their own representation in gull
+fn container(ty: &Type) -> Option<Container<'_>> {
+    let path = match ty {
+        Type::Tuple(t) if !t.elems.is_empty() => {
+            return Some(Container::Tuple(t.elems.iter().collect()))
+        }
+        Type::Path(p) if p.qself.is_none() => &p.path,
+        _ => return None,
+    };
+
+    let segment = path.segments.last()?;
+    let args = match &segment.arguments {
+        PathArguments::AngleBracketed(args) => args
+            .args
+            .iter()
+            .filter_map(|a| match a {
+                GenericArgument::Type(t) => Some(t),
+                _ => None,
+            })
+            .collect::<Vec<_>>(),
+        _ => return None,
+    };
+
+    match (segment.ident.to_string().as_str(), args.as_slice()) {
+        ("Option", [inner]) => Some(Container::Option(inner)),
+        ("Vec", [inner]) | ("VecDeque", [inner]) => Some(Container::Vec(inner)),
+        ("BTreeSet", [inner]) | ("HashSet", [inner]) => Some(Container::Set(inner)),
+        ("BTreeMap", [k, v]) => Some(Container::Map(k, v, MapType::BTree)),
+        ("HashMap", [k, v]) => Some(Container::Map(k, v, MapType::Hash)),
+        _ => None,
+    }
+}
+
+fn first_type_arg<'a>(ty: &Type, segment: &'a syn::PathSegment) -> syn::Result<&'a Type> {
+    if let PathArguments::AngleBracketed(args) = &segment.arguments {
+        for arg in &args.args {
+            if let GenericArgument::Type(t) = arg {
+                return Ok(t);
+            }
+        }
+    }
+    Err(syn::Error::new_spanned(ty, "expected a type argument"))
+}
+
+fn generic_names(generics: &Generics) -> Vec<String> {
+    generics
+        .type_params()
+        .map(|p| p.ident.to_string())
+        .collect()
+}
+
+fn generic_param_definitions(generics: &Generics, ctx: &Context) -> syn::Result<Vec<TokenStream>> {
+    generics
+        .type_params()
+        .map(|p| {
+            let name = p.ident.to_string();
+            let bounds = if p.bounds.is_empty() {
+                quote!(None)
+            } else {
+                let bounds = &p.bounds;
+                let bounds = quote!(#bounds).to_string();
+                quote!(Some(#bounds.into()))
+            };
+            let default = match &p.default {
+                Some(ty) => {
+                    let default = ctx.gen_primitive(ty)?;
+                    quote!(Some(Box::new(#default)))
+                }
+                None => quote!(None),
+            };
+            Ok(quote!(::gull::prelude::TGeneric::TDefinition {
+                name: #name.into(),
+                bounds: #bounds,
+                hack_bounds: None,
+                flow_bounds: None,
+                default: #default,
+            }))
+        })
+        .collect()
+}
+
+// Collect `///` doc comments (which are `#[doc = "..."]` attributes) into
+// a single string.
+fn docs(attrs: &[Attribute]) -> String {
+    let mut lines = vec![];
+    for attr in attrs {
+        if !attr.path.is_ident("doc") {
+            continue;
+        }
+        if let Ok(Meta::NameValue(nv)) = attr.parse_meta() {
+            if let Lit::Str(s) = nv.lit {
+                let line = s.value();
+                lines.push(line.strip_prefix(' ').unwrap_or(&line).to_string());
+            }
+        }
+    }
+    lines.join("\n")
+}
+
+// Enums with an integer `#[repr]` are expected to be (de)serialized with
+// `serde_repr`. The `#[derive]` itself is already expanded and can't be seen
+fn has_integer_repr(attrs: &[Attribute]) -> bool {
+    const INTEGERS: &[&str] = &[
+        "i8", "i16", "i32", "i64", "isize", "u8", "u16", "u32", "u64", "usize",
+    ];
+    attrs
+        .iter()
+        .filter(|a| a.path.is_ident("repr"))
+        .filter_map(|a| match a.parse_meta() {
+            Ok(Meta::List(list)) => Some(list.nested),
+            _ => None,
+        })
+        .flatten()
+        .any(|nested| match nested {
+            NestedMeta::Meta(Meta::Path(path)) => {
+                INTEGERS.iter().any(|integer| path.is_ident(integer))
+            }
+            _ => false,
+        })
+}
+
+// Explicit `= N` discriminant of an enum variant
+fn discriminant_value(expr: &Expr) -> syn::Result<i32> {
+    match expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::Int(i), ..
+        }) => i.base10_parse(),
+        Expr::Unary(ExprUnary {
+            op: UnOp::Neg(_),
+            expr,
+            ..
+        }) => Ok(-discriminant_value(expr)?),
+        Expr::Paren(p) => discriminant_value(&p.expr),
+        _ => Err(syn::Error::new_spanned(
+            expr,
+            "only integer literal discriminants can be represented in gull",
+        )),
+    }
+}
+
+// `#[deprecated]`, `#[deprecated = "note"]` or
+// `#[deprecated(since = "version", note = "note")]` as a gull `Deprecation`
+fn deprecation(attrs: &[Attribute]) -> syn::Result<Option<TokenStream>> {
+    let attr = match attrs.iter().find(|a| a.path.is_ident("deprecated")) {
+        Some(attr) => attr,
+        None => return Ok(None),
+    };
+
+    let mut note = None;
+    let mut since = None;
+    match attr.parse_meta()? {
+        Meta::Path(_) => {}
+        Meta::NameValue(MetaNameValue {
+            lit: Lit::Str(s), ..
+        }) => note = Some(s.value()),
+        Meta::List(list) => {
+            for nested in list.nested {
+                match nested {
+                    NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                        path,
+                        lit: Lit::Str(s),
+                        ..
+                    })) if path.is_ident("note") => note = Some(s.value()),
+                    NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+                        path,
+                        lit: Lit::Str(s),
+                        ..
+                    })) if path.is_ident("since") => since = Some(s.value()),
+                    other => return Err(syn::Error::new_spanned(other, "unsupported deprecation")),
+                }
+            }
+        }
+        other => return Err(syn::Error::new_spanned(other, "unsupported deprecation")),
+    }
+
+    let option = |value: Option<String>| match value {
+        Some(value) => quote!(Some(#value.into())),
+        None => quote!(None),
+    };
+    let (note, since) = (option(note), option(since));
+    Ok(Some(quote! {
+        ::gull::prelude::Deprecation {
+            message: #note,
+            since: #since,
+        }
+    }))
+}
+
+/// The subset of `#[serde(...)]` attributes that changes the JSON shape.
+#[derive(Default)]
+struct SerdeAttrs {
+    rename: Option<String>,
+    rename_all: Option<RenameRule>,
+    skip: bool,
+    flatten: bool,
+    tag: Option<String>,
+    untagged: bool,
+}
+
+impl SerdeAttrs {
+    fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut result = Self::default();
+
+        for attr in attrs {
+            if !attr.path.is_ident("serde") {
+                continue;
+            }
+
+            let list = match attr.parse_meta()? {
+                Meta::List(list) => list,
+                _ => continue,
+            };
+
+            for nested in list.nested {
+                match nested {
+                    NestedMeta::Meta(Meta::NameValue(nv)) => {
+                        let value = match &nv.lit {
+                            Lit::Str(s) => s.value(),
+                            _ => continue,
+                        };
+                        if nv.path.is_ident("rename") {
+                            result.rename = Some(value);
+                        } else if nv.path.is_ident("rename_all") {
+                            result.rename_all = Some(RenameRule::parse(&value, &nv.lit)?);
+                        } else if nv.path.is_ident("tag") {
+                            result.tag = Some(value);
+                        }
+                    }
+                    NestedMeta::Meta(Meta::Path(p)) => {
+                        if p.is_ident("skip") {
+                            result.skip = true;
+                        } else if p.is_ident("skip_serializing") || p.is_ident("skip_deserializing")
+                        {
+                            // the field is in the JSON in only one direction
+                            return Err(syn::Error::new_spanned(
+                                p,
+                                "skipping a field in only one direction can't be represented in gull",
+                            ));
+                        } else if p.is_ident("untagged") {
+                            result.untagged = true;
+                        } else if p.is_ident("flatten") {
+                            result.flatten = true;
+                        }
+                    }
+                    // e.g. #[serde(rename(serialize = "a", deserialize = "b"))]
+                    NestedMeta::Meta(Meta::List(l)) if l.path.is_ident("rename") => {
+                        return Err(syn::Error::new_spanned(
+                            l,
+                            "different serialize and deserialize names can't be represented in gull",
+                        ));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+/// Mirrors serde's `rename_all` rules.
+#[derive(Clone, Copy)]
+enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    fn parse(s: &str, lit: &Lit) -> syn::Result<Self> {
+        Ok(match s {
+            "lowercase" => Self::Lower,
+            "UPPERCASE" => Self::Upper,
+            "PascalCase" => Self::Pascal,
+            "camelCase" => Self::Camel,
+            "snake_case" => Self::Snake,
+            "SCREAMING_SNAKE_CASE" => Self::ScreamingSnake,
+            "kebab-case" => Self::Kebab,
+            "SCREAMING-KEBAB-CASE" => Self::ScreamingKebab,
+            _ => {
+                return Err(syn::Error::new(
+                    Span::call_site(),
+                    format!("unknown rename rule `{}` in {}", s, quote!(#lit)),
+                ))
+            }
+        })
+    }
+
+    // variants are expected to be PascalCase
+    fn apply_to_variant(self, variant: &str) -> String {
+        match self {
+            Self::Pascal => variant.to_string(),
+            Self::Lower => variant.to_ascii_lowercase(),
+            Self::Upper => variant.to_ascii_uppercase(),
+            Self::Camel => lowercase_first(variant),
+            Self::Snake => {
+                let mut snake = String::new();
+                for (i, ch) in variant.char_indices() {
+                    if i > 0 && ch.is_uppercase() {
+                        snake.push('_');
+                    }
+                    snake.push(ch.to_ascii_lowercase());
+                }
+                snake
+            }
+            Self::ScreamingSnake => Self::Snake.apply_to_variant(variant).to_ascii_uppercase(),
+            Self::Kebab => Self::Snake.apply_to_variant(variant).replace('_', "-"),
+            Self::ScreamingKebab => Self::ScreamingSnake
+                .apply_to_variant(variant)
+                .replace('_', "-"),
+        }
+    }
+
+    // fields are expected to be snake_case
+    fn apply_to_field(self, field: &str) -> String {
+        match self {
+            Self::Lower | Self::Snake => field.to_string(),
+            Self::Upper | Self::ScreamingSnake => field.to_ascii_uppercase(),
+            Self::Pascal => {
+                let mut pascal = String::new();
+                let mut capitalize = true;
+                for ch in field.chars() {
+                    if ch == '_' {
+                        capitalize = true;
+                    } else if capitalize {
+                        pascal.push(ch.to_ascii_uppercase());
+                        capitalize = false;
+                    } else {
+                        pascal.push(ch);
+                    }
+                }
+                pascal
+            }
+            Self::Camel => lowercase_first(&Self::Pascal.apply_to_field(field)),
+            Self::Kebab => field.replace('_', "-"),
+            Self::ScreamingKebab => Self::ScreamingSnake.apply_to_field(field).replace('_', "-"),
+        }
+    }
+}
+
+// Char wise, names can start with a multi byte character
+fn lowercase_first(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expand, RenameRule};
+
+    fn error(input: syn::DeriveInput) -> String {
+        expand(&input).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn unsupported_numbers_test() {
+        k9::snapshot!(
+            error(syn::parse_quote!(
+                struct Counter {
+                    count: u64,
+                }
+            )),
+            "`u64` has no gull primitive type, use `i64` instead"
+        );
+        k9::snapshot!(
+            error(syn::parse_quote!(
+                struct Offsets(Vec<Option<isize>>);
+            )),
+            "`isize` has no gull primitive type, use `i64` instead"
+        );
+        k9::snapshot!(
+            error(syn::parse_quote!(
+                enum Value {
+                    Big(std::collections::HashMap<String, u128>),
+                }
+            )),
+            "`u128` has no gull primitive type, use `i64` instead"
+        );
+    }
+
+    #[test]
+    fn one_way_skip_test() {
+        k9::snapshot!(
+            error(syn::parse_quote!(
+                struct Node {
+                    #[serde(skip_serializing)]
+                    cache: String,
+                }
+            )),
+            "skipping a field in only one direction can't be represented in gull"
+        );
+    }
+
+    #[test]
+    fn rename_rule_test() {
+        assert_eq!(RenameRule::Camel.apply_to_variant("ÉtatCivil"), "ÉtatCivil");
+        assert_eq!(RenameRule::Camel.apply_to_variant("Married"), "married");
+        assert_eq!(RenameRule::Camel.apply_to_field("état_civil"), "étatCivil");
+    }
 }